@@ -15,7 +15,7 @@ use crate::scheduler::*; // Modify
 use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::{SideMetadataSanity, SideMetadataContext};
@@ -152,8 +152,18 @@ impl<VM: VMBinding> Plan for MyGC<VM> {
     fn base(&self) -> &BasePlan<VM> {
         &self.common.base
     }
+
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.common.base
+    }
     // ANCHOR_END: plan_base
 
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.common.for_each_space(f);
+        f(&self.copyspace0);
+        f(&self.copyspace1);
+    }
+
     // Add
     // ANCHOR: plan_common
     fn common(&self) -> &CommonPlan<VM> {
@@ -172,7 +182,7 @@ impl<VM: VMBinding> MyGC<VM> {
         _scheduler: &'static GCWorkScheduler<VM>,
     ) -> Self {
         // Modify
-        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
 
         let res = MyGC {
@@ -187,6 +197,8 @@ impl<VM: VMBinding> MyGC<VM> {
                 vm_map,
                 mmapper,
                 &mut heap,
+                options.poison_on_release,
+                options.protect_on_release,
             ),
             // ANCHOR_END: copyspace_new
             copyspace1: CopySpace::new(
@@ -198,6 +210,8 @@ impl<VM: VMBinding> MyGC<VM> {
                 vm_map,
                 mmapper,
                 &mut heap,
+                options.poison_on_release,
+                options.protect_on_release,
             ),
             common: CommonPlan::new(vm_map, mmapper, options, heap, &MYGC_CONSTRAINTS, global_metadata_specs.clone()),
         };
@@ -4,6 +4,7 @@ use crate::Plan;
 use crate::plan::barriers::NoBarrier;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
 use crate::util::alloc::BumpAllocator;
@@ -54,6 +55,7 @@ lazy_static! {
         AllocationType::Default => AllocatorSelector::BumpPointer(0),
         AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(1),
         AllocationType::Los => AllocatorSelector::LargeObject(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 // ANCHOR_END: allocator_mapping
@@ -69,14 +71,18 @@ pub fn create_mygc_mutator<VM: VMBinding>(
         allocator_mapping: &*ALLOCATOR_MAPPING,
         // Modify
         // ANCHOR: space_mapping
-        space_mapping: box vec![
-            (AllocatorSelector::BumpPointer(0), mygc.tospace()),
-            (
-                AllocatorSelector::BumpPointer(1),
-                mygc.common.get_immortal(),
-            ),
-            (AllocatorSelector::LargeObject(0), mygc.common.get_los()),
-        ],
+        space_mapping: with_extra_spaces(
+            vec![
+                (AllocatorSelector::BumpPointer(0), mygc.tospace()),
+                (
+                    AllocatorSelector::BumpPointer(1),
+                    mygc.common.get_immortal(),
+                ),
+                (AllocatorSelector::LargeObject(0), mygc.common.get_los()),
+            ],
+            plan,
+            mygc.common.get_immortal(),
+        ),
         // ANCHOR_END: space_mapping
         prepare_func: &mygc_mutator_prepare, // Modify
         release_func: &mygc_mutator_release, // Modify
@@ -61,6 +61,7 @@ extern void* trace_retain_referent(MMTk_TraceLocal trace_local, void* obj);
 extern void gc_init(size_t heap_size);
 extern bool will_never_move(void* object);
 extern bool process(char* name, char* value);
+extern bool process_bulk(char* options);
 extern void scan_region();
 extern void handle_user_collection_request(void *tls);
 
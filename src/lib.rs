@@ -70,6 +70,8 @@ pub use mmtk::MMTK;
 pub(crate) use mmtk::VM_MAP;
 
 mod policy;
+pub use policy::space::SpaceStats;
+pub use policy::vmregion::VMRegionError;
 
 pub mod memory_manager;
 pub mod plan;
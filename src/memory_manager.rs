@@ -14,13 +14,19 @@
 use crate::mmtk::MMTK;
 use crate::plan::AllocationSemantics;
 use crate::plan::{Mutator, MutatorContext};
+use crate::policy::space::SpaceStats;
+use crate::policy::vmregion::VMRegionError;
 use crate::scheduler::WorkBucketStage;
 use crate::scheduler::{GCWork, GCWorker};
 use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::constants::{LOG_BYTES_IN_PAGE, MIN_OBJECT_SIZE};
-use crate::util::heap::layout::vm_layout_constants::HEAP_END;
-use crate::util::heap::layout::vm_layout_constants::HEAP_START;
+use crate::util::conversions;
+use crate::util::heap::layout::vm_layout_constants::{vm_layout, BYTES_IN_CHUNK};
+use crate::util::metadata::side_metadata::{self, SideMetadataStats};
 use crate::util::opaque_pointer::*;
+use crate::util::options::{OptionError, OptionInfo, PRINT_OPTIONS_ENV_VAR};
+use crate::util::GCTriggerPolicy;
+use crate::util::VMLayout;
 use crate::util::{Address, ObjectReference};
 use crate::vm::Collection;
 use crate::vm::VMBinding;
@@ -67,13 +73,45 @@ pub fn gc_init<VM: VMBinding>(mmtk: &'static mut MMTK<VM>, heap_size: usize) {
         }
     }
     assert!(heap_size > 0, "Invalid heap size");
+    if mmtk.options.transparent_hugepages {
+        crate::util::memory::set_transparent_hugepages(true);
+    }
+    if mmtk.options.numa {
+        crate::util::numa::set_numa_enabled(true);
+    }
     mmtk.plan
         .gc_init(heap_size, &crate::VM_MAP, &mmtk.scheduler);
+    if mmtk.options.pretouch_heap {
+        crate::util::heap::pretouch::pretouch_heap(&*mmtk.plan, heap_size, mmtk.options.threads);
+    }
+    // From now on, only dynamic options can be changed.
+    mmtk.options.mark_initialized();
     info!("Initialized MMTk with {:?}", mmtk.options.plan);
+    if std::env::var(PRINT_OPTIONS_ENV_VAR).map_or(false, |v| v == "1" || v == "true") {
+        // Print to stderr rather than logging, so the options are shown in release builds (where logging is compiled out).
+        eprintln!("============================ MMTk Options ============================");
+        for option in mmtk.options.describe() {
+            eprintln!("{}", option);
+        }
+        eprintln!("------------------------------ End MMTk Options -----------------------------");
+    }
     #[cfg(feature = "extreme_assertions")]
     warn!("The feature 'extreme_assertions' is enabled. MMTk will run expensive run-time checks. Slow performance should be expected.");
 }
 
+/// Set the GC trigger policy for an MMTk instance. By default, MMTk creates a policy based on the `gc_trigger` option.
+/// A VM can provide its own policy with this method. This method needs to be called before `gc_init()`.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `gc_trigger`: The GC trigger policy to use.
+pub fn set_gc_trigger<VM: VMBinding>(
+    mmtk: &'static mut MMTK<VM>,
+    gc_trigger: Box<dyn GCTriggerPolicy>,
+) {
+    mmtk.plan.base_mut().set_gc_trigger(gc_trigger);
+}
+
 /// Request MMTk to create a mutator for the given thread. For performance reasons, A VM should
 /// store the returned mutator in a thread local storage that can be accessed efficiently.
 ///
@@ -191,7 +229,9 @@ pub fn enable_collection<VM: VMBinding>(mmtk: &'static MMTK<VM>, tls: VMThread)
     mmtk.plan.base().initialized.store(true, Ordering::SeqCst);
 }
 
-/// Process MMTk run-time options.
+/// Process MMTk run-time options. Init-only options can only be set before `gc_init()`.
+/// Dynamic options (such as `stress_factor`) can also be set while the program runs.
+/// Returns false if the option cannot be set at this point, or the value is invalid.
 ///
 /// Arguments:
 /// * `mmtk`: A reference to an MMTk instance.
@@ -207,6 +247,116 @@ pub fn process<VM: VMBinding>(mmtk: &'static MMTK<VM>, name: &str, value: &str)
     unsafe { mmtk.options.process(name, value) }
 }
 
+/// Process a comma separated list of MMTk run-time options, such as `"threads=8,stress_factor=4096"`.
+/// Returns an error for the first option that is unknown or has an invalid value. The options before
+/// the error are set. Like `process()`, this cannot set the plan, and init-only options cannot be set after `gc_init()`.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `options`: The options to process, in the form of `name=value` pairs separated by commas.
+pub fn process_bulk<VM: VMBinding>(
+    mmtk: &'static MMTK<VM>,
+    options: &str,
+) -> Result<(), OptionError> {
+    unsafe { mmtk.options.process_bulk(options) }
+}
+
+/// Return all the MMTk options with their current values, default values and descriptions.
+/// Bindings can include these in bug reports or diagnostics. Setting the env var `MMTK_PRINT_OPTIONS=1`
+/// prints the same information to stderr at `gc_init()`.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+pub fn options<VM: VMBinding>(mmtk: &MMTK<VM>) -> impl Iterator<Item = OptionInfo> {
+    mmtk.options.describe().into_iter()
+}
+
+/// Return the page usage and address ranges of each space in the plan. Unlike `used_bytes()`/`free_bytes()`/
+/// `total_bytes()`, which return numbers for the whole heap, this gives a breakdown by spaces.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+pub fn space_stats<VM: VMBinding>(mmtk: &MMTK<VM>) -> Vec<SpaceStats> {
+    let mut stats = vec![];
+    mmtk.plan
+        .for_each_space(&mut |space| stats.push(space.space_stats()));
+    stats
+}
+
+/// Return the memory that MMTk has mapped for each kind of side metadata (e.g. mark bits, log bits, and Immix line and
+/// block tables). Unlike the estimate in `reserved_pages()`, this is the memory actually mapped, and it goes down when
+/// the metadata is released with the memory of the spaces. Side metadata is shared by all MMTk instances, so this is the
+/// same for every instance.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+pub fn side_metadata_stats<VM: VMBinding>(_mmtk: &MMTK<VM>) -> Vec<SideMetadataStats> {
+    side_metadata::side_metadata_stats()
+}
+
+/// Register a pre-populated memory region, such as a boot image, that the VM has mapped. MMTk treats the objects
+/// in the region as immortal, and does not trace through them. If the VM writes a reference into an object
+/// in the region, it needs to call `record_vm_region_write()` for the object.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `name`: The name of the region, used in logging.
+/// * `start`: The start of the region. It needs to be aligned to chunks (`BYTES_IN_CHUNK`).
+/// * `size`: The size of the region in bytes. It needs to be a multiple of chunks. The region cannot overlap
+///   the MMTk heap range (see `starting_heap_address()` and `last_heap_address()`), or any other region.
+pub fn register_vm_region<VM: VMBinding>(
+    mmtk: &MMTK<VM>,
+    name: &str,
+    start: Address,
+    size: usize,
+) -> Result<(), VMRegionError> {
+    mmtk.plan.base().vm_regions.register(name, start, size)
+}
+
+/// Map an image file privately (copy-on-write) at the given address, and register it as a VM region
+/// (see `register_vm_region()`). Returns the size of the region, which is the file size aligned up to chunks.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `name`: The name of the region, used in logging.
+/// * `path`: The path of the image file.
+/// * `start`: The address to map the file at. It needs to be aligned to chunks (`BYTES_IN_CHUNK`).
+pub fn load_vm_region<VM: VMBinding>(
+    mmtk: &MMTK<VM>,
+    name: &str,
+    path: &str,
+    start: Address,
+) -> Result<usize, VMRegionError> {
+    let file = std::fs::File::open(path).map_err(VMRegionError::Io)?;
+    let file_size = file.metadata().map_err(VMRegionError::Io)?.len() as usize;
+    let size = conversions::raw_align_up(file_size.max(1), BYTES_IN_CHUNK);
+    // Check the range before mapping the file, so we will not map a file at an invalid address.
+    let regions = &mmtk.plan.base().vm_regions;
+    regions.check(start, size)?;
+    crate::util::memory::mmap_file_noreplace(start, file_size, &file).map_err(VMRegionError::Io)?;
+    regions.register(name, start, size).map(|_| size)
+}
+
+/// Is the address in a VM region registered with `register_vm_region()` or `load_vm_region()`?
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `addr`: The address to check.
+pub fn is_in_vm_region<VM: VMBinding>(mmtk: &MMTK<VM>, addr: Address) -> bool {
+    mmtk.plan.base().vm_regions.contains(addr)
+}
+
+/// Record that the VM wrote a reference into an object in a VM region. The object is scanned as a root in the next GC,
+/// and in the GCs after that as long as it refers into the heap, so the objects it refers to are kept alive (and updated
+/// if they move). The VM needs to call this for every write of a reference into a VM region.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `object`: The object in a VM region that was written to.
+pub fn record_vm_region_write<VM: VMBinding>(mmtk: &MMTK<VM>, object: ObjectReference) {
+    mmtk.plan.base().vm_regions.record_write(object)
+}
+
 /// Return used memory in bytes.
 ///
 /// Arguments:
@@ -223,16 +373,27 @@ pub fn free_bytes<VM: VMBinding>(mmtk: &MMTK<VM>) -> usize {
     mmtk.plan.get_free_pages() << LOG_BYTES_IN_PAGE
 }
 
+/// Set the layout of the heap in the virtual address space, e.g. a layout below 32 GB created by
+/// `VMLayout::contiguous()` so the VM can use compressed pointers. This can only be called once, before
+/// the layout is used by creating an MMTk instance or by querying the heap range (e.g. `starting_heap_address()`).
+/// By default, MMTk uses `VMLayout::DEFAULT`.
+///
+/// Arguments:
+/// * `layout`: The layout to use.
+pub fn set_vm_layout(layout: VMLayout) {
+    crate::util::heap::layout::vm_layout_constants::set_vm_layout(layout);
+}
+
 /// Return the starting address of the heap. *Note that currently MMTk uses
-/// a fixed address range as heap.*
+/// a fixed address range as heap, which can be changed by `set_vm_layout()`.*
 pub fn starting_heap_address() -> Address {
-    HEAP_START
+    vm_layout().heap_start
 }
 
 /// Return the ending address of the heap. *Note that currently MMTk uses
-/// a fixed address range as heap.*
+/// a fixed address range as heap, which can be changed by `set_vm_layout()`.*
 pub fn last_heap_address() -> Address {
-    HEAP_END
+    vm_layout().heap_end
 }
 
 /// Return the total memory in bytes.
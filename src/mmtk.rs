@@ -7,10 +7,11 @@ use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::layout::map::Map;
 use crate::util::opaque_pointer::*;
-use crate::util::options::{Options, UnsafeOptionsWrapper};
+use crate::util::options::{OptionError, Options, UnsafeOptionsWrapper};
 use crate::util::reference_processor::ReferenceProcessors;
 #[cfg(feature = "sanity")]
 use crate::util::sanity::sanity_checker::SanityChecker;
+use crate::util::verifier::HeapVerifier;
 use crate::vm::VMBinding;
 use std::default::Default;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -22,9 +23,8 @@ lazy_static! {
     // The considerations are:
     // 1. We need VMMap and Mmapper to create spaces. It is natural that the mappers are not
     //    part of MMTK, as creating MMTK requires these mappers. We could use Rc/Arc for these mappers though.
-    // 2. These mmappers are possibly global across multiple MMTk instances, as they manage the
-    //    entire address space.
-    // TODO: We should refactor this when we know more about how multiple MMTK instances work.
+    // 2. These mmappers are global across multiple MMTk instances, as they manage the
+    //    entire address space. Different instances use disjoint address ranges.
 
     /// A global VMMap that manages the mapping of spaces to virtual memory ranges.
     pub static ref VM_MAP: VMMap = VMMap::new();
@@ -37,7 +37,9 @@ lazy_static! {
 }
 
 /// An MMTk instance. MMTk allows multiple instances to run independently, and each instance gives users a separate heap.
-/// *Note that multi-instances is not fully supported yet*
+/// The instances share the global `VM_MAP`, `MMAPPER` and `SFT_MAP`, but each instance reserves its spaces from a
+/// distinct part of the heap address range (see `HeapMeta`), so their spaces never overlap. Multiple instances are
+/// only supported on 64-bit targets, and the instances need to be created and initialized (`gc_init()`) one at a time.
 pub struct MMTK<VM: VMBinding> {
     pub(crate) plan: Box<dyn Plan<VM = VM>>,
     pub(crate) reference_processors: ReferenceProcessors,
@@ -46,13 +48,33 @@ pub struct MMTK<VM: VMBinding> {
     pub(crate) scheduler: Arc<GCWorkScheduler<VM>>,
     #[cfg(feature = "sanity")]
     pub(crate) sanity_checker: Mutex<SanityChecker>,
+    pub(crate) verifier: HeapVerifier,
     inside_harness: AtomicBool,
 }
 
 impl<VM: VMBinding> MMTK<VM> {
     pub fn new() -> Self {
+        Self::new_with(Options::default())
+    }
+
+    /// Create an MMTk instance with the given options in addition to the ones from the environment variables.
+    /// The options use the same format as `memory_manager::process_bulk()`, e.g. `"plan=SemiSpace,threads=2"`.
+    /// Unlike `process_bulk()`, this also allows choosing the plan, so instances in the same process can
+    /// use different plans.
+    pub fn with_options(options: &str) -> Result<Self, OptionError> {
+        let mut opts = Options::default();
+        opts.set_bulk_from_str(options)?;
+        Ok(Self::new_with(opts))
+    }
+
+    fn new_with(options: Options) -> Self {
+        if options.relocate_heap_on_conflict {
+            crate::util::heap::layout::vm_layout_constants::relocate_heap_if_occupied();
+        }
+        // The spaces of the plan are laid out based on the VM layout, so it cannot be changed any more.
+        crate::util::heap::layout::vm_layout_constants::freeze_vm_layout();
         let scheduler = GCWorkScheduler::new();
-        let options = Arc::new(UnsafeOptionsWrapper::new(Options::default()));
+        let options = Arc::new(UnsafeOptionsWrapper::new(options));
         let plan = crate::plan::create_plan(
             options.plan,
             &VM_MAP,
@@ -68,6 +90,7 @@ impl<VM: VMBinding> MMTK<VM> {
             scheduler,
             #[cfg(feature = "sanity")]
             sanity_checker: Mutex::new(SanityChecker::new()),
+            verifier: HeapVerifier::new(),
             inside_harness: AtomicBool::new(false),
         }
     }
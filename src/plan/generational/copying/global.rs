@@ -16,7 +16,7 @@ use crate::scheduler::*;
 use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::SideMetadataSanity;
@@ -161,13 +161,26 @@ impl<VM: VMBinding> Plan for GenCopy<VM> {
     /// Return the number of pages avilable for allocation. Assuming all future allocations goes to nursery.
     fn get_pages_avail(&self) -> usize {
         // super.get_pages_avail() / 2 to reserve pages for copying
-        (self.get_total_pages() - self.get_pages_reserved()) >> 1
+        (self
+            .get_total_pages()
+            .saturating_sub(self.get_pages_reserved()))
+            >> 1
     }
 
     fn base(&self) -> &BasePlan<VM> {
         &self.gen.common.base
     }
 
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.gen.common.base
+    }
+
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.gen.for_each_space(f);
+        f(&self.copyspace0);
+        f(&self.copyspace1);
+    }
+
     fn common(&self) -> &CommonPlan<VM> {
         &self.gen.common
     }
@@ -187,7 +200,7 @@ impl<VM: VMBinding> GenCopy<VM> {
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
     ) -> Self {
-        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         // We have no specific side metadata for copying. So just use the ones from generational.
         let global_metadata_specs =
             crate::plan::generational::new_generational_global_metadata_specs::<VM>();
@@ -201,6 +214,8 @@ impl<VM: VMBinding> GenCopy<VM> {
             vm_map,
             mmapper,
             &mut heap,
+            options.poison_on_release,
+            options.protect_on_release,
         );
         let copyspace1 = CopySpace::new(
             "copyspace1",
@@ -211,6 +226,8 @@ impl<VM: VMBinding> GenCopy<VM> {
             vm_map,
             mmapper,
             &mut heap,
+            options.poison_on_release,
+            options.protect_on_release,
         );
 
         let res = GenCopy {
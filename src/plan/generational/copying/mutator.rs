@@ -4,6 +4,7 @@ use crate::plan::barriers::*;
 use crate::plan::generational::gc_work::GenNurseryProcessEdges;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
 use crate::util::alloc::BumpAllocator;
@@ -34,6 +35,7 @@ lazy_static! {
         AllocationType::Default => AllocatorSelector::BumpPointer(0),
         AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(1),
         AllocationType::Los => AllocatorSelector::LargeObject(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 
@@ -44,17 +46,21 @@ pub fn create_gencopy_mutator<VM: VMBinding>(
     let gencopy = mmtk.plan.downcast_ref::<GenCopy<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: box vec![
-            (AllocatorSelector::BumpPointer(0), &gencopy.gen.nursery),
-            (
-                AllocatorSelector::BumpPointer(1),
-                gencopy.gen.common.get_immortal(),
-            ),
-            (
-                AllocatorSelector::LargeObject(0),
-                gencopy.gen.common.get_los(),
-            ),
-        ],
+        space_mapping: with_extra_spaces(
+            vec![
+                (AllocatorSelector::BumpPointer(0), &gencopy.gen.nursery),
+                (
+                    AllocatorSelector::BumpPointer(1),
+                    gencopy.gen.common.get_immortal(),
+                ),
+                (
+                    AllocatorSelector::LargeObject(0),
+                    gencopy.gen.common.get_los(),
+                ),
+            ],
+            &*mmtk.plan,
+            gencopy.gen.common.get_immortal(),
+        ),
         prepare_func: &gencopy_mutator_prepare,
         release_func: &gencopy_mutator_release,
     };
@@ -55,6 +55,8 @@ impl<VM: VMBinding> Gen<VM> {
                 vm_map,
                 mmapper,
                 &mut heap,
+                options.poison_on_release,
+                options.protect_on_release,
             ),
             common: CommonPlan::new(
                 vm_map,
@@ -69,6 +71,12 @@ impl<VM: VMBinding> Gen<VM> {
         }
     }
 
+    /// Call the closure for each space in Gen.
+    pub fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.common.for_each_space(f);
+        f(&self.nursery);
+    }
+
     /// Verify side metadata specs used in the spaces in Gen.
     pub fn verify_side_metadata_sanity(&self, sanity: &mut SideMetadataSanity) {
         self.common.verify_side_metadata_sanity(sanity);
@@ -109,7 +117,7 @@ impl<VM: VMBinding> Gen<VM> {
         space: &dyn Space<VM>,
     ) -> bool {
         let nursery_full = self.nursery.reserved_pages()
-            >= (conversions::bytes_to_pages_up(self.common.base.options.max_nursery));
+            >= (conversions::bytes_to_pages_up(self.common.base.options.max_nursery.get()));
         if nursery_full {
             return true;
         }
@@ -137,7 +145,7 @@ impl<VM: VMBinding> Gen<VM> {
             .base
             .user_triggered_collection
             .load(Ordering::SeqCst)
-            && self.common.base.options.full_heap_system_gc
+            && self.common.base.options.full_heap_system_gc.get()
         {
             // User triggered collection, and we force full heap for user triggered collection
             true
@@ -208,7 +216,8 @@ impl<VM: VMBinding> Gen<VM> {
 
     /// Check a plan to see if the next GC should be a full heap GC.
     pub fn should_next_gc_be_full_heap(plan: &dyn Plan<VM = VM>) -> bool {
-        plan.get_pages_avail() < conversions::bytes_to_pages_up(plan.base().options.min_nursery)
+        plan.get_pages_avail()
+            < conversions::bytes_to_pages_up(plan.base().options.min_nursery.get())
     }
 
     /// Set next_gc_full_heap to the given value.
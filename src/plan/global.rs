@@ -9,12 +9,15 @@ use crate::plan::Mutator;
 use crate::policy::immortalspace::ImmortalSpace;
 use crate::policy::largeobjectspace::LargeObjectSpace;
 use crate::policy::space::Space;
+use crate::policy::vmregion::VMRegions;
 use crate::scheduler::gc_work::ProcessEdgesWork;
 use crate::scheduler::*;
 use crate::util::alloc::allocators::AllocatorSelector;
 #[cfg(feature = "analysis")]
 use crate::util::analysis::AnalysisManager;
+use crate::util::constants::DEFAULT_STRESS_FACTOR;
 use crate::util::conversions::bytes_to_pages;
+use crate::util::heap::gc_trigger::{create_gc_trigger, GCTriggerPolicy};
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::layout::map::Map;
@@ -25,6 +28,7 @@ use crate::util::metadata::side_metadata::SideMetadataSpec;
 use crate::util::options::PlanSelector;
 use crate::util::options::{Options, UnsafeOptionsWrapper};
 use crate::util::statistics::stats::Stats;
+use crate::util::stress_gc::StressGCSchedule;
 use crate::util::{Address, ObjectReference};
 use crate::util::{VMMutatorThread, VMWorkerThread};
 use crate::vm::*;
@@ -133,6 +137,9 @@ pub fn create_mutator<VM: VMBinding>(
         PlanSelector::PageProtect => {
             crate::plan::pageprotect::mutator::create_pp_mutator(tls, &*mmtk.plan)
         }
+        PlanSelector::NativeMarkSweep => {
+            crate::plan::nativemarksweep::mutator::create_nms_mutator(tls, &*mmtk.plan)
+        }
     })
 }
 
@@ -160,6 +167,9 @@ pub fn create_plan<VM: VMBinding>(
         PlanSelector::PageProtect => Box::new(crate::plan::pageprotect::PageProtect::new(
             vm_map, mmapper, options,
         )),
+        PlanSelector::NativeMarkSweep => Box::new(
+            crate::plan::nativemarksweep::NativeMarkSweep::new(vm_map, mmapper, options, scheduler),
+        ),
     }
 }
 
@@ -189,6 +199,14 @@ pub trait Plan: 'static + Sync + Downcast {
         mmtk: &'static MMTK<Self::VM>,
     ) -> GCWorkerLocalPtr;
     fn base(&self) -> &BasePlan<Self::VM>;
+    /// Get the base plan mutably, e.g. to install a GC trigger policy before `gc_init()` (see
+    /// `memory_manager::set_gc_trigger()`). Note that this is a required method: a binding that implements
+    /// its own plan needs to add it.
+    fn base_mut(&mut self) -> &mut BasePlan<Self::VM>;
+    /// Call the closure for each space in the plan, including the spaces in `CommonPlan`/`BasePlan`. This is used by
+    /// `memory_manager::space_stats()`. Note that this is a required method: a binding that implements its own plan
+    /// needs to add it.
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<Self::VM>));
     fn schedule_collection(&'static self, _scheduler: &GCWorkScheduler<Self::VM>);
     fn common(&self) -> &CommonPlan<Self::VM> {
         panic!("Common Plan not handled!")
@@ -239,6 +257,9 @@ pub trait Plan: 'static + Sync + Downcast {
 
     fn prepare(&mut self, tls: VMWorkerThread);
     fn release(&mut self, tls: VMWorkerThread);
+    /// Called at the end of a GC, after the mutators are resumed. Work added to the
+    /// scheduler's `background_work` bucket here runs concurrently with the mutators.
+    fn end_of_gc(&'static self, _scheduler: &GCWorkScheduler<Self::VM>) {}
 
     fn poll(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
         if self.collection_required(space_full, space) {
@@ -291,12 +312,20 @@ pub trait Plan: 'static + Sync + Downcast {
         self.get_pages_used() + self.get_collection_reserve()
     }
 
+    /// Get the current heap size in pages. This is decided by the GC trigger policy, and
+    /// it never exceeds the heap size given in `gc_init()`. Before `gc_init()`, there is no GC trigger policy,
+    /// and this returns the heap size in the heap meta, which is 0.
     fn get_total_pages(&self) -> usize {
-        self.base().heap.get_total_pages()
+        match self.base().try_gc_trigger() {
+            Some(gc_trigger) => gc_trigger.get_current_heap_size_in_pages(),
+            None => self.base().heap.get_total_pages(),
+        }
     }
 
     fn get_pages_avail(&self) -> usize {
-        self.get_total_pages() - self.get_pages_reserved()
+        // A GC trigger may shrink the heap size below the reserved pages.
+        self.get_total_pages()
+            .saturating_sub(self.get_pages_reserved())
     }
 
     fn get_collection_reserve(&self) -> usize {
@@ -310,11 +339,11 @@ pub trait Plan: 'static + Sync + Downcast {
     }
 
     fn get_free_pages(&self) -> usize {
-        self.get_total_pages() - self.get_pages_used()
+        self.get_total_pages().saturating_sub(self.get_pages_used())
     }
 
     fn handle_user_collection_request(&self, tls: VMMutatorThread, force: bool) {
-        if force || !self.options().ignore_system_g_c {
+        if force || !self.options().ignore_system_g_c.get() {
             info!("User triggering collection");
             self.base()
                 .user_triggered_collection
@@ -368,6 +397,8 @@ pub struct BasePlan<VM: VMBinding> {
     pub cur_collection_attempts: AtomicUsize,
     pub control_collector_context: ControllerCollectorContext<VM>,
     pub stats: Stats,
+    // The GC trigger policy. It is created from the options in gc_init(), unless the binding has set one.
+    gc_trigger: Option<Box<dyn GCTriggerPolicy>>,
     mmapper: &'static Mmapper,
     pub vm_map: &'static VMMap,
     pub options: Arc<UnsafeOptionsWrapper>,
@@ -379,6 +410,8 @@ pub struct BasePlan<VM: VMBinding> {
     pub mutator_iterator_lock: Mutex<()>,
     // A counter that keeps tracks of the number of bytes allocated since last stress test
     pub allocation_bytes: AtomicUsize,
+    /// The allocations at which stress GCs are forced (see the `stress_gc_interval` option)
+    pub(crate) stress_gc: StressGCSchedule,
     // Wrapper around analysis counters
     #[cfg(feature = "analysis")]
     pub analysis_manager: AnalysisManager<VM>,
@@ -392,6 +425,11 @@ pub struct BasePlan<VM: VMBinding> {
     pub ro_space: ImmortalSpace<VM>,
     #[cfg(feature = "vm_space")]
     pub vm_space: ImmortalSpace<VM>,
+    /// The extra immortal spaces named by the `extra_spaces` option. The binding allocates into them
+    /// with `AllocationSemantics::Extra0` to `Extra3`.
+    pub extra_spaces: Vec<ImmortalSpace<VM>>,
+    /// Pre-populated regions (such as boot images) that the binding registers at runtime.
+    pub vm_regions: VMRegions,
 }
 
 #[cfg(feature = "vm_space")]
@@ -436,9 +474,28 @@ impl<VM: VMBinding> BasePlan<VM> {
         global_side_metadata_specs: Vec<SideMetadataSpec>,
     ) -> BasePlan<VM> {
         let stats = Stats::new(&options);
+        let stress_gc = StressGCSchedule::new(&options);
         // Initializing the analysis manager and routines
         #[cfg(feature = "analysis")]
         let analysis_manager = AnalysisManager::new(&stats);
+        let extra_spaces = options
+            .extra_spaces
+            .names
+            .iter()
+            .map(|name| {
+                ImmortalSpace::new(
+                    // Spaces are alive as long as the MMTk instance, which is never dropped.
+                    Box::leak(name.clone().into_boxed_str()),
+                    true,
+                    VMRequest::discontiguous(),
+                    global_side_metadata_specs.clone(),
+                    vm_map,
+                    mmapper,
+                    &mut heap,
+                    constraints,
+                )
+            })
+            .collect();
         BasePlan {
             #[cfg(feature = "code_space")]
             code_space: ImmortalSpace::new(
@@ -482,6 +539,8 @@ impl<VM: VMBinding> BasePlan<VM> {
                 constraints,
                 global_side_metadata_specs,
             ),
+            extra_spaces,
+            vm_regions: VMRegions::new(),
 
             initialized: AtomicBool::new(false),
             gc_status: Mutex::new(GcStatus::NotInGC),
@@ -494,6 +553,7 @@ impl<VM: VMBinding> BasePlan<VM> {
             cur_collection_attempts: AtomicUsize::new(0),
             control_collector_context: ControllerCollectorContext::new(),
             stats,
+            gc_trigger: None,
             mmapper,
             heap,
             vm_map,
@@ -503,6 +563,7 @@ impl<VM: VMBinding> BasePlan<VM> {
             scanned_stacks: AtomicUsize::new(0),
             mutator_iterator_lock: Mutex::new(()),
             allocation_bytes: AtomicUsize::new(0),
+            stress_gc,
             #[cfg(feature = "analysis")]
             analysis_manager,
         }
@@ -522,6 +583,13 @@ impl<VM: VMBinding> BasePlan<VM> {
         self.heap
             .total_pages
             .store(bytes_to_pages(heap_size), Ordering::Relaxed);
+        if self.gc_trigger.is_none() {
+            self.gc_trigger = Some(create_gc_trigger(&self.options));
+        }
+        self.gc_trigger
+            .as_mut()
+            .unwrap()
+            .init(bytes_to_pages(heap_size));
         self.control_collector_context.init(scheduler);
 
         #[cfg(feature = "code_space")]
@@ -535,6 +603,25 @@ impl<VM: VMBinding> BasePlan<VM> {
             self.vm_space.init(vm_map);
             self.vm_space.ensure_mapped();
         }
+        for space in self.extra_spaces.iter_mut() {
+            space.init(vm_map);
+        }
+    }
+
+    /// Get the GC trigger policy. This can only be called after gc_init().
+    pub fn gc_trigger(&self) -> &dyn GCTriggerPolicy {
+        self.gc_trigger.as_deref().unwrap()
+    }
+
+    /// Get the GC trigger policy, or `None` if it is not installed yet (before gc_init(), unless the binding
+    /// has called `memory_manager::set_gc_trigger()`).
+    pub fn try_gc_trigger(&self) -> Option<&dyn GCTriggerPolicy> {
+        self.gc_trigger.as_deref()
+    }
+
+    /// Set the GC trigger policy. This needs to be called before gc_init().
+    pub fn set_gc_trigger(&mut self, gc_trigger: Box<dyn GCTriggerPolicy>) {
+        self.gc_trigger = Some(gc_trigger);
     }
 
     // Depends on what base spaces we use, unsync may be unused.
@@ -552,6 +639,9 @@ impl<VM: VMBinding> BasePlan<VM> {
         {
             pages += self.ro_space.reserved_pages();
         }
+        for space in self.extra_spaces.iter() {
+            pages += space.reserved_pages();
+        }
 
         // The VM space may be used as an immutable boot image, in which case, we should not count
         // it as part of the heap size.
@@ -586,6 +676,20 @@ impl<VM: VMBinding> BasePlan<VM> {
             trace!("trace_object: object in boot space");
             return self.vm_space.trace_object(_trace, _object);
         }
+
+        for space in self.extra_spaces.iter() {
+            if space.in_space(_object) {
+                trace!("trace_object: object in {}", space.get_name());
+                return space.trace_object(_trace, _object);
+            }
+        }
+
+        // Objects in VM regions are immortal, and we do not trace through them. Objects in the regions
+        // that have references into the heap are scanned as roots.
+        if self.vm_regions.contains(_object.to_address()) {
+            trace!("trace_object: object in a VM region");
+            return _object;
+        }
         panic!("No special case for space in trace_object({:?})", _object);
     }
 
@@ -598,6 +702,9 @@ impl<VM: VMBinding> BasePlan<VM> {
         self.ro_space.prepare();
         #[cfg(feature = "vm_space")]
         self.vm_space.prepare();
+        for space in self.extra_spaces.iter_mut() {
+            space.prepare();
+        }
     }
 
     pub fn release(&mut self, _tls: VMWorkerThread, _primary: bool) {
@@ -609,6 +716,9 @@ impl<VM: VMBinding> BasePlan<VM> {
         self.ro_space.release();
         #[cfg(feature = "vm_space")]
         self.vm_space.release();
+        for space in self.extra_spaces.iter_mut() {
+            space.release();
+        }
     }
 
     pub fn set_collection_kind(&self) {
@@ -642,9 +752,9 @@ impl<VM: VMBinding> BasePlan<VM> {
         *gc_status = s;
         if *gc_status == GcStatus::NotInGC {
             // FIXME stats
-            if self.stats.get_gathering_stats() {
-                self.stats.end_gc();
-            }
+            // end_gc() always records the GC timing for the GC trigger, and only updates the counters
+            // if we are gathering stats.
+            self.stats.end_gc();
         }
     }
 
@@ -696,9 +806,18 @@ impl<VM: VMBinding> BasePlan<VM> {
         );
     }
 
+    /// Should every allocation take the slow path of the allocators? This is the case for the stress tests
+    /// and the analysis routines, which count the allocations.
+    #[inline(always)]
+    pub fn is_stress_test(&self) -> bool {
+        self.options.stress_factor.get() != DEFAULT_STRESS_FACTOR
+            || self.options.analysis_factor.get() != DEFAULT_STRESS_FACTOR
+            || self.stress_gc.is_enabled()
+    }
+
     #[inline]
     pub(super) fn stress_test_gc_required(&self) -> bool {
-        let stress_factor = self.options.stress_factor;
+        let stress_factor = self.options.stress_factor.get();
         if self.initialized.load(Ordering::SeqCst)
             && (self.allocation_bytes.load(Ordering::SeqCst) > stress_factor)
         {
@@ -727,12 +846,33 @@ impl<VM: VMBinding> BasePlan<VM> {
             plan.get_pages_reserved(),
             plan.get_total_pages()
         );
-        let heap_full = plan.get_pages_reserved() > plan.get_total_pages();
+        let heap_full = self.gc_trigger().is_heap_full(plan.get_pages_reserved());
 
         space_full || stress_force_gc || heap_full
     }
 
-    #[allow(unused_variables)] // depending on the enabled features, base may not be used.
+    pub(crate) fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        #[cfg(feature = "code_space")]
+        f(&self.code_space);
+        #[cfg(feature = "code_space")]
+        f(&self.code_lo_space);
+        #[cfg(feature = "ro_space")]
+        f(&self.ro_space);
+        #[cfg(feature = "vm_space")]
+        f(&self.vm_space);
+        for space in self.extra_spaces.iter() {
+            f(space);
+        }
+    }
+
+    /// Get the extra space that an `AllocationSemantics::Extra0` to `Extra3` allocation goes to,
+    /// or `None` if the `extra_spaces` option does not name a space for it.
+    pub fn get_extra_space(&self, semantics: AllocationSemantics) -> Option<&ImmortalSpace<VM>> {
+        semantics
+            .extra_space_index()
+            .and_then(|i| self.extra_spaces.get(i))
+    }
+
     pub(crate) fn verify_side_metadata_sanity(
         &self,
         side_metadata_sanity_checker: &mut SideMetadataSanity,
@@ -746,6 +886,9 @@ impl<VM: VMBinding> BasePlan<VM> {
         #[cfg(feature = "vm_space")]
         self.vm_space
             .verify_side_metadata_sanity(side_metadata_sanity_checker);
+        for space in self.extra_spaces.iter() {
+            space.verify_side_metadata_sanity(side_metadata_sanity_checker);
+        }
     }
 }
 
@@ -781,13 +924,21 @@ impl<VM: VMBinding> CommonPlan<VM> {
             los: LargeObjectSpace::new(
                 "los",
                 true,
+                false,
                 VMRequest::discontiguous(),
                 global_side_metadata_specs.clone(),
                 vm_map,
                 mmapper,
                 &mut heap,
                 constraints,
-                false,
+                options.poison_on_release,
+                options.protect_on_release,
+                // Cached cells are not released, so they cannot be protected.
+                if options.protect_on_release {
+                    0
+                } else {
+                    options.los_cache_pages
+                },
             ),
             base: BasePlan::new(
                 vm_map,
@@ -873,6 +1024,12 @@ impl<VM: VMBinding> CommonPlan<VM> {
         &self.los
     }
 
+    pub(crate) fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.base.for_each_space(f);
+        f(&self.immortal);
+        f(&self.los);
+    }
+
     pub(crate) fn verify_side_metadata_sanity(
         &self,
         side_metadata_sanity_checker: &mut SideMetadataSanity,
@@ -898,4 +1055,31 @@ pub enum AllocationSemantics {
     Code = 3,
     ReadOnly = 4,
     LargeCode = 5,
+    /// Allocate into the first extra immortal space named by the `extra_spaces` option
+    /// (the immortal space of the plan if the option does not name one).
+    Extra0 = 6,
+    /// Allocate into the second extra immortal space. See `Extra0`.
+    Extra1 = 7,
+    /// Allocate into the third extra immortal space. See `Extra0`.
+    Extra2 = 8,
+    /// Allocate into the fourth extra immortal space. See `Extra0`.
+    Extra3 = 9,
+}
+
+/// The maximum number of extra immortal spaces (see `AllocationSemantics::Extra0`).
+pub const MAX_EXTRA_SPACES: usize = 4;
+
+impl AllocationSemantics {
+    /// The allocation semantics for each extra space, in the order of the `extra_spaces` option.
+    pub const EXTRA_SPACES: [AllocationSemantics; MAX_EXTRA_SPACES] = [
+        AllocationSemantics::Extra0,
+        AllocationSemantics::Extra1,
+        AllocationSemantics::Extra2,
+        AllocationSemantics::Extra3,
+    ];
+
+    /// The index of the extra space in the `extra_spaces` option, if this is one of the `Extra` semantics.
+    pub fn extra_space_index(self) -> Option<usize> {
+        Self::EXTRA_SPACES.iter().position(|&s| s == self)
+    }
 }
@@ -14,7 +14,7 @@ use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::analysis::GcHookWork;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 use crate::util::metadata::side_metadata::SideMetadataContext;
 use crate::util::metadata::side_metadata::SideMetadataSanity;
@@ -88,7 +88,7 @@ impl<VM: VMBinding> Plan for Immix<VM> {
             true,
             self.base().cur_collection_attempts.load(Ordering::SeqCst),
             self.base().is_user_triggered_collection(),
-            self.base().options.full_heap_system_gc,
+            self.base().options.full_heap_system_gc.get(),
         );
         // Stop & scan mutators (mutator scanning can happen before STW)
         // The blocks are not identical, clippy is wrong. Probably it does not recognize the constant type parameter.
@@ -146,6 +146,15 @@ impl<VM: VMBinding> Plan for Immix<VM> {
         self.immix_space.release();
     }
 
+    fn end_of_gc(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        if self.options().lazy_sweep && self.options().eager_complete_sweep {
+            let work_packets = self
+                .immix_space
+                .generate_lazy_sweep_tasks(scheduler.num_workers());
+            scheduler.background_work.bulk_add(work_packets);
+        }
+    }
+
     fn get_collection_reserve(&self) -> usize {
         self.immix_space.defrag_headroom_pages()
     }
@@ -158,6 +167,15 @@ impl<VM: VMBinding> Plan for Immix<VM> {
         &self.common.base
     }
 
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.common.base
+    }
+
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.common.for_each_space(f);
+        f(&self.immix_space);
+    }
+
     fn common(&self) -> &CommonPlan<VM> {
         &self.common
     }
@@ -170,7 +188,7 @@ impl<VM: VMBinding> Immix<VM> {
         options: Arc<UnsafeOptionsWrapper>,
         scheduler: Arc<GCWorkScheduler<VM>>,
     ) -> Self {
-        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
         let immix = Immix {
             immix_space: ImmixSpace::new(
@@ -180,6 +198,7 @@ impl<VM: VMBinding> Immix<VM> {
                 &mut heap,
                 scheduler,
                 global_metadata_specs.clone(),
+                options.poison_on_release,
             ),
             common: CommonPlan::new(
                 vm_map,
@@ -1,6 +1,7 @@
 use super::Immix;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::plan::Plan;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
@@ -40,6 +41,7 @@ lazy_static! {
         AllocationType::Default => AllocatorSelector::Immix(0),
         AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(0),
         AllocationType::Los => AllocatorSelector::LargeObject(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 
@@ -50,14 +52,18 @@ pub fn create_immix_mutator<VM: VMBinding>(
     let immix = plan.downcast_ref::<Immix<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: box vec![
-            (AllocatorSelector::Immix(0), &immix.immix_space),
-            (
-                AllocatorSelector::BumpPointer(0),
-                immix.common.get_immortal(),
-            ),
-            (AllocatorSelector::LargeObject(0), immix.common.get_los()),
-        ],
+        space_mapping: with_extra_spaces(
+            vec![
+                (AllocatorSelector::Immix(0), &immix.immix_space),
+                (
+                    AllocatorSelector::BumpPointer(0),
+                    immix.common.get_immortal(),
+                ),
+                (AllocatorSelector::LargeObject(0), immix.common.get_los()),
+            ],
+            plan,
+            immix.common.get_immortal(),
+        ),
         prepare_func: &immix_mutator_prepare,
         release_func: &immix_mutator_release,
     };
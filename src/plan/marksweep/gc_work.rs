@@ -98,23 +98,59 @@ impl<VM: VMBinding> GCWork<VM> for MSSweepChunks<VM> {
         // Since only a single thread generates the sweep work packets as well as it is a Stop-the-World collector,
         // we can assume that the chunk mark metadata is not being accessed by anything else and hence we use
         // non-atomic accesses
+        let mut chunks = vec![];
         while chunk < end {
             if is_chunk_mapped(chunk) && unsafe { is_chunk_marked_unsafe(chunk) } {
-                work_packets.push(box MSSweepChunk { ms, chunk });
+                chunks.push(chunk);
             }
 
             chunk += BYTES_IN_CHUNK;
         }
 
-        debug!("Generated {} sweep work packets", work_packets.len());
         #[cfg(debug_assertions)]
         {
+            // With lazy sweeping, mutators allocate while the chunks are swept, so we cannot check
+            // the live bytes against `active_bytes`. A total of 0 packets disables the check.
+            let total_work_packets = if self.plan.options().lazy_sweep {
+                0
+            } else {
+                chunks.len() as u32
+            };
             ms.total_work_packets
-                .store(work_packets.len() as u32, Ordering::SeqCst);
+                .store(total_work_packets, Ordering::SeqCst);
             ms.completed_work_packets.store(0, Ordering::SeqCst);
             ms.work_live_bytes.store(0, Ordering::SeqCst);
         }
 
+        if self.plan.options().lazy_sweep {
+            debug!("Queued {} chunks for lazy sweeping", chunks.len());
+            ms.add_lazy_sweep_chunks(chunks);
+            return;
+        }
+
+        for chunk in chunks {
+            work_packets.push(box MSSweepChunk { ms, chunk });
+        }
+        debug!("Generated {} sweep work packets", work_packets.len());
         mmtk.scheduler.work_buckets[WorkBucketStage::Release].bulk_add(work_packets);
     }
 }
+
+/// Work packet that sweeps the chunks that are not swept yet, when lazy sweeping is enabled. It runs in the
+/// background after mutators resume, or in the `Prepare` stage of the next GC.
+pub struct MSLazySweep<VM: VMBinding> {
+    ms: &'static MallocSpace<VM>,
+}
+
+impl<VM: VMBinding> MSLazySweep<VM> {
+    pub fn new(ms: &'static MallocSpace<VM>) -> Self {
+        Self { ms }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for MSLazySweep<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        while self.ms.lazy_sweep_one() {}
+    }
+}
@@ -3,7 +3,7 @@ use crate::plan::global::BasePlan;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::GcStatus;
 use crate::plan::global::NoCopy;
-use crate::plan::marksweep::gc_work::{MSProcessEdges, MSSweepChunks};
+use crate::plan::marksweep::gc_work::{MSLazySweep, MSProcessEdges, MSSweepChunks};
 use crate::plan::marksweep::mutator::ALLOCATOR_MAPPING;
 use crate::plan::AllocationSemantics;
 use crate::plan::Plan;
@@ -20,7 +20,7 @@ use crate::util::alloc_bit::ALLOC_SIDE_METADATA_SPEC;
 use crate::util::analysis::GcHookWork;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSanity};
 use crate::util::options::UnsafeOptionsWrapper;
@@ -67,7 +67,17 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
         // Prepare global/collectors/mutators
         scheduler.work_buckets[WorkBucketStage::Prepare]
             .add(Prepare::<Self, NoCopy<VM>>::new(self));
-        scheduler.work_buckets[WorkBucketStage::Prepare].add(MSSweepChunks::<VM>::new(self));
+        if self.options().lazy_sweep {
+            // Chunks that were not swept after the last GC are swept in parallel before marking.
+            let work_packets: Vec<Box<dyn GCWork<VM>>> = (0..scheduler.num_workers())
+                .map(|_| box MSLazySweep::new(&self.ms) as Box<dyn GCWork<VM>>)
+                .collect();
+            scheduler.work_buckets[WorkBucketStage::Prepare].bulk_add(work_packets);
+            // The chunks are queued for lazy sweeping after marking. They are only swept after this GC.
+            scheduler.work_buckets[WorkBucketStage::Release].add(MSSweepChunks::<VM>::new(self));
+        } else {
+            scheduler.work_buckets[WorkBucketStage::Prepare].add(MSSweepChunks::<VM>::new(self));
+        }
         scheduler.work_buckets[WorkBucketStage::RefClosure]
             .add(ProcessWeakRefs::<MSProcessEdges<VM>>::new());
         // Release global/collectors/mutators
@@ -88,7 +98,6 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
 
     fn prepare(&mut self, tls: VMWorkerThread) {
         self.common.prepare(tls, true);
-        // Dont need to prepare for MallocSpace
     }
 
     fn release(&mut self, tls: VMWorkerThread) {
@@ -96,6 +105,16 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
         self.common.release(tls, true);
     }
 
+    fn end_of_gc(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        if self.options().lazy_sweep && self.options().eager_complete_sweep {
+            // Each worker sweeps the remaining chunks in the background.
+            let work_packets: Vec<Box<dyn GCWork<VM>>> = (0..scheduler.num_workers())
+                .map(|_| box MSLazySweep::new(&self.ms) as Box<dyn GCWork<VM>>)
+                .collect();
+            scheduler.background_work.bulk_add(work_packets);
+        }
+    }
+
     fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
         self.base().collection_required(self, space_full, space)
     }
@@ -112,6 +131,15 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
         &self.common.base
     }
 
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.common.base
+    }
+
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.common.for_each_space(f);
+        f(&self.ms);
+    }
+
     fn common(&self) -> &CommonPlan<VM> {
         &self.common
     }
@@ -137,7 +165,7 @@ impl<VM: VMBinding> MarkSweep<VM> {
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
     ) -> Self {
-        let heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         // if global_alloc_bit is enabled, ALLOC_SIDE_METADATA_SPEC will be added to
         // SideMetadataContext by default, so we don't need to add it here.
         #[cfg(feature = "global_alloc_bit")]
@@ -152,7 +180,7 @@ impl<VM: VMBinding> MarkSweep<VM> {
         ]);
 
         let res = MarkSweep {
-            ms: MallocSpace::new(global_metadata_specs.clone()),
+            ms: MallocSpace::new(global_metadata_specs.clone(), options.poison_on_release),
             common: CommonPlan::new(
                 vm_map,
                 mmapper,
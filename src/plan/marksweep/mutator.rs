@@ -2,6 +2,7 @@ use super::MarkSweep;
 use crate::plan::barriers::NoBarrier;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::alloc::allocators::Allocators;
@@ -24,6 +25,7 @@ lazy_static! {
         AllocationType::Default => AllocatorSelector::Malloc(0),
         AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(0),
         AllocationType::Los => AllocatorSelector::LargeObject(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 
@@ -34,14 +36,18 @@ pub fn create_ms_mutator<VM: VMBinding>(
     let ms = plan.downcast_ref::<MarkSweep<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: box vec![
-            (AllocatorSelector::Malloc(0), ms.ms_space()),
-            (
-                AllocatorSelector::BumpPointer(0),
-                ms.common().get_immortal(),
-            ),
-            (AllocatorSelector::LargeObject(0), ms.common().get_los()),
-        ],
+        space_mapping: with_extra_spaces(
+            vec![
+                (AllocatorSelector::Malloc(0), ms.ms_space()),
+                (
+                    AllocatorSelector::BumpPointer(0),
+                    ms.common().get_immortal(),
+                ),
+                (AllocatorSelector::LargeObject(0), ms.common().get_los()),
+            ],
+            plan,
+            ms.common().get_immortal(),
+        ),
         prepare_func: &ms_mutator_prepare,
         release_func: &ms_mutator_release,
     };
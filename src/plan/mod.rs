@@ -26,6 +26,7 @@ pub use global::AllocationSemantics;
 pub use global::CopyContext;
 pub(crate) use global::GcStatus;
 pub use global::Plan;
+pub use global::MAX_EXTRA_SPACES;
 
 mod mutator_context;
 pub use mutator_context::Mutator;
@@ -43,6 +44,7 @@ pub use transitive_closure::{ObjectsClosure, TransitiveClosure};
 mod generational;
 mod immix;
 mod marksweep;
+mod nativemarksweep;
 mod nogc;
 mod pageprotect;
 mod semispace;
@@ -53,6 +55,8 @@ mod semispace;
 pub use generational::copying::GENCOPY_CONSTRAINTS;
 pub use immix::IMMIX_CONSTRAINTS;
 pub use marksweep::MS_CONSTRAINTS;
+pub use nativemarksweep::NATIVE_MS_CONSTRAINTS;
 pub use nogc::NOGC_CONSTRAINTS;
 pub use pageprotect::PP_CONSTRAINTS;
+pub use pageprotect::PP_MOVING_CONSTRAINTS;
 pub use semispace::SS_CONSTRAINTS;
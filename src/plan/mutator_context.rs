@@ -3,6 +3,7 @@
 use crate::plan::barriers::{Barrier, WriteTarget};
 use crate::plan::global::Plan;
 use crate::plan::AllocationSemantics as AllocationType;
+use crate::plan::MAX_EXTRA_SPACES;
 use crate::policy::space::Space;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
 use crate::util::{Address, ObjectReference};
@@ -13,6 +14,39 @@ use enum_map::EnumMap;
 
 type SpaceMapping<VM> = Vec<(AllocatorSelector, &'static dyn Space<VM>)>;
 
+/// The bump pointer allocators for the extra spaces, in the order of `AllocationSemantics::EXTRA_SPACES`.
+/// Plans should not use these allocators for their own spaces.
+const EXTRA_SPACE_ALLOCATORS: [AllocatorSelector; MAX_EXTRA_SPACES] = [
+    AllocatorSelector::BumpPointer(2),
+    AllocatorSelector::BumpPointer(3),
+    AllocatorSelector::BumpPointer(4),
+    AllocatorSelector::BumpPointer(5),
+];
+
+/// Get the allocator for one of the `Extra` allocation semantics. Plans use this in their allocator mapping.
+pub(crate) fn extra_space_allocator(semantics: AllocationType) -> AllocatorSelector {
+    EXTRA_SPACE_ALLOCATORS[semantics
+        .extra_space_index()
+        .expect("Not an extra space allocation semantics")]
+}
+
+/// Add the allocators for the extra spaces to the space mapping of a plan. If the `extra_spaces` option names
+/// fewer spaces than `MAX_EXTRA_SPACES`, the remaining allocators allocate into `fallback`.
+pub(crate) fn with_extra_spaces<VM: VMBinding>(
+    mut space_mapping: SpaceMapping<VM>,
+    plan: &'static dyn Plan<VM = VM>,
+    fallback: &'static dyn Space<VM>,
+) -> Box<SpaceMapping<VM>> {
+    let extra_spaces = &plan.base().extra_spaces;
+    for (i, &selector) in EXTRA_SPACE_ALLOCATORS.iter().enumerate() {
+        let space = extra_spaces
+            .get(i)
+            .map_or(fallback, |space| space as &dyn Space<VM>);
+        space_mapping.push((selector, space));
+    }
+    box space_mapping
+}
+
 // This struct is part of the Mutator struct.
 // We are trying to make it fixed-sized so that VM bindings can easily define a Mutator type to have the exact same layout as our Mutator struct.
 #[repr(C)]
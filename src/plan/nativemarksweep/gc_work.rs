@@ -0,0 +1,56 @@
+use crate::plan::global::NoCopy;
+use crate::plan::global::Plan;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::*;
+use crate::util::Address;
+use crate::util::ObjectReference;
+use crate::vm::VMBinding;
+use crate::MMTK;
+use std::ops::{Deref, DerefMut};
+
+use super::NativeMarkSweep;
+
+pub struct NMSProcessEdges<VM: VMBinding> {
+    plan: &'static NativeMarkSweep<VM>,
+    base: ProcessEdgesBase<NMSProcessEdges<VM>>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for NMSProcessEdges<VM> {
+    type VM = VM;
+    const OVERWRITE_REFERENCE: bool = false;
+    fn new(edges: Vec<Address>, _roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, mmtk);
+        let plan = base.plan().downcast_ref::<NativeMarkSweep<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    #[inline]
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        trace!("Tracing object {}", object);
+        if self.plan.ms_space().in_space(object) {
+            self.plan.ms_space().trace_object::<Self>(self, object)
+        } else {
+            self.plan
+                .common()
+                .trace_object::<Self, NoCopy<VM>>(self, object)
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for NMSProcessEdges<VM> {
+    type Target = ProcessEdgesBase<Self>;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for NMSProcessEdges<VM> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
@@ -0,0 +1,8 @@
+//! Plan: native marksweep (using MMTk's own segregated free-list allocator instead of malloc)
+
+mod gc_work;
+mod global;
+pub mod mutator;
+
+pub use self::global::NativeMarkSweep;
+pub use self::global::NATIVE_MS_CONSTRAINTS;
@@ -0,0 +1,71 @@
+use super::NativeMarkSweep;
+use crate::plan::barriers::NoBarrier;
+use crate::plan::mutator_context::Mutator;
+use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
+use crate::plan::AllocationSemantics as AllocationType;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::FreeListAllocator;
+use crate::util::{VMMutatorThread, VMWorkerThread};
+use crate::vm::VMBinding;
+use crate::Plan;
+use enum_map::enum_map;
+use enum_map::EnumMap;
+
+/// Drop the free lists of the allocator before the blocks are swept, as the sweep links the free cells again.
+pub fn nms_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    let free_list_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationType::Default])
+    }
+    .downcast_mut::<FreeListAllocator<VM>>()
+    .unwrap();
+    free_list_allocator.reset();
+}
+
+pub fn nms_mutator_release<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    // Do nothing
+}
+
+lazy_static! {
+    pub static ref ALLOCATOR_MAPPING: EnumMap<AllocationType, AllocatorSelector> = enum_map! {
+        AllocationType::Default => AllocatorSelector::FreeList(0),
+        AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(0),
+        AllocationType::Los => AllocatorSelector::LargeObject(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
+    };
+}
+
+pub fn create_nms_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    plan: &'static dyn Plan<VM = VM>,
+) -> Mutator<VM> {
+    let ms = plan.downcast_ref::<NativeMarkSweep<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &*ALLOCATOR_MAPPING,
+        space_mapping: with_extra_spaces(
+            vec![
+                (AllocatorSelector::FreeList(0), ms.ms_space()),
+                (
+                    AllocatorSelector::BumpPointer(0),
+                    ms.common().get_immortal(),
+                ),
+                (AllocatorSelector::LargeObject(0), ms.common().get_los()),
+            ],
+            plan,
+            ms.common().get_immortal(),
+        ),
+        prepare_func: &nms_mutator_prepare,
+        release_func: &nms_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, plan, &config.space_mapping),
+        barrier: box NoBarrier,
+        mutator_tls,
+        config,
+        plan,
+    }
+}
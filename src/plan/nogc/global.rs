@@ -11,7 +11,7 @@ use crate::scheduler::GCWorkerLocalPtr;
 use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 #[allow(unused_imports)]
 use crate::util::heap::VMRequest;
@@ -71,6 +71,15 @@ impl<VM: VMBinding> Plan for NoGC<VM> {
         &self.base
     }
 
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.base
+    }
+
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.base.for_each_space(f);
+        f(&self.nogc_space);
+    }
+
     fn prepare(&mut self, _tls: VMWorkerThread) {
         unreachable!()
     }
@@ -103,9 +112,9 @@ impl<VM: VMBinding> NoGC<VM> {
         options: Arc<UnsafeOptionsWrapper>,
     ) -> Self {
         #[cfg(not(feature = "nogc_lock_free"))]
-        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         #[cfg(feature = "nogc_lock_free")]
-        let heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
 
         let global_specs = SideMetadataContext::new_global_specs(&[]);
 
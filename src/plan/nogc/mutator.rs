@@ -1,6 +1,7 @@
 use crate::plan::barriers::NoBarrier;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::nogc::NoGC;
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::plan::Plan;
@@ -13,6 +14,7 @@ use enum_map::EnumMap;
 lazy_static! {
     pub static ref ALLOCATOR_MAPPING: EnumMap<AllocationType, AllocatorSelector> = enum_map! {
         AllocationType::Default | AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly | AllocationType::Los => AllocatorSelector::BumpPointer(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 
@@ -24,12 +26,14 @@ pub fn create_nogc_mutator<VM: VMBinding>(
     mutator_tls: VMMutatorThread,
     plan: &'static dyn Plan<VM = VM>,
 ) -> Mutator<VM> {
+    let nogc_space = &plan.downcast_ref::<NoGC<VM>>().unwrap().nogc_space;
     let config = MutatorConfig {
         allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: box vec![(
-            AllocatorSelector::BumpPointer(0),
-            &plan.downcast_ref::<NoGC<VM>>().unwrap().nogc_space,
-        )],
+        space_mapping: with_extra_spaces(
+            vec![(AllocatorSelector::BumpPointer(0), nogc_space)],
+            plan,
+            nogc_space,
+        ),
         prepare_func: &nogc_mutator_noop,
         release_func: &nogc_mutator_noop,
     };
@@ -1,12 +1,74 @@
 use super::global::PageProtect;
 use crate::plan::global::NoCopy;
-use crate::policy::space::Space;
+use crate::plan::CopyContext;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::space::{Space, SFT};
 use crate::scheduler::gc_work::*;
+use crate::scheduler::GCWorkerLocal;
+use crate::util::alloc::allocator;
+use crate::util::opaque_pointer::*;
 use crate::util::{Address, ObjectReference};
 use crate::vm::VMBinding;
 use crate::MMTK;
 use std::ops::{Deref, DerefMut};
 
+/// Copy context of PageProtect. Objects are only copied if the plan is moving. Each copy gets its own pages
+/// in the large object space, which are reserved before the object is copied (see
+/// `LargeObjectSpace::trace_object_and_move()`).
+pub struct PPCopyContext<VM: VMBinding> {
+    plan: &'static PageProtect<VM>,
+}
+
+impl<VM: VMBinding> CopyContext for PPCopyContext<VM> {
+    type VM = VM;
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        self.plan.constraints()
+    }
+    fn init(&mut self, _tls: VMWorkerThread) {}
+    fn prepare(&mut self) {}
+    fn release(&mut self) {}
+    #[inline(always)]
+    fn alloc_copy(
+        &mut self,
+        original: ObjectReference,
+        bytes: usize,
+        align: usize,
+        offset: isize,
+        _semantics: crate::AllocationSemantics,
+    ) -> Address {
+        debug_assert!(self.plan.moving);
+        let cell = self.plan.space.take_copy_cell(original, bytes);
+        allocator::align_allocation::<VM>(cell, align, offset, VM::MIN_ALIGNMENT, true)
+    }
+    #[inline(always)]
+    fn post_copy(
+        &mut self,
+        obj: ObjectReference,
+        _tib: Address,
+        _bytes: usize,
+        _semantics: crate::AllocationSemantics,
+    ) {
+        // Mark the copy and put it on the treadmill. This also clears its forwarding bits.
+        self.plan.space.initialize_object_metadata(obj, false);
+    }
+}
+
+impl<VM: VMBinding> PPCopyContext<VM> {
+    pub fn new(mmtk: &'static MMTK<VM>) -> Self {
+        Self {
+            plan: mmtk.plan.downcast_ref::<PageProtect<VM>>().unwrap(),
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWorkerLocal for PPCopyContext<VM> {
+    fn init(&mut self, tls: VMWorkerThread) {
+        CopyContext::init(self, tls);
+    }
+}
+
 /// Edge scanning work packet.
 pub struct PPProcessEdges<VM: VMBinding> {
     /// Use a static ref to the specific plan to avoid overhead from dynamic dispatch or
@@ -25,21 +87,49 @@ impl<VM: VMBinding> ProcessEdgesWork for PPProcessEdges<VM> {
     }
     #[inline]
     fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
-        let object = unsafe {
-            let untagged_word = object.to_address().as_usize() & !0b11usize;
-            Address::from_usize(untagged_word).to_object_reference()
-        };
+        let object = untag(object);
         if object.is_null() {
             return object;
         }
         if self.plan.space.in_space(object) {
-            self.plan.space.trace_object::<Self>(self, object)
+            if self.plan.moving {
+                let tls = self.worker().tls;
+                self.plan
+                    .space
+                    .trace_object_and_move::<Self, PPCopyContext<VM>>(
+                        self,
+                        object,
+                        unsafe { self.worker().local::<PPCopyContext<VM>>() },
+                        tls,
+                    )
+            } else {
+                self.plan.space.trace_object::<Self>(self, object)
+            }
         } else {
             self.plan
                 .common
                 .trace_object::<Self, NoCopy<VM>>(self, object)
         }
     }
+    #[inline]
+    fn process_edge(&mut self, slot: Address) {
+        let object = unsafe { slot.load::<ObjectReference>() };
+        let new_object = self.trace_object(object);
+        // Only update the edge if the object was moved, and keep the tag bits of the old reference.
+        if self.plan.moving && new_object != untag(object) {
+            let tag = object.to_address().as_usize() & 0b11usize;
+            unsafe { slot.store(new_object.to_address() + tag) };
+        }
+    }
+}
+
+/// Clear the tag bits that a VM may use in the low 2 bits of a reference.
+#[inline(always)]
+fn untag(object: ObjectReference) -> ObjectReference {
+    unsafe {
+        let untagged_word = object.to_address().as_usize() & !0b11usize;
+        Address::from_usize(untagged_word).to_object_reference()
+    }
 }
 
 impl<VM: VMBinding> Deref for PPProcessEdges<VM> {
@@ -1,4 +1,4 @@
-use super::gc_work::PPProcessEdges;
+use super::gc_work::{PPCopyContext, PPProcessEdges};
 use super::mutator::ALLOCATOR_MAPPING;
 use crate::mmtk::MMTK;
 use crate::plan::global::GcStatus;
@@ -13,7 +13,7 @@ use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::analysis::GcHookWork;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::SideMetadataContext;
@@ -22,8 +22,7 @@ use crate::util::options::UnsafeOptionsWrapper;
 use crate::util::sanity::sanity_checker::*;
 use crate::{plan::global::BasePlan, vm::VMBinding};
 use crate::{
-    plan::global::{CommonPlan, NoCopy},
-    policy::largeobjectspace::LargeObjectSpace,
+    plan::global::CommonPlan, policy::largeobjectspace::LargeObjectSpace,
     util::opaque_pointer::VMWorkerThread,
 };
 use enum_map::EnumMap;
@@ -32,6 +31,10 @@ use std::sync::Arc;
 pub struct PageProtect<VM: VMBinding> {
     pub space: LargeObjectSpace<VM>,
     pub common: CommonPlan<VM>,
+    /// Copy every reachable object in the space to new pages in every GC (see the `page_protect_moving` option).
+    pub moving: bool,
+    /// The pages of the space that survived the last GC. This is the estimate of the pages a moving GC copies.
+    live_pages: usize,
 }
 
 pub const CONSTRAINTS: PlanConstraints = PlanConstraints {
@@ -39,11 +42,24 @@ pub const CONSTRAINTS: PlanConstraints = PlanConstraints {
     ..PlanConstraints::default()
 };
 
+/// The constraints of PageProtect if `page_protect_moving` is set.
+pub const MOVING_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: true,
+    gc_header_bits: 2,
+    gc_header_words: 0,
+    num_specialized_scans: 1,
+    ..PlanConstraints::default()
+};
+
 impl<VM: VMBinding> Plan for PageProtect<VM> {
     type VM = VM;
 
     fn constraints(&self) -> &'static PlanConstraints {
-        &CONSTRAINTS
+        if self.moving {
+            &MOVING_CONSTRAINTS
+        } else {
+            &CONSTRAINTS
+        }
     }
 
     fn create_worker_local(
@@ -51,7 +67,7 @@ impl<VM: VMBinding> Plan for PageProtect<VM> {
         tls: VMWorkerThread,
         mmtk: &'static MMTK<Self::VM>,
     ) -> GCWorkerLocalPtr {
-        let mut c = NoCopy::new(mmtk);
+        let mut c = PPCopyContext::new(mmtk);
         c.init(tls);
         GCWorkerLocalPtr::new(c)
     }
@@ -81,18 +97,18 @@ impl<VM: VMBinding> Plan for PageProtect<VM> {
         self.base().set_collection_kind();
         self.base().set_gc_status(GcStatus::GcPrepare);
         self.common()
-            .schedule_common::<PPProcessEdges<VM>>(&CONSTRAINTS, scheduler);
+            .schedule_common::<PPProcessEdges<VM>>(self.constraints(), scheduler);
         // Stop & scan mutators (mutator scanning can happen before STW)
         scheduler.work_buckets[WorkBucketStage::Unconstrained]
             .add(StopMutators::<PPProcessEdges<VM>>::new());
         // Prepare global/collectors/mutators
         scheduler.work_buckets[WorkBucketStage::Prepare]
-            .add(Prepare::<Self, NoCopy<VM>>::new(self));
+            .add(Prepare::<Self, PPCopyContext<VM>>::new(self));
         scheduler.work_buckets[WorkBucketStage::RefClosure]
             .add(ProcessWeakRefs::<PPProcessEdges<VM>>::new());
         // Release global/collectors/mutators
         scheduler.work_buckets[WorkBucketStage::Release]
-            .add(Release::<Self, NoCopy<VM>>::new(self));
+            .add(Release::<Self, PPCopyContext<VM>>::new(self));
         // Scheduling all the gc hooks of analysis routines. It is generally recommended
         // to take advantage of the scheduling system we have in place for more performance
         #[cfg(feature = "analysis")]
@@ -100,7 +116,7 @@ impl<VM: VMBinding> Plan for PageProtect<VM> {
         // Resume mutators
         #[cfg(feature = "sanity")]
         scheduler.work_buckets[WorkBucketStage::Final]
-            .add(ScheduleSanityGC::<Self, NoCopy<VM>>::new(self));
+            .add(ScheduleSanityGC::<Self, PPCopyContext<VM>>::new(self));
         scheduler.set_finalizer(Some(EndOfGC));
     }
 
@@ -116,6 +132,7 @@ impl<VM: VMBinding> Plan for PageProtect<VM> {
     fn release(&mut self, tls: VMWorkerThread) {
         self.common.release(tls, true);
         self.space.release(true);
+        self.live_pages = self.space.reserved_pages();
     }
 
     fn collection_required(&self, space_full: bool, space: &dyn Space<Self::VM>) -> bool {
@@ -123,7 +140,15 @@ impl<VM: VMBinding> Plan for PageProtect<VM> {
     }
 
     fn get_collection_reserve(&self) -> usize {
-        0
+        // A moving GC copies the live objects. We reserve the pages that survived the last GC, rather than the
+        // whole space, which would halve the heap. The objects allocated since then may survive too, so the
+        // copies may exceed the reserve. This is fine, as the GC workers do not poll (and a copy that cannot
+        // get any pages is kept in place).
+        if self.moving {
+            self.live_pages
+        } else {
+            0
+        }
     }
 
     fn get_pages_used(&self) -> usize {
@@ -134,6 +159,15 @@ impl<VM: VMBinding> Plan for PageProtect<VM> {
         &self.common.base
     }
 
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.common.base
+    }
+
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.common.for_each_space(f);
+        f(&self.space);
+    }
+
     fn common(&self) -> &CommonPlan<VM> {
         &self.common
     }
@@ -145,29 +179,40 @@ impl<VM: VMBinding> PageProtect<VM> {
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
     ) -> Self {
-        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
+        let moving = options.page_protect_moving;
+        let constraints = if moving {
+            &MOVING_CONSTRAINTS
+        } else {
+            &CONSTRAINTS
+        };
 
         let ret = PageProtect {
             space: LargeObjectSpace::new(
                 "los",
                 true,
+                moving,
                 VMRequest::discontiguous(),
                 global_metadata_specs.clone(),
                 vm_map,
                 mmapper,
                 &mut heap,
-                &CONSTRAINTS,
+                constraints,
+                options.poison_on_release,
                 true,
+                0,
             ),
             common: CommonPlan::new(
                 vm_map,
                 mmapper,
                 options,
                 heap,
-                &CONSTRAINTS,
+                constraints,
                 global_metadata_specs,
             ),
+            moving,
+            live_pages: 0,
         };
 
         // Use SideMetadataSanity to check if each spec is valid. This is also needed for check
@@ -2,6 +2,8 @@
 //!
 //! Allocate each object on a separate page and protect the memory on release.
 //! This GC is commonly used for debugging purposes.
+//! With the `page_protect_moving` option, every GC also copies every reachable object to new pages,
+//! so that stale references to the old copies crash on access.
 
 pub(super) mod gc_work;
 pub(super) mod global;
@@ -9,3 +11,4 @@ pub(super) mod mutator;
 
 pub use self::global::PageProtect;
 pub use self::global::CONSTRAINTS as PP_CONSTRAINTS;
+pub use self::global::MOVING_CONSTRAINTS as PP_MOVING_CONSTRAINTS;
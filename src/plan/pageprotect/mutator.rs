@@ -1,6 +1,7 @@
 use super::PageProtect;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::plan::Plan;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
@@ -23,6 +24,7 @@ lazy_static! {
         AllocationType::Default | AllocationType::Los => AllocatorSelector::LargeObject(0),
         // Temporarily put code and readonly objects to immortal space, for v8 support.
         AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 
@@ -35,13 +37,17 @@ pub fn create_pp_mutator<VM: VMBinding>(
     let page = plan.downcast_ref::<PageProtect<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: box vec![
-            (
-                AllocatorSelector::BumpPointer(0),
-                page.common.get_immortal(),
-            ),
-            (AllocatorSelector::LargeObject(0), &page.space),
-        ],
+        space_mapping: with_extra_spaces(
+            vec![
+                (
+                    AllocatorSelector::BumpPointer(0),
+                    page.common.get_immortal(),
+                ),
+                (AllocatorSelector::LargeObject(0), &page.space),
+            ],
+            plan,
+            page.common.get_immortal(),
+        ),
         prepare_func: &pp_mutator_prepare,
         release_func: &pp_mutator_release,
     };
@@ -15,7 +15,7 @@ use crate::util::alloc::allocators::AllocatorSelector;
 use crate::util::analysis::GcHookWork;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{HEAP_END, HEAP_START};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::heap::HeapMeta;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSanity};
@@ -141,6 +141,16 @@ impl<VM: VMBinding> Plan for SemiSpace<VM> {
         &self.common.base
     }
 
+    fn base_mut(&mut self) -> &mut BasePlan<VM> {
+        &mut self.common.base
+    }
+
+    fn for_each_space(&self, f: &mut dyn FnMut(&dyn Space<VM>)) {
+        self.common.for_each_space(f);
+        f(&self.copyspace0);
+        f(&self.copyspace1);
+    }
+
     fn common(&self) -> &CommonPlan<VM> {
         &self.common
     }
@@ -152,7 +162,7 @@ impl<VM: VMBinding> SemiSpace<VM> {
         mmapper: &'static Mmapper,
         options: Arc<UnsafeOptionsWrapper>,
     ) -> Self {
-        let mut heap = HeapMeta::new(HEAP_START, HEAP_END);
+        let mut heap = HeapMeta::new(vm_layout().heap_start, vm_layout().heap_end);
         let global_metadata_specs = SideMetadataContext::new_global_specs(&[]);
 
         let res = SemiSpace {
@@ -166,6 +176,8 @@ impl<VM: VMBinding> SemiSpace<VM> {
                 vm_map,
                 mmapper,
                 &mut heap,
+                options.poison_on_release,
+                options.protect_on_release,
             ),
             copyspace1: CopySpace::new(
                 "copyspace1",
@@ -176,6 +188,8 @@ impl<VM: VMBinding> SemiSpace<VM> {
                 vm_map,
                 mmapper,
                 &mut heap,
+                options.poison_on_release,
+                options.protect_on_release,
             ),
             common: CommonPlan::new(
                 vm_map,
@@ -2,6 +2,7 @@ use super::SemiSpace;
 use crate::plan::barriers::NoBarrier;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::{extra_space_allocator, with_extra_spaces};
 use crate::plan::AllocationSemantics as AllocationType;
 use crate::plan::Plan;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
@@ -38,6 +39,7 @@ lazy_static! {
         AllocationType::Default => AllocatorSelector::BumpPointer(0),
         AllocationType::Immortal | AllocationType::Code | AllocationType::LargeCode | AllocationType::ReadOnly => AllocatorSelector::BumpPointer(1),
         AllocationType::Los => AllocatorSelector::LargeObject(0),
+        extra @ (AllocationType::Extra0 | AllocationType::Extra1 | AllocationType::Extra2 | AllocationType::Extra3) => extra_space_allocator(extra),
     };
 }
 
@@ -48,11 +50,15 @@ pub fn create_ss_mutator<VM: VMBinding>(
     let ss = plan.downcast_ref::<SemiSpace<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &*ALLOCATOR_MAPPING,
-        space_mapping: box vec![
-            (AllocatorSelector::BumpPointer(0), ss.tospace()),
-            (AllocatorSelector::BumpPointer(1), ss.common.get_immortal()),
-            (AllocatorSelector::LargeObject(0), ss.common.get_los()),
-        ],
+        space_mapping: with_extra_spaces(
+            vec![
+                (AllocatorSelector::BumpPointer(0), ss.tospace()),
+                (AllocatorSelector::BumpPointer(1), ss.common.get_immortal()),
+                (AllocatorSelector::LargeObject(0), ss.common.get_los()),
+            ],
+            plan,
+            ss.common.get_immortal(),
+        ),
         prepare_func: &ss_mutator_prepare,
         release_func: &ss_mutator_release,
     };
@@ -94,6 +94,8 @@ impl<VM: VMBinding> CopySpace<VM> {
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         heap: &mut HeapMeta,
+        poison_memory_on_release: bool,
+        protect_memory_on_release: bool,
     ) -> Self {
         let local_specs = extract_side_metadata(&[
             *VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC,
@@ -116,17 +118,20 @@ impl<VM: VMBinding> CopySpace<VM> {
             mmapper,
             heap,
         );
+        let mut pr = if vmrequest.is_discontiguous() {
+            MonotonePageResource::new_discontiguous(META_DATA_PAGES_PER_REGION, vm_map)
+        } else {
+            MonotonePageResource::new_contiguous(
+                common.start,
+                common.extent,
+                META_DATA_PAGES_PER_REGION,
+                vm_map,
+            )
+        };
+        pr.common_mut().poison_memory_on_release = poison_memory_on_release;
+        pr.protect_memory_on_release = protect_memory_on_release;
         CopySpace {
-            pr: if vmrequest.is_discontiguous() {
-                MonotonePageResource::new_discontiguous(META_DATA_PAGES_PER_REGION, vm_map)
-            } else {
-                MonotonePageResource::new_contiguous(
-                    common.start,
-                    common.extent,
-                    META_DATA_PAGES_PER_REGION,
-                    vm_map,
-                )
-            },
+            pr,
             common,
             from_space: AtomicBool::new(from_space),
         }
@@ -83,6 +83,7 @@ impl Block {
 
     /// Block defrag state table (side)
     pub const DEFRAG_STATE_TABLE: SideMetadataSpec = SideMetadataSpec {
+        name: "ImmixBlockDefrag",
         is_global: false,
         offset: if super::BLOCK_ONLY {
             // If BLOCK_ONLY is set, we do not use any line marktables.
@@ -96,6 +97,7 @@ impl Block {
 
     /// Block mark table (side)
     pub const MARK_TABLE: SideMetadataSpec = SideMetadataSpec {
+        name: "ImmixBlockMark",
         is_global: false,
         offset: SideMetadataOffset::layout_after(&Self::DEFRAG_STATE_TABLE),
         log_num_of_bits: 3,
@@ -287,6 +289,13 @@ impl Block {
             } else {
                 // There are some marked lines. Keep the block live.
                 if marked_lines != Block::LINES {
+                    if space.poison_memory_on_release() {
+                        for line in self.lines() {
+                            if !line.is_marked(line_mark_state) {
+                                crate::util::memory::poison(line.start(), Line::BYTES);
+                            }
+                        }
+                    }
                     // There are holes. Mark the block as reusable.
                     self.set_state(BlockState::Reusable {
                         unavailable_lines: marked_lines as _,
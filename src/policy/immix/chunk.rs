@@ -135,6 +135,7 @@ pub struct ChunkMap {
 impl ChunkMap {
     /// Chunk alloc table
     pub const ALLOC_TABLE: SideMetadataSpec = SideMetadataSpec {
+        name: "ImmixChunkAlloc",
         is_global: false,
         offset: SideMetadataOffset::layout_after(&Block::MARK_TABLE),
         log_num_of_bits: 3,
@@ -185,16 +186,19 @@ impl ChunkMap {
         self.chunk_range.lock().clone()
     }
 
+    /// All the allocated chunks in the heap.
+    pub fn allocated_chunks(&self) -> impl Iterator<Item = Chunk> + '_ {
+        self.all_chunks()
+            .filter(move |c| self.get(*c) == ChunkState::Allocated)
+    }
+
     /// Helper function to create per-chunk processing work packets.
     pub fn generate_tasks<VM: VMBinding>(
         &self,
         func: impl Fn(Chunk) -> Box<dyn GCWork<VM>>,
     ) -> Vec<Box<dyn GCWork<VM>>> {
         let mut work_packets: Vec<Box<dyn GCWork<VM>>> = vec![];
-        for chunk in self
-            .all_chunks()
-            .filter(|c| self.get(*c) == ChunkState::Allocated)
-        {
+        for chunk in self.allocated_chunks() {
             work_packets.push(func(chunk));
         }
         work_packets
@@ -219,10 +223,6 @@ struct SweepChunk<VM: VMBinding> {
 impl<VM: VMBinding> GCWork<VM> for SweepChunk<VM> {
     #[inline]
     fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
-        let mut histogram = self.space.defrag.new_histogram();
-        if self.space.chunk_map.get(self.chunk) == ChunkState::Allocated {
-            self.chunk.sweep(self.space, &mut histogram);
-        }
-        self.space.defrag.add_completed_mark_histogram(histogram);
+        self.space.sweep_chunk(self.chunk);
     }
 }
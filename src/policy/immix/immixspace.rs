@@ -11,6 +11,7 @@ use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
 use crate::util::heap::HeapMeta;
 use crate::util::heap::PageResource;
 use crate::util::heap::VMRequest;
+use crate::util::lazy_sweep::LazySweeper;
 use crate::util::metadata::side_metadata::{self, *};
 use crate::util::metadata::{self, compare_exchange_metadata, load_metadata, MetadataSpec};
 use crate::util::object_forwarding as ForwardingWord;
@@ -29,7 +30,10 @@ use atomic::Ordering;
 use std::{
     iter::Step,
     ops::Range,
-    sync::{atomic::AtomicU8, Arc},
+    sync::{
+        atomic::{AtomicU8, AtomicUsize},
+        Arc,
+    },
 };
 
 pub struct ImmixSpace<VM: VMBinding> {
@@ -49,6 +53,8 @@ pub struct ImmixSpace<VM: VMBinding> {
     mark_state: u8,
     /// Work packet scheduler
     scheduler: Arc<GCWorkScheduler<VM>>,
+    /// Chunks to be swept after mutators resume, if lazy sweeping is enabled.
+    lazy_sweeper: LazySweeper,
 }
 
 unsafe impl<VM: VMBinding> Sync for ImmixSpace<VM> {}
@@ -67,6 +73,28 @@ impl<VM: VMBinding> SFT for ImmixSpace<VM> {
     fn is_sane(&self) -> bool {
         true
     }
+    fn verify_object(&self, object: ObjectReference) -> Result<(), String> {
+        if super::BLOCK_ONLY {
+            return Ok(());
+        }
+        // Every line that the object spans must be marked, otherwise the line will be reused while the object is live.
+        let start = VM::VMObjectModel::object_start_ref(object);
+        let end = start + VM::VMObjectModel::get_current_size(object);
+        let state = self.line_mark_state.load(Ordering::Acquire);
+        let mut line = Line::from(Line::align(start));
+        while line.start() < end {
+            if !line.is_marked(state) {
+                return Err(format!(
+                    "line {} of the object [{}, {}) is not marked",
+                    line.start(),
+                    start,
+                    end
+                ));
+            }
+            line = Line::forward(line, 1);
+        }
+        Ok(())
+    }
     fn initialize_object_metadata(&self, _object: ObjectReference, _alloc: bool) {
         #[cfg(feature = "global_alloc_bit")]
         crate::util::alloc_bit::set_alloc_bit(_object);
@@ -126,6 +154,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         heap: &mut HeapMeta,
         scheduler: Arc<GCWorkScheduler<VM>>,
         global_side_metadata_specs: Vec<SideMetadataSpec>,
+        poison_memory_on_release: bool,
     ) -> Self {
         let common = CommonSpace::new(
             SpaceOptions {
@@ -144,12 +173,14 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             mmapper,
             heap,
         );
+        let mut pr = if common.vmrequest.is_discontiguous() {
+            FreeListPageResource::new_discontiguous(0, vm_map)
+        } else {
+            FreeListPageResource::new_contiguous(common.start, common.extent, 0, vm_map)
+        };
+        pr.common_mut().poison_memory_on_release = poison_memory_on_release;
         ImmixSpace {
-            pr: if common.vmrequest.is_discontiguous() {
-                FreeListPageResource::new_discontiguous(0, vm_map)
-            } else {
-                FreeListPageResource::new_contiguous(common.start, common.extent, 0, vm_map)
-            },
+            pr,
             common,
             chunk_map: ChunkMap::new(),
             line_mark_state: AtomicU8::new(Line::RESET_MARK_STATE),
@@ -158,9 +189,15 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             defrag: Defrag::default(),
             mark_state: Self::UNMARKED_STATE,
             scheduler,
+            lazy_sweeper: LazySweeper::new(),
         }
     }
 
+    /// Are free lines and released blocks poisoned (see the `poison_on_release` option)?
+    pub fn poison_memory_on_release(&self) -> bool {
+        self.pr.common().poison_memory_on_release
+    }
+
     /// Get the number of defrag headroom pages.
     pub fn defrag_headroom_pages(&self) -> usize {
         self.defrag.defrag_headroom_pages(self)
@@ -205,20 +242,44 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             // For header metadata, we use cyclic mark bits.
             unimplemented!("cyclic mark bits is not supported at the moment");
         }
+        // # Safety: ImmixSpace reference is always valid within this collection cycle.
+        let space = unsafe { &*(self as *const Self) };
+        if self.lazy_sweeper.is_complete() {
+            space.prepare_blocks();
+        } else {
+            // Chunks that were not swept after the last GC must be swept before the blocks are prepared.
+            // They are swept in parallel, and the last packet to finish prepares the blocks.
+            let num_packets = self.scheduler().num_workers();
+            let remaining = Arc::new(AtomicUsize::new(num_packets));
+            let work_packets = (0..num_packets)
+                .map(|_| box PrepareLazySweep {
+                    space,
+                    remaining: remaining.clone(),
+                } as Box<dyn GCWork<VM>>)
+                .collect();
+            self.scheduler().work_buckets[WorkBucketStage::Prepare].bulk_add(work_packets);
+        }
+    }
+
+    /// Prepare the defrag info and each block for GC. All the chunks must have been swept.
+    fn prepare_blocks(&'static self) {
+        debug_assert!(self.lazy_sweeper.is_complete());
         // Prepare defrag info
         if super::DEFRAG {
             self.defrag.prepare(self);
         }
+        if VM::VMActivePlan::global().options().lazy_sweep {
+            // The mark histograms of the lazy sweep have been used for the defrag threshold.
+            self.defrag.mark_histograms.lock().clear();
+        }
         // Prepare each block for GC
         let threshold = self.defrag.defrag_spill_threshold.load(Ordering::Acquire);
-        // # Safety: ImmixSpace reference is always valid within this collection cycle.
-        let space = unsafe { &*(self as *const Self) };
         let work_packets = self
             .chunk_map
             .generate_tasks(|chunk| box PrepareBlockState {
-                space,
+                space: self,
                 chunk,
-                defrag_threshold: if space.in_defrag() {
+                defrag_threshold: if self.in_defrag() {
                     Some(threshold)
                 } else {
                     None
@@ -248,10 +309,16 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             self.reusable_blocks.reset();
         }
         // Sweep chunks and blocks
-        // # Safety: ImmixSpace reference is always valid within this collection cycle.
-        let space = unsafe { &*(self as *const Self) };
-        let work_packets = self.chunk_map.generate_sweep_tasks(space);
-        self.scheduler().work_buckets[WorkBucketStage::Release].bulk_add(work_packets);
+        if VM::VMActivePlan::global().options().lazy_sweep {
+            // The mark histograms are kept until the next GC uses them, as chunks are swept after this GC.
+            let chunks = self.chunk_map.allocated_chunks().map(|chunk| chunk.start());
+            self.lazy_sweeper.add_chunks(chunks);
+        } else {
+            // # Safety: ImmixSpace reference is always valid within this collection cycle.
+            let space = unsafe { &*(self as *const Self) };
+            let work_packets = self.chunk_map.generate_sweep_tasks(space);
+            self.scheduler().work_buckets[WorkBucketStage::Release].bulk_add(work_packets);
+        }
         if super::DEFRAG {
             self.defrag.release(self)
         }
@@ -263,14 +330,56 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         self.pr.release_pages(block.start());
     }
 
+    /// Sweep a chunk, and report its mark histogram for defragmentation.
+    pub fn sweep_chunk(&self, chunk: Chunk) {
+        let mut histogram = self.defrag.new_histogram();
+        if self.chunk_map.get(chunk) == ChunkState::Allocated {
+            chunk.sweep(self, &mut histogram);
+        }
+        self.defrag.add_completed_mark_histogram(histogram);
+    }
+
+    /// Sweep one of the chunks that are not swept yet. Return false if there is no such chunk.
+    pub fn lazy_sweep_one(&self) -> bool {
+        self.lazy_sweeper
+            .sweep_one(|chunk| self.sweep_chunk(Chunk::from(chunk)))
+    }
+
+    /// Sweep all the chunks that are not swept yet.
+    fn complete_lazy_sweep(&self) {
+        self.lazy_sweeper
+            .complete(|chunk| self.sweep_chunk(Chunk::from(chunk)));
+    }
+
+    /// Generate work packets that sweep the remaining chunks in the background after mutators resume.
+    pub fn generate_lazy_sweep_tasks(
+        &'static self,
+        num_packets: usize,
+    ) -> Vec<Box<dyn GCWork<VM>>> {
+        (0..num_packets)
+            .map(|_| box LazySweepChunks { space: self } as Box<dyn GCWork<VM>>)
+            .collect()
+    }
+
     /// Allocate a clean block.
     pub fn get_clean_block(&self, tls: VMThread, copy: bool) -> Option<Block> {
+        // Sweeping may release blocks and avoid a GC.
+        if !self.lazy_sweeper.is_complete() {
+            while VM::VMActivePlan::global().collection_required(false, self)
+                && self.lazy_sweep_one()
+            {}
+        }
         let block_address = self.acquire(tls, Block::PAGES);
         if block_address.is_zero() {
             return None;
         }
         self.defrag.notify_new_clean_block(copy);
         let block = Block::from(block_address);
+        // The other blocks in the chunk need to be swept before the chunk is used again.
+        self.lazy_sweeper
+            .ensure_swept(block.chunk().start(), |chunk| {
+                self.sweep_chunk(Chunk::from(chunk))
+            });
         block.init(copy);
         self.chunk_map.set(block.chunk(), ChunkState::Allocated);
         Some(block)
@@ -281,7 +390,11 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         if super::BLOCK_ONLY {
             return None;
         }
-        let result = self.reusable_blocks.pop();
+        let mut result = self.reusable_blocks.pop();
+        // Sweep more chunks to find reusable blocks.
+        while result.is_none() && self.lazy_sweep_one() {
+            result = self.reusable_blocks.pop();
+        }
         if let Some(block) = result {
             // println!("Reuse {:?}", block);
             block.init(copy);
@@ -571,3 +684,34 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanObjectsAndMarkLines<E> {
         }
     }
 }
+
+/// Sweep the chunks that were not swept after the last GC, in the `Prepare` stage of a GC.
+/// The last packet to finish waits for the chunks that other threads are sweeping, and prepares the blocks.
+struct PrepareLazySweep<VM: VMBinding> {
+    space: &'static ImmixSpace<VM>,
+    /// The number of packets that have not finished.
+    remaining: Arc<AtomicUsize>,
+}
+
+impl<VM: VMBinding> GCWork<VM> for PrepareLazySweep<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        while self.space.lazy_sweep_one() {}
+        if self.remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.space.complete_lazy_sweep();
+            self.space.prepare_blocks();
+        }
+    }
+}
+
+/// Sweep the chunks that are not swept yet, concurrently with the mutators.
+struct LazySweepChunks<VM: VMBinding> {
+    space: &'static ImmixSpace<VM>,
+}
+
+impl<VM: VMBinding> GCWork<VM> for LazySweepChunks<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        while self.space.lazy_sweep_one() {}
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
 
 use self::chunk::ChunkMap;
 
-use super::mallocspace::metadata::ACTIVE_PAGE_METADATA_SPEC;
+use super::mallocspace::metadata::OFFSET_MALLOC_METADATA_SPEC;
 
 /// Mark/sweep memory for block-level only
 pub const BLOCK_ONLY: bool = false;
@@ -38,8 +38,8 @@ fn validate_features() {
 }
 
 /// The start of immix side metadata is after the last MallocSpace side metadata.
-const IMMIX_LOCAL_SIDE_METADATA_BASE_OFFSET: SideMetadataOffset =
-    SideMetadataOffset::layout_after(&ACTIVE_PAGE_METADATA_SPEC);
+pub(crate) const IMMIX_LOCAL_SIDE_METADATA_BASE_OFFSET: SideMetadataOffset =
+    SideMetadataOffset::layout_after(&OFFSET_MALLOC_METADATA_SPEC);
 
 /// Immix's Last local side metadata. Used to calculate `LOCAL_SIDE_METADATA_VM_BASE_OFFSET`.
 pub const LAST_LOCAL_SIDE_METADATA: SideMetadataSpec = ChunkMap::ALLOC_TABLE;
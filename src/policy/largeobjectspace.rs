@@ -2,23 +2,30 @@ use atomic::Ordering;
 
 use crate::plan::PlanConstraints;
 use crate::plan::TransitiveClosure;
+use crate::plan::{AllocationSemantics, CopyContext};
 use crate::policy::space::SpaceOptions;
-use crate::policy::space::{CommonSpace, Space, SFT};
+use crate::policy::space::{CommonSpace, Space, SpaceStats, SFT};
 use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::conversions;
 use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
 use crate::util::heap::HeapMeta;
 use crate::util::heap::{FreeListPageResource, PageResource, VMRequest};
+use crate::util::memory;
 use crate::util::metadata;
 use crate::util::metadata::compare_exchange_metadata;
 use crate::util::metadata::load_metadata;
 use crate::util::metadata::side_metadata::SideMetadataContext;
 use crate::util::metadata::side_metadata::SideMetadataSpec;
 use crate::util::metadata::store_metadata;
+use crate::util::object_forwarding;
 use crate::util::opaque_pointer::*;
 use crate::util::treadmill::TreadMill;
 use crate::util::{Address, ObjectReference};
-use crate::vm::ObjectModel;
 use crate::vm::VMBinding;
+use crate::vm::{ActivePlan, Collection, ObjectModel};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::AtomicUsize;
+use std::sync::Mutex;
 
 #[allow(unused)]
 const PAGE_MASK: usize = !(BYTES_IN_PAGE - 1);
@@ -26,14 +33,117 @@ const MARK_BIT: usize = 0b01;
 const NURSERY_BIT: usize = 0b10;
 const LOS_BIT_MASK: usize = 0b11;
 
+/// The size (in pages) of the blocks that young objects are bump-allocated in. Larger objects get a block of their own.
+const YOUNG_BLOCK_PAGES: usize = 64;
+
+/// The largest cell (in pages) that we cache for reuse.
+const MAX_CACHED_CELL_PAGES: usize = 256;
+/// Number of size classes for cached cells. Cells of up to 4 pages have their own size classes.
+/// Larger cells are rounded up to one of 4 size classes between each power of two.
+const NUM_CACHED_SIZE_CLASSES: usize = 28;
+
+/// Get the size class of a cell of `pages` pages, and the pages of the size class.
+/// Returns None if we do not cache cells of this size.
+fn cached_size_class(pages: usize) -> Option<(usize, usize)> {
+    debug_assert!(pages > 0);
+    if pages > MAX_CACHED_CELL_PAGES {
+        None
+    } else if pages <= 4 {
+        Some((pages - 1, pages))
+    } else {
+        // pages is in (2^log, 2^(log+1)]
+        let log = (usize::BITS - 1 - (pages - 1).leading_zeros()) as usize;
+        let step = 1 << (log - 2);
+        let class_pages = conversions::raw_align_up(pages, step);
+        let class = 4 + (log - 2) * 4 + ((class_pages - (1 << log)) / step - 1);
+        Some((class, class_pages))
+    }
+}
+
+/// A block of the young region.
+struct RegionBlock {
+    pages: usize,
+    /// Was the block allocated after the last GC? Then all its objects were young at the start of this GC.
+    young: bool,
+    /// The number of mature objects in the block.
+    mature_objects: usize,
+}
+
+/// The young region of a large object space. Young objects are bump-allocated in blocks, and a GC releases
+/// a young block in bulk if none of its objects survive, so the dead young objects are never visited.
+/// The survivors are promoted in place: they are added to the treadmill, and their block is released
+/// when the last of them dies.
+struct YoungRegion {
+    /// All the blocks of the space, by their start address
+    blocks: BTreeMap<Address, RegionBlock>,
+    /// The blocks allocated since the last GC
+    young_blocks: Vec<Address>,
+    /// The free part of the block that young objects are bump-allocated in
+    cursor: Address,
+    limit: Address,
+}
+
+impl YoungRegion {
+    fn new() -> Self {
+        YoungRegion {
+            blocks: BTreeMap::new(),
+            young_blocks: vec![],
+            cursor: Address::ZERO,
+            limit: Address::ZERO,
+        }
+    }
+
+    fn add_block(&mut self, start: Address, pages: usize) {
+        self.blocks.insert(
+            start,
+            RegionBlock {
+                pages,
+                young: true,
+                mature_objects: 0,
+            },
+        );
+        self.young_blocks.push(start);
+    }
+
+    /// Stop bump-allocating in the current block. The next young object gets a new block.
+    fn retire_block(&mut self) {
+        self.cursor = Address::ZERO;
+        self.limit = Address::ZERO;
+    }
+
+    /// Get the block that contains a cell.
+    fn block_of(&mut self, cell: Address) -> (Address, &mut RegionBlock) {
+        let (start, block) = self.blocks.range_mut(..=cell).next_back().unwrap();
+        debug_assert!(cell < *start + conversions::pages_to_bytes(block.pages));
+        (*start, block)
+    }
+}
+
 /// This type implements a policy for large objects. Each instance corresponds
-/// to one Treadmill space.
+/// to one Treadmill space. Newly allocated objects are young, and they are allocated in the young region
+/// (see `YoungRegion`). They are only added to the treadmill if they survive a GC.
 pub struct LargeObjectSpace<VM: VMBinding> {
     common: CommonSpace<VM>,
     pr: FreeListPageResource<VM>,
     mark_state: usize,
     in_nursery_gc: bool,
+    /// Mature objects
     treadmill: TreadMill,
+    /// The blocks that the objects are allocated in
+    region: Mutex<YoungRegion>,
+    /// Do young objects share blocks? Otherwise each object has a block of its own, so the cell of each
+    /// dead object is released (and protected), moved objects do not keep their old block alive, and the
+    /// alloc bit of each dead object is cleared.
+    share_blocks: bool,
+    /// The cells reserved for the copies of the objects that are being forwarded in a moving GC
+    copy_cells: Mutex<HashMap<ObjectReference, Address>>,
+    /// Cells freed by the last GC, for each size class. We reuse them for new objects instead of releasing
+    /// their pages, and release them in the next GC if they are not reused.
+    free_cells: Vec<Mutex<Vec<Address>>>,
+    /// Pages of the cells in `free_cells`
+    cached_pages: AtomicUsize,
+    /// The maximum pages that we keep in `free_cells`. If this is 0, cells are not cached.
+    max_cached_pages: usize,
 }
 
 impl<VM: VMBinding> SFT for LargeObjectSpace<VM> {
@@ -41,10 +151,21 @@ impl<VM: VMBinding> SFT for LargeObjectSpace<VM> {
         self.get_name()
     }
     fn is_live(&self, object: ObjectReference) -> bool {
+        if self.common.movable && object_forwarding::is_forwarded::<VM>(object) {
+            return true;
+        }
         self.test_mark_bit(object, self.mark_state)
     }
     fn is_movable(&self) -> bool {
-        false
+        self.common.movable
+    }
+    #[inline(always)]
+    fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
+        if self.common.movable && object_forwarding::is_forwarded::<VM>(object) {
+            Some(object_forwarding::read_forwarding_pointer::<VM>(object))
+        } else {
+            None
+        }
     }
     #[cfg(feature = "sanity")]
     fn is_sane(&self) -> bool {
@@ -72,11 +193,19 @@ impl<VM: VMBinding> SFT for LargeObjectSpace<VM> {
         if self.common.needs_log_bit {
             VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_unlogged::<VM>(object, Ordering::SeqCst);
         }
+        if self.common.movable {
+            // The cell may have held a forwarded object before, or this is a copy of a forwarded object.
+            object_forwarding::clear_forwarding_bits::<VM>(object);
+        }
 
         #[cfg(feature = "global_alloc_bit")]
         crate::util::alloc_bit::set_alloc_bit(object);
-        let cell = VM::VMObjectModel::object_start_ref(object);
-        self.treadmill.add_to_treadmill(cell, alloc);
+        if !alloc {
+            // A copy is mature, and it keeps its block alive.
+            let cell = VM::VMObjectModel::object_start_ref(object);
+            self.treadmill.add_to_treadmill(cell);
+            self.region.lock().unwrap().block_of(cell).1.mature_objects += 1;
+        }
     }
 }
 
@@ -101,6 +230,23 @@ impl<VM: VMBinding> Space<VM> for LargeObjectSpace<VM> {
     fn release_multiple_pages(&mut self, start: Address) {
         self.pr.release_pages(start);
     }
+
+    /// The cached free cells are still reserved by the page resource, but they are not used by any object.
+    fn reserved_pages(&self) -> usize {
+        let data_pages = self.used_pages();
+        let meta_pages = self.common.metadata.calculate_reserved_pages(data_pages);
+        data_pages + meta_pages
+    }
+
+    fn space_stats(&self) -> SpaceStats {
+        SpaceStats {
+            name: self.get_name(),
+            reserved_pages: self.used_pages(),
+            committed_pages: self.pr.committed_pages(),
+            contiguous: self.common.contiguous,
+            regions: self.regions(),
+        }
+    }
 }
 
 impl<VM: VMBinding> LargeObjectSpace<VM> {
@@ -108,27 +254,34 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
     pub fn new(
         name: &'static str,
         zeroed: bool,
+        movable: bool,
         vmrequest: VMRequest,
         global_side_metadata_specs: Vec<SideMetadataSpec>,
         vm_map: &'static VMMap,
         mmapper: &'static Mmapper,
         heap: &mut HeapMeta,
         constraints: &'static PlanConstraints,
+        poison_memory_on_release: bool,
         protect_memory_on_release: bool,
+        max_cached_pages: usize,
     ) -> Self {
+        let mut local_specs = vec![*VM::VMObjectModel::LOCAL_LOS_MARK_NURSERY_SPEC];
+        if movable {
+            // Objects are copied to new cells by a moving PageProtect GC.
+            local_specs.push(*VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC);
+            local_specs.push(*VM::VMObjectModel::LOCAL_FORWARDING_POINTER_SPEC);
+        }
         let common = CommonSpace::new(
             SpaceOptions {
                 name,
-                movable: false,
+                movable,
                 immortal: false,
                 zeroed,
                 needs_log_bit: constraints.needs_log_bit,
                 vmrequest,
                 side_metadata_specs: SideMetadataContext {
                     global: global_side_metadata_specs,
-                    local: metadata::extract_side_metadata(&[
-                        *VM::VMObjectModel::LOCAL_LOS_MARK_NURSERY_SPEC,
-                    ]),
+                    local: metadata::extract_side_metadata(&local_specs),
                 },
             },
             vm_map,
@@ -140,13 +293,26 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
         } else {
             FreeListPageResource::new_contiguous(common.start, common.extent, 0, vm_map)
         };
+        pr.common_mut().poison_memory_on_release = poison_memory_on_release;
         pr.protect_memory_on_release = protect_memory_on_release;
+        // Cached cells are not released, so they would not be protected.
+        debug_assert!(!protect_memory_on_release || max_cached_pages == 0);
         LargeObjectSpace {
             pr,
             common,
             mark_state: 0,
             in_nursery_gc: false,
             treadmill: TreadMill::new(),
+            region: Mutex::new(YoungRegion::new()),
+            share_blocks: !movable
+                && !protect_memory_on_release
+                && !cfg!(feature = "global_alloc_bit"),
+            copy_cells: Mutex::new(HashMap::new()),
+            free_cells: (0..NUM_CACHED_SIZE_CLASSES)
+                .map(|_| Mutex::new(vec![]))
+                .collect(),
+            cached_pages: AtomicUsize::new(0),
+            max_cached_pages,
         }
     }
 
@@ -154,17 +320,22 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
         if full_heap {
             debug_assert!(self.treadmill.from_space_empty());
             self.mark_state = MARK_BIT - self.mark_state;
+            self.treadmill.flip();
         }
-        self.treadmill.flip(full_heap);
         self.in_nursery_gc = !full_heap;
+        self.region.get_mut().unwrap().retire_block();
     }
 
     pub fn release(&mut self, full_heap: bool) {
-        self.sweep_large_pages(true);
-        debug_assert!(self.treadmill.nursery_empty());
+        // The cells cached by the last GC that are not reused are released with the dead blocks of this GC.
+        let mut released = self.take_free_cells();
         if full_heap {
-            self.sweep_large_pages(false);
+            self.sweep_large_pages(&mut released);
         }
+        self.sweep_young_blocks(&mut released);
+        // Objects copied in this GC do not share blocks with the objects allocated after it.
+        self.region.get_mut().unwrap().retire_block();
+        self.pr.release_pages_bulk(released);
     }
     // Allow nested-if for this function to make it clear that test_and_mark() is only executed
     // for the outer condition is met.
@@ -185,7 +356,13 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
             // Note that test_and_mark() has side effects
             if self.test_and_mark(object, self.mark_state) {
                 let cell = VM::VMObjectModel::object_start_ref(object);
-                self.treadmill.copy(cell, nursery_object);
+                if nursery_object {
+                    // Promote the young object. It stays in place, and keeps its block alive.
+                    self.treadmill.add_to_treadmill(cell);
+                    self.region.lock().unwrap().block_of(cell).1.mature_objects += 1;
+                } else {
+                    self.treadmill.copy(cell);
+                }
                 self.clear_nursery(object);
                 trace.process_node(object);
             }
@@ -193,30 +370,234 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
         object
     }
 
-    fn sweep_large_pages(&mut self, sweep_nursery: bool) {
-        // FIXME: borrow checker fighting
-        // didn't call self.release_multiple_pages
-        // so the compiler knows I'm borrowing two different fields
-        if sweep_nursery {
-            for cell in self.treadmill.collect_nursery() {
-                // println!("- cn {}", cell);
-                #[cfg(feature = "global_alloc_bit")]
-                crate::util::alloc_bit::unset_addr_alloc_bit(cell);
-                self.pr.release_pages(get_super_page(cell));
+    /// Trace an object in a moving GC. A reachable object is copied to a new cell with the copy context,
+    /// and the references to it are updated to the copy. The cell of the old object is not marked, so it is
+    /// released at the end of the GC.
+    ///
+    /// The cell for the copy is acquired before the object is copied. If the space cannot get the pages,
+    /// the object is kept in place and marked, as in a non-moving GC, instead of failing the GC.
+    pub fn trace_object_and_move<T: TransitiveClosure, C: CopyContext>(
+        &self,
+        trace: &mut T,
+        object: ObjectReference,
+        copy_context: &mut C,
+        tls: VMWorkerThread,
+    ) -> ObjectReference {
+        debug_assert!(self.common.movable);
+        debug_assert!(!self.in_nursery_gc);
+        // An object that is kept in place is marked.
+        if self.test_mark_bit(object, self.mark_state) {
+            return object;
+        }
+        let forwarding_status = object_forwarding::attempt_to_forward::<VM>(object);
+        if object_forwarding::state_is_forwarded_or_being_forwarded(forwarding_status) {
+            return self.spin_and_get_forwarded_object(object, forwarding_status);
+        }
+        // Another thread may have kept the object in place after we checked the mark bit.
+        if self.test_mark_bit(object, self.mark_state) {
+            object_forwarding::clear_forwarding_bits::<VM>(object);
+            return object;
+        }
+        // Reserve enough pages for the copy at any alignment.
+        let bytes = crate::util::alloc::allocator::get_maximum_aligned_size::<VM>(
+            VM::VMObjectModel::get_current_size(object),
+            VM::MAX_ALIGNMENT,
+            VM::MIN_ALIGNMENT,
+        );
+        let pages = conversions::bytes_to_pages_up(bytes);
+        let cell = self.acquire_for_copy(tls.0, pages);
+        if cell.is_zero() {
+            warn!("No pages to copy {} to. It is kept in place.", object);
+            // Mark the object before clearing the forwarding bits, so other threads see it as kept in place.
+            self.test_and_mark(object, self.mark_state);
+            self.treadmill
+                .copy(VM::VMObjectModel::object_start_ref(object));
+            object_forwarding::clear_forwarding_bits::<VM>(object);
+            trace.process_node(object);
+            return object;
+        }
+        self.region.lock().unwrap().add_block(cell, pages);
+        self.copy_cells.lock().unwrap().insert(object, cell);
+        // The copy is allocated in the reserved cell (see `take_copy_cell()`), and is put on the treadmill
+        // as a marked object.
+        let new_object = object_forwarding::forward_object::<VM, _>(
+            object,
+            AllocationSemantics::Los,
+            copy_context,
+        );
+        trace.process_node(new_object);
+        new_object
+    }
+
+    /// Wait for another thread to forward an object. The object may be kept in place instead.
+    fn spin_and_get_forwarded_object(
+        &self,
+        object: ObjectReference,
+        forwarding_status: usize,
+    ) -> ObjectReference {
+        let mut forwarding_status = forwarding_status;
+        while object_forwarding::state_is_being_forwarded(forwarding_status) {
+            if self.test_mark_bit(object, self.mark_state) {
+                return object;
             }
+            forwarding_status = object_forwarding::get_forwarding_status::<VM>(object);
+        }
+        if object_forwarding::state_is_forwarded_or_being_forwarded(forwarding_status) {
+            object_forwarding::spin_and_get_forwarded_object::<VM>(object, forwarding_status)
         } else {
-            for cell in self.treadmill.collect() {
-                // println!("- ts {}", cell);
-                #[cfg(feature = "global_alloc_bit")]
-                crate::util::alloc_bit::unset_addr_alloc_bit(cell);
-                self.pr.release_pages(get_super_page(cell));
+            // The object was kept in place.
+            debug_assert!(self.test_mark_bit(object, self.mark_state));
+            object
+        }
+    }
+
+    /// Take the cell reserved for the copy of an object in `trace_object_and_move()`. The copy of `bytes`
+    /// bytes needs to fit in the cell.
+    pub fn take_copy_cell(&self, original: ObjectReference, bytes: usize) -> Address {
+        let cell = self.copy_cells.lock().unwrap().remove(&original).unwrap();
+        debug_assert!(bytes <= conversions::pages_to_bytes(self.pr.get_allocated_pages(cell)));
+        cell
+    }
+
+    /// Sweep the blocks allocated since the last GC. The young objects that survived this GC have been promoted,
+    /// so a block without mature objects is dead, and it is freed without visiting its objects.
+    fn sweep_young_blocks(&self, released: &mut Vec<Address>) {
+        let mut region = self.region.lock().unwrap();
+        for start in std::mem::take(&mut region.young_blocks) {
+            let block = region.blocks.get_mut(&start).unwrap();
+            block.young = false;
+            if block.mature_objects == 0 {
+                region.blocks.remove(&start);
+                self.free_block(start, released);
+            }
+        }
+    }
+
+    /// Sweep the dead mature objects. A block is freed when its last mature object dies.
+    fn sweep_large_pages(&self, released: &mut Vec<Address>) {
+        let mut region = self.region.lock().unwrap();
+        for cell in self.treadmill.collect() {
+            let (start, block) = region.block_of(cell);
+            block.mature_objects -= 1;
+            // The young blocks are swept after this.
+            if block.mature_objects == 0 && !block.young {
+                region.blocks.remove(&start);
+                self.free_block(start, released);
+            }
+        }
+    }
+
+    /// Free a dead block. The block is cached for reuse if possible. Otherwise it is added to `released`.
+    fn free_block(&self, start: Address, released: &mut Vec<Address>) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::bzero_alloc_bit(
+            start,
+            conversions::pages_to_bytes(self.pr.get_allocated_pages(start)),
+        );
+        if !self.cache_free_cell(start) {
+            released.push(start);
+        }
+    }
+
+    /// Cache a free cell for reuse. Return false if the cell cannot be cached.
+    fn cache_free_cell(&self, start: Address) -> bool {
+        if self.max_cached_pages == 0 {
+            return false;
+        }
+        let pages = self.pr.get_allocated_pages(start);
+        match cached_size_class(pages) {
+            Some((class, class_pages)) if class_pages == pages => {
+                if self.cached_pages.load(Ordering::Relaxed) + pages > self.max_cached_pages {
+                    return false;
+                }
+                self.cached_pages.fetch_add(pages, Ordering::Relaxed);
+                if self.pr.common().poison_memory_on_release {
+                    memory::poison(start, conversions::pages_to_bytes(pages));
+                }
+                self.free_cells[class].lock().unwrap().push(start);
+                true
             }
+            _ => false,
         }
     }
 
-    /// Allocate an object
+    /// Pages reserved by the page resource, excluding the cached free cells.
+    fn used_pages(&self) -> usize {
+        self.pr
+            .reserved_pages()
+            .saturating_sub(self.cached_pages.load(Ordering::Relaxed))
+    }
+
+    /// Take all the cached cells.
+    fn take_free_cells(&self) -> Vec<Address> {
+        self.cached_pages.store(0, Ordering::Relaxed);
+        self.free_cells
+            .iter()
+            .flat_map(|cells| std::mem::take(&mut *cells.lock().unwrap()))
+            .collect()
+    }
+
+    /// Allocate a cell of `pages` pages for an object. Small objects are bump-allocated in a shared young block.
     pub fn allocate_pages(&self, tls: VMThread, pages: usize) -> Address {
-        self.acquire(tls, pages)
+        let bytes = conversions::pages_to_bytes(pages);
+        if self.share_blocks && pages < YOUNG_BLOCK_PAGES {
+            {
+                let mut region = self.region.lock().unwrap();
+                if region.cursor + bytes <= region.limit {
+                    let cell = region.cursor;
+                    region.cursor += bytes;
+                    return cell;
+                }
+            }
+            // We cannot hold the lock while we get a block, as we may block for a GC.
+            let block = self.allocate_block(tls, YOUNG_BLOCK_PAGES);
+            if !block.is_zero() {
+                let mut region = self.region.lock().unwrap();
+                region.add_block(block, YOUNG_BLOCK_PAGES);
+                region.cursor = block + bytes;
+                region.limit = block + conversions::pages_to_bytes(YOUNG_BLOCK_PAGES);
+            }
+            return block;
+        }
+        let block = self.allocate_block(tls, pages);
+        if !block.is_zero() {
+            let pages = self.pr.get_allocated_pages(block);
+            self.region.lock().unwrap().add_block(block, pages);
+        }
+        block
+    }
+
+    /// Allocate a block of at least `pages` pages.
+    fn allocate_block(&self, tls: VMThread, pages: usize) -> Address {
+        if self.max_cached_pages == 0 {
+            return self.acquire(tls, pages);
+        }
+        match cached_size_class(pages) {
+            Some((class, class_pages)) => {
+                // Reuse a cached cell. Its pages are still reserved by the page resource, but cached pages are
+                // not counted as used, so we poll as if we acquired new pages.
+                let cell = self.free_cells[class].lock().unwrap().pop();
+                if let Some(cell) = cell {
+                    self.cached_pages.fetch_sub(class_pages, Ordering::Relaxed);
+                    if VM::VMActivePlan::is_mutator(tls)
+                        && VM::VMActivePlan::global().poll(false, self.as_space())
+                    {
+                        // The cell is released by the GC if it is not reused before.
+                        self.cached_pages.fetch_add(class_pages, Ordering::Relaxed);
+                        self.free_cells[class].lock().unwrap().push(cell);
+                        VM::VMCollection::block_for_gc(VMMutatorThread(tls));
+                        return unsafe { Address::zero() };
+                    }
+                    if self.common.zeroed {
+                        memory::zero(cell, conversions::pages_to_bytes(class_pages));
+                    }
+                    return cell;
+                }
+                // Allocate the whole size class so the cell can be cached when it is freed.
+                self.acquire(tls, class_pages)
+            }
+            None => self.acquire(tls, pages),
+        }
     }
 
     fn test_and_mark(&self, object: ObjectReference, value: usize) -> bool {
@@ -262,7 +643,7 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
     }
 
     /// Check if a given object is in nursery
-    fn is_in_nursery(&self, object: ObjectReference) -> bool {
+    pub(crate) fn is_in_nursery(&self, object: ObjectReference) -> bool {
         load_metadata::<VM>(
             &VM::VMObjectModel::LOCAL_LOS_MARK_NURSERY_SPEC,
             object,
@@ -297,6 +678,38 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
     }
 }
 
-fn get_super_page(cell: Address) -> Address {
-    cell.align_down(BYTES_IN_PAGE)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_size_class() {
+        assert_eq!(cached_size_class(1), Some((0, 1)));
+        assert_eq!(cached_size_class(4), Some((3, 4)));
+        assert_eq!(cached_size_class(5), Some((4, 5)));
+        assert_eq!(cached_size_class(8), Some((7, 8)));
+        assert_eq!(cached_size_class(9), Some((8, 10)));
+        // 64 KB
+        assert_eq!(cached_size_class(16), Some((11, 16)));
+        assert_eq!(cached_size_class(17), Some((12, 20)));
+        // 1 MB
+        assert_eq!(
+            cached_size_class(MAX_CACHED_CELL_PAGES),
+            Some((NUM_CACHED_SIZE_CLASSES - 1, MAX_CACHED_CELL_PAGES))
+        );
+        assert_eq!(cached_size_class(MAX_CACHED_CELL_PAGES + 1), None);
+    }
+
+    #[test]
+    fn test_cached_size_classes() {
+        // Each size class holds the pages it is used for, and the classes are in order.
+        let mut last = cached_size_class(1).unwrap();
+        for pages in 2..=MAX_CACHED_CELL_PAGES {
+            let (class, class_pages) = cached_size_class(pages).unwrap();
+            assert!(class_pages >= pages);
+            assert!(class < NUM_CACHED_SIZE_CLASSES);
+            assert!((class, class_pages) == last || class == last.0 + 1 && last.1 == pages - 1);
+            last = (class, class_pages);
+        }
+    }
 }
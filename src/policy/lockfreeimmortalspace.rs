@@ -1,5 +1,5 @@
 use crate::mmtk::SFT_MAP;
-use crate::policy::space::{CommonSpace, Space, SFT};
+use crate::policy::space::{CommonSpace, Space, SpaceStats, SFT};
 use crate::util::address::Address;
 use crate::util::conversions::bytes_to_chunks_up;
 use crate::util::heap::PageResource;
@@ -8,9 +8,7 @@ use crate::util::ObjectReference;
 
 use crate::util::conversions;
 use crate::util::heap::layout::heap_layout::VMMap;
-use crate::util::heap::layout::vm_layout_constants::{
-    AVAILABLE_BYTES, AVAILABLE_END, AVAILABLE_START,
-};
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::metadata::side_metadata::SideMetadataSanity;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSpec};
 use crate::util::opaque_pointer::*;
@@ -87,28 +85,25 @@ impl<VM: VMBinding> Space<VM> for LockFreeImmortalSpace<VM> {
             .load(Ordering::SeqCst);
         let total_bytes = conversions::pages_to_bytes(total_pages);
         assert!(total_pages > 0);
+        let start = vm_layout().available_start();
         assert!(
-            total_bytes <= AVAILABLE_BYTES,
+            total_bytes <= vm_layout().available_bytes(),
             "Initial requested memory ({} bytes) overflows the heap. Max heap size is {} bytes.",
             total_bytes,
-            AVAILABLE_BYTES
+            vm_layout().available_bytes()
         );
-        self.limit = AVAILABLE_START + total_bytes;
+        self.limit = start + total_bytes;
         // Eagerly memory map the entire heap (also zero all the memory)
-        crate::util::memory::dzmmap_noreplace(AVAILABLE_START, total_bytes).unwrap();
+        crate::util::memory::dzmmap_noreplace(start, total_bytes).unwrap();
         if self
             .metadata
-            .try_map_metadata_space(AVAILABLE_START, total_bytes)
+            .try_map_metadata_space(start, total_bytes)
             .is_err()
         {
             // TODO(Javad): handle meta space allocation failure
             panic!("failed to mmap meta memory");
         }
-        SFT_MAP.update(
-            self.as_sft(),
-            AVAILABLE_START,
-            bytes_to_chunks_up(total_bytes),
-        );
+        SFT_MAP.update(self.as_sft(), start, bytes_to_chunks_up(total_bytes));
     }
 
     fn reserved_pages(&self) -> usize {
@@ -138,6 +133,29 @@ impl<VM: VMBinding> Space<VM> for LockFreeImmortalSpace<VM> {
         "LockFreeImmortalSpace"
     }
 
+    /// We have to override the default implementation because
+    /// LockFreeImmortalSpace doesn't have a page resource
+    fn space_stats(&self) -> SpaceStats {
+        let cursor = unsafe { Address::from_usize(self.cursor.load(Ordering::Relaxed)) };
+        let pages = conversions::bytes_to_pages_up(cursor - vm_layout().available_start());
+        SpaceStats {
+            name: self.get_name(),
+            reserved_pages: pages,
+            committed_pages: pages,
+            contiguous: true,
+            regions: vec![(vm_layout().available_start(), self.limit)],
+        }
+    }
+
+    /// The whole heap range and its metadata are already mapped in `init()`.
+    fn eagerly_map(&self, _bytes: usize) -> Vec<(Address, usize)> {
+        let start = vm_layout().available_start();
+        let size = self.limit - start;
+        let mut ranges = vec![(start, size)];
+        ranges.extend(self.metadata.metadata_address_ranges(start, size));
+        ranges
+    }
+
     /// We have to override the default implementation because
     /// LockFreeImmortalSpace doesn't put metadata in a common space
     fn verify_side_metadata_sanity(&self, side_metadata_sanity_checker: &mut SideMetadataSanity) {
@@ -155,8 +173,8 @@ impl<VM: VMBinding> LockFreeImmortalSpace<VM> {
     ) -> Self {
         Self {
             name,
-            cursor: AtomicUsize::new(AVAILABLE_START.as_usize()),
-            limit: AVAILABLE_END,
+            cursor: AtomicUsize::new(vm_layout().available_start().as_usize()),
+            limit: vm_layout().available_end(),
             slow_path_zeroing,
             metadata: SideMetadataContext {
                 global: global_side_metadata_specs,
@@ -1,10 +1,12 @@
 use super::metadata::*;
 use crate::plan::TransitiveClosure;
 use crate::policy::space::CommonSpace;
+use crate::policy::space::SpaceStats;
 use crate::policy::space::SFT;
-use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::constants::{BYTES_IN_ADDRESS, BYTES_IN_PAGE};
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::PageResource;
+use crate::util::lazy_sweep::LazySweeper;
 use crate::util::malloc::*;
 use crate::util::metadata::side_metadata::{
     bzero_metadata, SideMetadataContext, SideMetadataSanity, SideMetadataSpec,
@@ -27,6 +29,10 @@ use std::collections::HashMap;
 #[cfg(debug_assertions)]
 use std::sync::Mutex;
 
+// The alignment of the addresses returned by malloc. Objects that need a larger alignment (or an offset)
+// are allocated from an over-sized malloc region.
+const MALLOC_ALIGNMENT: usize = 2 * BYTES_IN_ADDRESS;
+
 // If true, we will use a hashmap to store all the allocated memory from malloc, and use it
 // to make sure our allocation is correct.
 #[cfg(debug_assertions)]
@@ -38,6 +44,10 @@ pub struct MallocSpace<VM: VMBinding> {
     pub chunk_addr_min: AtomicUsize, // XXX: have to use AtomicUsize to represent an Address
     pub chunk_addr_max: AtomicUsize,
     metadata: SideMetadataContext,
+    /// Chunks to be swept after mutators resume, if lazy sweeping is enabled.
+    lazy_sweeper: LazySweeper,
+    /// Fill the cells with `memory::POISON_PATTERN` before they are freed (see the `poison_on_release` option).
+    poison_memory_on_release: bool,
     // Mapping between allocated address and its size - this is used to check correctness.
     // Size will be set to zero when the memory is freed.
     #[cfg(debug_assertions)]
@@ -156,6 +166,23 @@ impl<VM: VMBinding> Space<VM> for MallocSpace<VM> {
         data_pages + meta_pages
     }
 
+    /// Memory in malloc space is managed by malloc, so it does not have an address range.
+    fn space_stats(&self) -> SpaceStats {
+        let pages = conversions::bytes_to_pages_up(self.active_bytes.load(Ordering::SeqCst));
+        SpaceStats {
+            name: self.get_name(),
+            reserved_pages: pages,
+            committed_pages: pages,
+            contiguous: false,
+            regions: vec![],
+        }
+    }
+
+    /// Memory in malloc space is mapped by malloc when objects are allocated.
+    fn eagerly_map(&self, _bytes: usize) -> Vec<(Address, usize)> {
+        vec![]
+    }
+
     fn verify_side_metadata_sanity(&self, side_metadata_sanity_checker: &mut SideMetadataSanity) {
         side_metadata_sanity_checker
             .verify_metadata_context(std::any::type_name::<Self>(), &self.metadata)
@@ -163,7 +190,10 @@ impl<VM: VMBinding> Space<VM> for MallocSpace<VM> {
 }
 
 impl<VM: VMBinding> MallocSpace<VM> {
-    pub fn new(global_side_metadata_specs: Vec<SideMetadataSpec>) -> Self {
+    pub fn new(
+        global_side_metadata_specs: Vec<SideMetadataSpec>,
+        poison_memory_on_release: bool,
+    ) -> Self {
         MallocSpace {
             phantom: PhantomData,
             active_bytes: AtomicUsize::new(0),
@@ -173,9 +203,12 @@ impl<VM: VMBinding> MallocSpace<VM> {
                 global: global_side_metadata_specs,
                 local: metadata::extract_side_metadata(&[
                     MetadataSpec::OnSide(ACTIVE_PAGE_METADATA_SPEC),
+                    MetadataSpec::OnSide(OFFSET_MALLOC_METADATA_SPEC),
                     *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
                 ]),
             },
+            lazy_sweeper: LazySweeper::new(),
+            poison_memory_on_release,
             #[cfg(debug_assertions)]
             active_mem: Mutex::new(HashMap::new()),
             #[cfg(debug_assertions)]
@@ -187,7 +220,14 @@ impl<VM: VMBinding> MallocSpace<VM> {
         }
     }
 
-    pub fn alloc(&self, tls: VMThread, size: usize) -> Address {
+    pub fn alloc(&self, tls: VMThread, size: usize, align: usize, offset: isize) -> Address {
+        // Free the dead objects of unswept chunks before deciding whether we need a GC.
+        if !self.lazy_sweeper.is_complete() {
+            while VM::VMActivePlan::global().collection_required(false, self)
+                && self.lazy_sweep_one()
+            {}
+        }
+
         // TODO: Should refactor this and Space.acquire()
         if VM::VMActivePlan::global().poll(false, self) {
             assert!(VM::VMActivePlan::is_mutator(tls), "Polling in GC worker");
@@ -195,15 +235,32 @@ impl<VM: VMBinding> MallocSpace<VM> {
             return unsafe { Address::zero() };
         }
 
-        let raw = unsafe { calloc(1, size) };
-        let address = Address::from_mut_ptr(raw);
-
-        if !address.is_zero() {
-            let actual_size = unsafe { malloc_usable_size(raw) };
-            // If the side metadata for the address has not yet been mapped, we will map all the side metadata for the range [address, address + actual_size).
-            if !is_meta_space_mapped(address, actual_size) {
+        let (raw, address) = if align <= MALLOC_ALIGNMENT && offset == 0 {
+            let raw = Address::from_mut_ptr(unsafe { calloc(1, size) });
+            (raw, raw)
+        } else {
+            Self::offset_alloc(size, align, offset)
+        };
+
+        if !raw.is_zero() {
+            let actual_size = unsafe { malloc_usable_size(raw.to_mut_ptr()) };
+            // The memory may be in chunks that are not swept yet. Sweep them first, otherwise
+            // the new object would be freed as it is not marked.
+            if !self.lazy_sweeper.is_complete() {
+                let mut chunk = conversions::chunk_align_down(raw);
+                while chunk < raw + actual_size {
+                    self.lazy_sweeper
+                        .ensure_swept(chunk, |chunk| self.sweep_chunk(chunk));
+                    chunk += BYTES_IN_CHUNK;
+                }
+            }
+            // If the side metadata for the address has not yet been mapped, we will map all the side metadata for the range [raw, raw + actual_size).
+            if !is_meta_space_mapped(raw, actual_size) {
                 // Map the metadata space for the associated chunk
-                self.map_metadata_and_update_bound(address, actual_size);
+                self.map_metadata_and_update_bound(raw, actual_size);
+            }
+            if address != raw {
+                set_offset_malloc_bit(address);
             }
             self.active_bytes.fetch_add(actual_size, Ordering::SeqCst);
 
@@ -217,10 +274,61 @@ impl<VM: VMBinding> MallocSpace<VM> {
         address
     }
 
+    /// Allocate `size` bytes from malloc so that the returned address plus `offset` is aligned to `align`.
+    /// We over-allocate, and store the address returned by malloc in the word before the returned address.
+    /// Returns the address returned by malloc and the aligned address.
+    fn offset_alloc(size: usize, align: usize, offset: isize) -> (Address, Address) {
+        debug_assert!(align.is_power_of_two());
+        let raw = Address::from_mut_ptr(unsafe { calloc(1, size + align + BYTES_IN_ADDRESS) });
+        if raw.is_zero() {
+            return (raw, raw);
+        }
+        let start = raw + BYTES_IN_ADDRESS;
+        let delta = (-offset - start.as_usize() as isize) & (align as isize - 1);
+        let address = start + delta as usize;
+        unsafe {
+            (address - BYTES_IN_ADDRESS)
+                .to_mut_ptr::<usize>()
+                .write_unaligned(raw.as_usize())
+        };
+        (raw, address)
+    }
+
+    /// Get the address returned by malloc for an object start.
+    fn get_malloc_addr(obj_start: Address, offset_malloc: bool) -> Address {
+        if offset_malloc {
+            unsafe {
+                Address::from_usize(
+                    (obj_start - BYTES_IN_ADDRESS)
+                        .to_ptr::<usize>()
+                        .read_unaligned(),
+                )
+            }
+        } else {
+            obj_start
+        }
+    }
+
+    /// Get the address returned by malloc for an object start, whether the object was allocated
+    /// with `offset_alloc()`, and the usable size of the malloc'd memory.
+    fn get_malloc_addr_size(obj_start: Address) -> (Address, bool, usize) {
+        let offset_malloc = is_offset_malloc(obj_start);
+        let malloc_addr = Self::get_malloc_addr(obj_start, offset_malloc);
+        let bytes = unsafe { malloc_usable_size(malloc_addr.to_mut_ptr()) };
+        (malloc_addr, offset_malloc, bytes)
+    }
+
     // XXX optimize: We pass the bytes in to free as otherwise there were multiple
     // indirect call instructions in the generated assembly
-    pub fn free(&self, addr: Address, bytes: usize) {
-        let ptr = addr.to_mut_ptr();
+    pub fn free(&self, addr: Address, bytes: usize, offset_malloc: bool) {
+        let malloc_addr = Self::get_malloc_addr(addr, offset_malloc);
+        if self.poison_memory_on_release {
+            crate::util::memory::poison(malloc_addr, bytes);
+        }
+        let ptr = malloc_addr.to_mut_ptr();
+        if offset_malloc {
+            unset_offset_malloc_bit(addr);
+        }
         trace!("Free memory {:?}", ptr);
         unsafe {
             free(ptr);
@@ -304,6 +412,16 @@ impl<VM: VMBinding> MallocSpace<VM> {
         }
     }
 
+    /// Queue chunks to be swept after mutators resume, instead of sweeping them in this GC.
+    pub fn add_lazy_sweep_chunks(&self, chunks: Vec<Address>) {
+        self.lazy_sweeper.add_chunks(chunks);
+    }
+
+    /// Sweep one of the chunks that are not swept yet. Return false if there is no such chunk.
+    pub fn lazy_sweep_one(&self) -> bool {
+        self.lazy_sweeper.sweep_one(|chunk| self.sweep_chunk(chunk))
+    }
+
     pub fn sweep_chunk(&self, chunk_start: Address) {
         // Call the relevant sweep function depending on the location of the mark bits
         match *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC {
@@ -390,14 +508,17 @@ impl<VM: VMBinding> MallocSpace<VM> {
                     if unsafe { is_alloced_object_unsafe(address) } {
                         let object = unsafe { address.to_object_reference() };
                         let obj_start = VM::VMObjectModel::object_start_ref(object);
-                        let bytes = unsafe { malloc_usable_size(obj_start.to_mut_ptr()) };
+                        let (malloc_addr, offset_malloc, bytes) =
+                            Self::get_malloc_addr_size(obj_start);
+                        // The malloc'd memory ends `skip` bytes after the object start.
+                        let skip = bytes - (obj_start - malloc_addr);
 
                         if !is_marked::<VM>(object, None) {
                             // Dead object
                             trace!("Object {} has been allocated but not marked", object);
 
                             // Free object
-                            self.free(obj_start, bytes);
+                            self.free(obj_start, bytes, offset_malloc);
                             trace!("free object {}", object);
                             unsafe { unset_alloc_bit_unsafe(object) };
                         } else {
@@ -406,13 +527,13 @@ impl<VM: VMBinding> MallocSpace<VM> {
                             chunk_is_empty = false;
                             page_is_empty = false;
 
-                            if address + bytes - page > BYTES_IN_PAGE {
+                            if address + skip - page > BYTES_IN_PAGE {
                                 last_on_page_boundary = true;
                             }
                         }
 
                         // Skip to next object
-                        address += bytes;
+                        address += skip;
                     } else {
                         // not an object
                         address += VM::MIN_ALIGNMENT;
@@ -443,7 +564,8 @@ impl<VM: VMBinding> MallocSpace<VM> {
                 if unsafe { is_alloced_object_unsafe(address) } {
                     let object = unsafe { address.to_object_reference() };
                     let obj_start = VM::VMObjectModel::object_start_ref(object);
-                    let bytes = unsafe { malloc_usable_size(obj_start.to_mut_ptr()) };
+                    let (malloc_addr, _, bytes) = Self::get_malloc_addr_size(obj_start);
+                    let skip = bytes - (obj_start - malloc_addr);
 
                     #[cfg(debug_assertions)]
                     if ASSERT_ALLOCATION {
@@ -469,7 +591,7 @@ impl<VM: VMBinding> MallocSpace<VM> {
                     live_bytes += bytes;
 
                     // Skip to next object
-                    address += bytes;
+                    address += skip;
                 } else {
                     // not an object
                     address += VM::MIN_ALIGNMENT;
@@ -545,7 +667,9 @@ impl<VM: VMBinding> MallocSpace<VM> {
             if unsafe { is_alloced_object_unsafe(address) } {
                 let object = unsafe { address.to_object_reference() };
                 let obj_start = VM::VMObjectModel::object_start_ref(object);
-                let bytes = unsafe { malloc_usable_size(obj_start.to_mut_ptr()) };
+                let (malloc_addr, offset_malloc, bytes) = Self::get_malloc_addr_size(obj_start);
+                // The malloc'd memory ends `skip` bytes after the object start.
+                let skip = bytes - (obj_start - malloc_addr);
 
                 #[cfg(debug_assertions)]
                 if ASSERT_ALLOCATION {
@@ -567,7 +691,7 @@ impl<VM: VMBinding> MallocSpace<VM> {
                     trace!("Object {} has been allocated but not marked", object);
 
                     // Free object
-                    self.free(obj_start, bytes);
+                    self.free(obj_start, bytes, offset_malloc);
                     trace!("free object {}", object);
                     unsafe { unset_alloc_bit_unsafe(object) };
                 } else {
@@ -577,7 +701,7 @@ impl<VM: VMBinding> MallocSpace<VM> {
                     chunk_is_empty = false;
                     page_is_empty = false;
 
-                    if address + bytes - page > BYTES_IN_PAGE {
+                    if address + skip - page > BYTES_IN_PAGE {
                         last_on_page_boundary = true;
                     }
 
@@ -589,7 +713,7 @@ impl<VM: VMBinding> MallocSpace<VM> {
                 }
 
                 // Skip to next object
-                address += bytes;
+                address += skip;
             } else {
                 // not an object
                 address += VM::MIN_ALIGNMENT;
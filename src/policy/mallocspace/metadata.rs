@@ -24,8 +24,9 @@ lazy_static! {
     /// Lock to synchronize the mapping of side metadata for a newly allocated chunk by malloc
     static ref CHUNK_MAP_LOCK: Mutex<()> = Mutex::new(());
     /// Maximum metadata address for the ACTIVE_CHUNK_METADATA_SPEC which is used to check bounds
-    static ref MAX_METADATA_ADDRESS: Address =
-        ACTIVE_CHUNK_METADATA_SPEC.get_absolute_offset() + (1_usize << LOG_MAX_GLOBAL_SIDE_METADATA_SIZE);
+    static ref MAX_METADATA_ADDRESS: Address = ACTIVE_CHUNK_METADATA_SPEC.get_absolute_offset()
+        + side_metadata::side_metadata_relocation()
+        + (1_usize << LOG_MAX_GLOBAL_SIDE_METADATA_SIZE);
 }
 
 /// Metadata spec for the active chunk byte
@@ -41,6 +42,7 @@ lazy_static! {
 /// we require its space to be contiguous and mapped only once. Otherwise we risk
 /// overwriting the previous mapping.
 pub(crate) const ACTIVE_CHUNK_METADATA_SPEC: SideMetadataSpec = SideMetadataSpec {
+    name: "MSActiveChunk",
     is_global: true,
     offset: SideMetadataOffset::layout_after(&crate::util::alloc_bit::ALLOC_SIDE_METADATA_SPEC),
     log_num_of_bits: 3,
@@ -58,12 +60,27 @@ pub(crate) const ACTIVE_CHUNK_METADATA_SPEC: SideMetadataSpec = SideMetadataSpec
 // XXX: This metadata spec is currently unused as we need to add a performant way to calculate
 // how many pages are active in this metadata spec. Explore SIMD vectorization with 8-bit integers
 pub(crate) const ACTIVE_PAGE_METADATA_SPEC: SideMetadataSpec = SideMetadataSpec {
+    name: "MSActivePage",
     is_global: false,
     offset: LOCAL_SIDE_METADATA_BASE_OFFSET,
     log_num_of_bits: 3,
     log_min_obj_size: constants::LOG_BYTES_IN_PAGE as usize,
 };
 
+/// Metadata spec for the offset malloc bit
+///
+/// An object that needs a larger alignment than malloc provides, or an offset, is allocated
+/// from an over-sized malloc region, so the object start is not the address returned by `malloc()`.
+/// This bit is set for such objects, and the address returned by `malloc()` is stored in the word
+/// before the object start. We use it to find the right pointer to free.
+pub(crate) const OFFSET_MALLOC_METADATA_SPEC: SideMetadataSpec = SideMetadataSpec {
+    name: "MSOffsetMalloc",
+    is_global: false,
+    offset: SideMetadataOffset::layout_after(&ACTIVE_PAGE_METADATA_SPEC),
+    log_num_of_bits: 0,
+    log_min_obj_size: constants::LOG_MIN_OBJECT_SIZE as usize,
+};
+
 /// Check if metadata is mapped for a range [addr, addr + size). Metadata is mapped per chunk,
 /// we will go through all the chunks for [address, address + size), and check if they are mapped.
 /// If any of the chunks is not mapped, return false. Otherwise return true.
@@ -223,6 +240,18 @@ pub fn set_alloc_bit(object: ObjectReference) {
     alloc_bit::set_alloc_bit(object);
 }
 
+pub fn is_offset_malloc(address: Address) -> bool {
+    side_metadata::load_atomic(&OFFSET_MALLOC_METADATA_SPEC, address, Ordering::SeqCst) == 1
+}
+
+pub fn set_offset_malloc_bit(address: Address) {
+    side_metadata::store_atomic(&OFFSET_MALLOC_METADATA_SPEC, address, 1, Ordering::SeqCst);
+}
+
+pub fn unset_offset_malloc_bit(address: Address) {
+    side_metadata::store_atomic(&OFFSET_MALLOC_METADATA_SPEC, address, 0, Ordering::SeqCst);
+}
+
 pub fn set_mark_bit<VM: VMBinding>(object: ObjectReference, ordering: Option<Ordering>) {
     store_metadata::<VM>(
         &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
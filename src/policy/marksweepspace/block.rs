@@ -0,0 +1,321 @@
+use super::MarkSweepSpace;
+use crate::policy::immix::chunk::Chunk;
+use crate::util::constants::*;
+use crate::util::metadata::side_metadata::{self, SideMetadataOffset, SideMetadataSpec};
+use crate::util::metadata::{self, MetadataSpec};
+use crate::util::{alloc_bit, memory};
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use std::sync::atomic::Ordering;
+
+/// Number of size classes.
+pub const NUM_SIZE_CLASSES: usize = 35;
+
+/// The cell size (in bytes) of each size class. A cell holds at most one object.
+pub const SIZE_CLASSES: [usize; NUM_SIZE_CLASSES] = [
+    16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 448, 512, 640, 768,
+    896, 1024, 1280, 1536, 1792, 2048, 2560, 3072, 3584, 4096, 5120, 6144, 7168, 8192,
+];
+
+/// The largest cell. Larger objects need to be allocated in the large object space.
+pub const MAX_CELL_BYTES: usize = SIZE_CLASSES[NUM_SIZE_CLASSES - 1];
+
+/// Get the smallest size class whose cells can hold `bytes` bytes.
+/// Returns None if `bytes` is larger than the largest cell.
+#[inline(always)]
+pub fn size_class(bytes: usize) -> Option<usize> {
+    if bytes > MAX_CELL_BYTES {
+        None
+    } else {
+        Some(SIZE_CLASSES.partition_point(|&cell| cell < bytes))
+    }
+}
+
+/// Data structure to reference a mark-sweep block. Each block is divided into cells of its size class.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
+pub struct Block(Address);
+
+impl Block {
+    /// Log bytes in block
+    pub const LOG_BYTES: usize = 16;
+    /// Bytes in block
+    pub const BYTES: usize = 1 << Self::LOG_BYTES;
+    /// Log pages in block
+    pub const LOG_PAGES: usize = Self::LOG_BYTES - LOG_BYTES_IN_PAGE as usize;
+    /// Pages in block
+    pub const PAGES: usize = 1 << Self::LOG_PAGES;
+
+    /// Block state table (side). A block is either unallocated (0) or allocated (1).
+    /// No plan uses both this space and Immix, so the local side metadata of this space starts where Immix's does.
+    pub const STATE_TABLE: SideMetadataSpec = SideMetadataSpec {
+        name: "MSBlockState",
+        is_global: false,
+        offset: crate::policy::immix::IMMIX_LOCAL_SIDE_METADATA_BASE_OFFSET,
+        log_num_of_bits: 3,
+        log_min_obj_size: Self::LOG_BYTES,
+    };
+
+    /// Block size class table (side)
+    pub const SIZE_CLASS_TABLE: SideMetadataSpec = SideMetadataSpec {
+        name: "MSBlockSizeClass",
+        is_global: false,
+        offset: SideMetadataOffset::layout_after(&Self::STATE_TABLE),
+        log_num_of_bits: 3,
+        log_min_obj_size: Self::LOG_BYTES,
+    };
+
+    /// Block free list table (side). This is the first free cell of a block that is not used by any allocator.
+    pub const FREE_LIST_TABLE: SideMetadataSpec = SideMetadataSpec {
+        name: "MSBlockFreeList",
+        is_global: false,
+        offset: SideMetadataOffset::layout_after(&Self::SIZE_CLASS_TABLE),
+        log_num_of_bits: LOG_BITS_IN_WORD,
+        log_min_obj_size: Self::LOG_BYTES,
+    };
+
+    const UNALLOCATED: usize = 0;
+    const ALLOCATED: usize = 1;
+
+    /// Get the block from a given address.
+    /// The address must be block-aligned.
+    #[inline(always)]
+    pub fn from(address: Address) -> Self {
+        debug_assert!(address.is_aligned_to(Self::BYTES));
+        Self(address)
+    }
+
+    /// Get the block containing the given address.
+    #[inline(always)]
+    pub fn containing(address: Address) -> Self {
+        Self(address.align_down(Self::BYTES))
+    }
+
+    /// Get all the blocks in a chunk.
+    #[inline(always)]
+    pub fn blocks_in_chunk(chunk: Chunk) -> impl Iterator<Item = Block> {
+        (0..Chunk::BYTES >> Self::LOG_BYTES)
+            .map(move |i| Block::from(chunk.start() + (i << Self::LOG_BYTES)))
+    }
+
+    /// Get block start address
+    pub const fn start(&self) -> Address {
+        self.0
+    }
+
+    /// Get block end address
+    pub const fn end(&self) -> Address {
+        self.0.add(Self::BYTES)
+    }
+
+    /// Get the chunk containing the block.
+    #[inline(always)]
+    pub fn chunk(&self) -> Chunk {
+        Chunk::from(Chunk::align(self.0))
+    }
+
+    /// Is the block allocated?
+    #[inline(always)]
+    pub fn is_allocated(&self) -> bool {
+        side_metadata::load_atomic(&Self::STATE_TABLE, self.start(), Ordering::SeqCst)
+            == Self::ALLOCATED
+    }
+
+    /// Get the size class of the block.
+    #[inline(always)]
+    pub fn size_class(&self) -> usize {
+        side_metadata::load_atomic(&Self::SIZE_CLASS_TABLE, self.start(), Ordering::SeqCst)
+    }
+
+    /// Get the cell size of the block.
+    #[inline(always)]
+    pub fn cell_bytes(&self) -> usize {
+        SIZE_CLASSES[self.size_class()]
+    }
+
+    /// Get the start addresses of all the cells in the block.
+    #[inline(always)]
+    pub fn cells(&self) -> impl DoubleEndedIterator<Item = Address> {
+        let start = self.start();
+        let cell_bytes = self.cell_bytes();
+        (0..Self::BYTES / cell_bytes).map(move |i| start + i * cell_bytes)
+    }
+
+    /// Take the free list of the block. The block has no free cells after this until it is swept again.
+    #[inline]
+    pub fn take_free_list(&self) -> Address {
+        let head =
+            side_metadata::load_atomic(&Self::FREE_LIST_TABLE, self.start(), Ordering::SeqCst);
+        side_metadata::store_atomic(&Self::FREE_LIST_TABLE, self.start(), 0, Ordering::SeqCst);
+        unsafe { Address::from_usize(head) }
+    }
+
+    /// Set the free list of the block.
+    #[inline]
+    fn set_free_list(&self, head: Address) {
+        side_metadata::store_atomic(
+            &Self::FREE_LIST_TABLE,
+            self.start(),
+            head.as_usize(),
+            Ordering::SeqCst,
+        );
+    }
+
+    /// Initialize a block acquired from the page resource. All the cells are free.
+    #[inline]
+    pub fn init(&self, size_class: usize) {
+        debug_assert!(size_class < NUM_SIZE_CLASSES);
+        // Pages released by this space may be dirty.
+        memory::zero(self.start(), Self::BYTES);
+        side_metadata::store_atomic(
+            &Self::SIZE_CLASS_TABLE,
+            self.start(),
+            size_class,
+            Ordering::SeqCst,
+        );
+        side_metadata::store_atomic(
+            &Self::STATE_TABLE,
+            self.start(),
+            Self::ALLOCATED,
+            Ordering::SeqCst,
+        );
+        self.set_free_list(Self::link_cells(self.cells().rev(), |_| true));
+    }
+
+    /// Deinitalize a block before releasing.
+    #[inline]
+    pub fn deinit(&self) {
+        side_metadata::store_atomic(
+            &Self::STATE_TABLE,
+            self.start(),
+            Self::UNALLOCATED,
+            Ordering::SeqCst,
+        );
+        self.set_free_list(Address::ZERO);
+    }
+
+    /// Link the cells that `is_free` returns true for into a free list. The first word of each free cell
+    /// points to the next free cell. As `cells` are usually in the reverse order, the list is in address order.
+    #[inline(always)]
+    fn link_cells(
+        cells: impl Iterator<Item = Address>,
+        mut is_free: impl FnMut(Address) -> bool,
+    ) -> Address {
+        let mut head = Address::ZERO;
+        for cell in cells {
+            if is_free(cell) {
+                unsafe { cell.store(head) };
+                head = cell;
+            }
+        }
+        head
+    }
+
+    /// Find the object in a cell with the alloc bit, or None if the cell is free.
+    #[inline(always)]
+    fn object_in_cell(cell: Address, cell_bytes: usize) -> Option<ObjectReference> {
+        let mut address = cell;
+        while address < cell + cell_bytes {
+            if unsafe { alloc_bit::is_alloced_object_unsafe(address) } {
+                return Some(unsafe { address.to_object_reference() });
+            }
+            address += MIN_OBJECT_SIZE;
+        }
+        None
+    }
+
+    /// Sweep this block. Dead objects are freed, and the free cells are linked into the block's free list.
+    /// Return true if the block has no live objects and is released.
+    pub fn sweep<VM: VMBinding>(&self, space: &MarkSweepSpace<VM>) -> bool {
+        let cell_bytes = self.cell_bytes();
+        let mark_bit_on_side = VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.is_on_side();
+        let mut live_cells = 0;
+        let head = Self::link_cells(self.cells().rev(), |cell| {
+            match Self::object_in_cell(cell, cell_bytes) {
+                Some(object) if space.is_marked(object) => {
+                    if !mark_bit_on_side {
+                        metadata::store_metadata::<VM>(
+                            &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                            object,
+                            0,
+                            None,
+                            Some(Ordering::SeqCst),
+                        );
+                    }
+                    live_cells += 1;
+                    false
+                }
+                Some(object) => {
+                    unsafe { alloc_bit::unset_alloc_bit_unsafe(object) };
+                    memory::zero(cell, cell_bytes);
+                    true
+                }
+                None => true,
+            }
+        });
+        if let MetadataSpec::OnSide(side) = *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC {
+            side_metadata::bzero_metadata(&side, self.start(), Self::BYTES);
+        }
+
+        if live_cells == 0 {
+            space.release_block(*self);
+            true
+        } else {
+            self.set_free_list(head);
+            if !head.is_zero() {
+                space.add_available_block(*self);
+            }
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_class() {
+        assert_eq!(size_class(1), Some(0));
+        assert_eq!(size_class(16), Some(0));
+        assert_eq!(size_class(17), Some(1));
+        assert_eq!(size_class(1000), Some(22));
+        assert_eq!(SIZE_CLASSES[22], 1024);
+        assert_eq!(size_class(MAX_CELL_BYTES), Some(NUM_SIZE_CLASSES - 1));
+        assert_eq!(size_class(MAX_CELL_BYTES + 1), None);
+    }
+
+    #[test]
+    fn test_size_classes() {
+        for i in 1..NUM_SIZE_CLASSES {
+            assert!(SIZE_CLASSES[i - 1] < SIZE_CLASSES[i]);
+        }
+        for cell in SIZE_CLASSES.iter() {
+            assert_eq!(cell % MIN_OBJECT_SIZE, 0);
+            // Each block has at least a few cells.
+            assert!(Block::BYTES / cell >= 8);
+        }
+    }
+
+    #[test]
+    fn test_side_metadata_before_vm_base() {
+        // The local side metadata of the space must not overlap the metadata of the VM.
+        let end = SideMetadataSpec {
+            name: "End",
+            is_global: false,
+            offset: SideMetadataOffset::layout_after(
+                &crate::policy::marksweepspace::LAST_LOCAL_SIDE_METADATA,
+            ),
+            log_num_of_bits: 0,
+            log_min_obj_size: 0,
+        };
+        let vm_base = SideMetadataSpec {
+            offset: side_metadata::LOCAL_SIDE_METADATA_VM_BASE_OFFSET,
+            ..end
+        };
+        #[cfg(target_pointer_width = "64")]
+        assert!(end.get_absolute_offset() <= vm_base.get_absolute_offset());
+        #[cfg(target_pointer_width = "32")]
+        assert!(end.get_rel_offset() <= vm_base.get_rel_offset());
+    }
+}
@@ -0,0 +1,263 @@
+use super::block::*;
+use crate::plan::TransitiveClosure;
+use crate::policy::immix::chunk::{Chunk, ChunkMap, ChunkState};
+use crate::policy::space::SpaceOptions;
+use crate::policy::space::{CommonSpace, Space, SFT};
+use crate::scheduler::{GCWork, GCWorkScheduler, GCWorker, WorkBucketStage};
+use crate::util::heap::layout::heap_layout::{Mmapper, VMMap};
+use crate::util::heap::FreeListPageResource;
+use crate::util::heap::HeapMeta;
+use crate::util::heap::PageResource;
+use crate::util::heap::VMRequest;
+use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSpec};
+use crate::util::metadata::{self, compare_exchange_metadata, load_metadata, MetadataSpec};
+use crate::util::opaque_pointer::VMThread;
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use crate::MMTK;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+/// A non-moving mark-sweep space with segregated free lists. The space is divided into blocks,
+/// and each block is divided into cells of a single size class. Mutators allocate from the
+/// free lists of blocks with a `FreeListAllocator`, and blocks are swept after each GC.
+pub struct MarkSweepSpace<VM: VMBinding> {
+    common: CommonSpace<VM>,
+    pr: FreeListPageResource<VM>,
+    /// Allocation status for all chunks in the space
+    pub chunk_map: ChunkMap,
+    /// Blocks with free cells that are not owned by any allocator, for each size class.
+    available_blocks: Vec<Mutex<Vec<Block>>>,
+    /// Work packet scheduler
+    scheduler: Arc<GCWorkScheduler<VM>>,
+}
+
+unsafe impl<VM: VMBinding> Sync for MarkSweepSpace<VM> {}
+
+impl<VM: VMBinding> SFT for MarkSweepSpace<VM> {
+    fn name(&self) -> &str {
+        self.get_name()
+    }
+    fn is_live(&self, object: ObjectReference) -> bool {
+        self.is_marked(object)
+    }
+    fn is_movable(&self) -> bool {
+        false
+    }
+    #[cfg(feature = "sanity")]
+    fn is_sane(&self) -> bool {
+        true
+    }
+    fn initialize_object_metadata(&self, object: ObjectReference, _alloc: bool) {
+        crate::util::alloc_bit::set_alloc_bit(object);
+    }
+}
+
+impl<VM: VMBinding> Space<VM> for MarkSweepSpace<VM> {
+    fn as_space(&self) -> &dyn Space<VM> {
+        self
+    }
+    fn as_sft(&self) -> &(dyn SFT + Sync + 'static) {
+        self
+    }
+    fn get_page_resource(&self) -> &dyn PageResource<VM> {
+        &self.pr
+    }
+    fn common(&self) -> &CommonSpace<VM> {
+        &self.common
+    }
+    fn init(&mut self, _vm_map: &'static VMMap) {
+        self.common().init(self.as_space());
+    }
+    fn release_multiple_pages(&mut self, _start: Address) {
+        panic!("marksweepspace only releases pages enmasse")
+    }
+}
+
+impl<VM: VMBinding> MarkSweepSpace<VM> {
+    const MARKED_STATE: u8 = 1;
+
+    /// Get side metadata specs
+    fn side_metadata_specs() -> Vec<SideMetadataSpec> {
+        metadata::extract_side_metadata(&[
+            MetadataSpec::OnSide(Block::STATE_TABLE),
+            MetadataSpec::OnSide(Block::SIZE_CLASS_TABLE),
+            MetadataSpec::OnSide(Block::FREE_LIST_TABLE),
+            MetadataSpec::OnSide(ChunkMap::ALLOC_TABLE),
+            *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+        ])
+    }
+
+    pub fn new(
+        name: &'static str,
+        vm_map: &'static VMMap,
+        mmapper: &'static Mmapper,
+        heap: &mut HeapMeta,
+        scheduler: Arc<GCWorkScheduler<VM>>,
+        global_side_metadata_specs: Vec<SideMetadataSpec>,
+        poison_memory_on_release: bool,
+    ) -> Self {
+        let common = CommonSpace::new(
+            SpaceOptions {
+                name,
+                movable: false,
+                immortal: false,
+                zeroed: true,
+                vmrequest: VMRequest::discontiguous(),
+                side_metadata_specs: SideMetadataContext {
+                    global: global_side_metadata_specs,
+                    local: Self::side_metadata_specs(),
+                },
+                needs_log_bit: false,
+            },
+            vm_map,
+            mmapper,
+            heap,
+        );
+        let mut pr = if common.vmrequest.is_discontiguous() {
+            FreeListPageResource::new_discontiguous(0, vm_map)
+        } else {
+            FreeListPageResource::new_contiguous(common.start, common.extent, 0, vm_map)
+        };
+        pr.common_mut().poison_memory_on_release = poison_memory_on_release;
+        MarkSweepSpace {
+            pr,
+            common,
+            chunk_map: ChunkMap::new(),
+            available_blocks: (0..NUM_SIZE_CLASSES).map(|_| Mutex::new(vec![])).collect(),
+            scheduler,
+        }
+    }
+
+    pub fn prepare(&mut self) {
+        // Blocks are swept and added back to the lists in the release phase.
+        for blocks in &self.available_blocks {
+            blocks.lock().unwrap().clear();
+        }
+    }
+
+    pub fn release(&mut self) {
+        // # Safety: MarkSweepSpace reference is always valid within this collection cycle.
+        let space = unsafe { &*(self as *const Self) };
+        let work_packets = self
+            .chunk_map
+            .generate_tasks(|chunk| box SweepChunk { space, chunk });
+        self.scheduler.work_buckets[WorkBucketStage::Release].bulk_add(work_packets);
+    }
+
+    /// Pop a block with free cells of the given size class.
+    pub fn get_available_block(&self, size_class: usize) -> Option<Block> {
+        self.available_blocks[size_class].lock().unwrap().pop()
+    }
+
+    /// Add a swept block with free cells to the available list of its size class.
+    pub fn add_available_block(&self, block: Block) {
+        self.available_blocks[block.size_class()]
+            .lock()
+            .unwrap()
+            .push(block);
+    }
+
+    /// Allocate a clean block, and divide it into cells of the given size class.
+    pub fn get_clean_block(&self, tls: VMThread, size_class: usize) -> Option<Block> {
+        let block_address = self.acquire(tls, Block::PAGES);
+        if block_address.is_zero() {
+            return None;
+        }
+        let block = Block::from(block_address);
+        block.init(size_class);
+        self.chunk_map.set(block.chunk(), ChunkState::Allocated);
+        Some(block)
+    }
+
+    /// Release a block.
+    pub fn release_block(&self, block: Block) {
+        block.deinit();
+        self.pr.release_pages(block.start());
+    }
+
+    /// Trace and mark objects.
+    #[inline(always)]
+    pub fn trace_object<T: TransitiveClosure>(
+        &self,
+        trace: &mut T,
+        object: ObjectReference,
+    ) -> ObjectReference {
+        debug_assert!(
+            crate::util::alloc_bit::is_alloced(object),
+            "{:x}: alloc bit not set",
+            object
+        );
+        if self.attempt_mark(object) {
+            trace.process_node(object);
+        }
+        object
+    }
+
+    /// Atomically mark an object.
+    #[inline(always)]
+    fn attempt_mark(&self, object: ObjectReference) -> bool {
+        loop {
+            let old_value = load_metadata::<VM>(
+                &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                object,
+                None,
+                Some(Ordering::SeqCst),
+            ) as u8;
+            if old_value == Self::MARKED_STATE {
+                return false;
+            }
+
+            if compare_exchange_metadata::<VM>(
+                &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+                object,
+                old_value as usize,
+                Self::MARKED_STATE as usize,
+                None,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                break;
+            }
+        }
+        true
+    }
+
+    /// Check if an object is marked.
+    #[inline(always)]
+    pub fn is_marked(&self, object: ObjectReference) -> bool {
+        let old_value = load_metadata::<VM>(
+            &VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+            object,
+            None,
+            Some(Ordering::SeqCst),
+        ) as u8;
+        old_value == Self::MARKED_STATE
+    }
+}
+
+/// Chunk sweeping work packet.
+struct SweepChunk<VM: VMBinding> {
+    space: &'static MarkSweepSpace<VM>,
+    chunk: Chunk,
+}
+
+impl<VM: VMBinding> GCWork<VM> for SweepChunk<VM> {
+    #[inline]
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        if self.space.chunk_map.get(self.chunk) != ChunkState::Allocated {
+            return;
+        }
+        // number of allocated blocks.
+        let mut allocated_blocks = 0;
+        for block in Block::blocks_in_chunk(self.chunk).filter(|block| block.is_allocated()) {
+            if !block.sweep(self.space) {
+                allocated_blocks += 1;
+            }
+        }
+        // Set this chunk as free if there is not live blocks.
+        if allocated_blocks == 0 {
+            self.space.chunk_map.set(self.chunk, ChunkState::Free)
+        }
+    }
+}
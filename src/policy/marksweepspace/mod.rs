@@ -0,0 +1,11 @@
+///! A marksweep space that allocates from segregated free lists in size-classed blocks.
+pub mod block;
+mod global;
+
+pub use global::*;
+
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use block::Block;
+
+/// MarkSweepSpace's last local side metadata. It must end before `LOCAL_SIDE_METADATA_VM_BASE_OFFSET`.
+pub const LAST_LOCAL_SIDE_METADATA: SideMetadataSpec = Block::FREE_LIST_TABLE;
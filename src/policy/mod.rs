@@ -19,3 +19,5 @@ pub mod immortalspace;
 pub mod largeobjectspace;
 pub mod lockfreeimmortalspace;
 pub mod mallocspace;
+pub mod marksweepspace;
+pub mod vmregion;
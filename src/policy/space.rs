@@ -3,8 +3,8 @@ use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSani
 use crate::util::Address;
 use crate::util::ObjectReference;
 
-use crate::util::heap::layout::vm_layout_constants::{AVAILABLE_BYTES, LOG_BYTES_IN_CHUNK};
-use crate::util::heap::layout::vm_layout_constants::{AVAILABLE_END, AVAILABLE_START};
+use crate::util::heap::layout::vm_layout_constants::{vm_layout, LOG_BYTES_IN_CHUNK};
+use crate::util::heap::pageresource::PRAllocResult;
 use crate::util::heap::{PageResource, VMRequest};
 use crate::vm::{ActivePlan, Collection, ObjectModel};
 
@@ -70,6 +70,11 @@ pub trait SFT {
     /// object - the sanity checker will fail if an object is not sane.
     #[cfg(feature = "sanity")]
     fn is_sane(&self) -> bool;
+    /// Check the policy-specific metadata of a reachable object after the transitive closure (see `util::verifier`).
+    /// Return a description of the problem if the metadata does not agree with the object being live.
+    fn verify_object(&self, _object: ObjectReference) -> Result<(), String> {
+        Ok(())
+    }
     /// Initialize object metadata (in the header, or in the side metadata).
     fn initialize_object_metadata(&self, object: ObjectReference, alloc: bool);
 }
@@ -80,7 +85,7 @@ const DEBUG_SFT: bool = cfg!(debug_assertions) && false;
 #[derive(Debug)]
 struct EmptySpaceSFT {}
 
-const EMPTY_SFT_NAME: &str = "empty";
+pub(crate) const EMPTY_SFT_NAME: &str = "empty";
 
 impl SFT for EmptySpaceSFT {
     fn name(&self) -> &str {
@@ -236,6 +241,12 @@ impl<'a> SFTMap<'a> {
         self_mut.sft[chunk] = sft;
     }
 
+    /// Are all the chunks in the range unused by any space?
+    pub fn is_empty_range(&self, start: Address, chunks: usize) -> bool {
+        let first = start.chunk_index();
+        (first..(first + chunks)).all(|chunk| self.sft[chunk].name() == EMPTY_SFT_NAME)
+    }
+
     pub fn is_in_space(&self, object: ObjectReference) -> bool {
         let not_in_space = object.to_address().chunk_index() >= self.sft.len()
             || self.get(object.to_address()).name() == EMPTY_SPACE_SFT.name();
@@ -281,40 +292,7 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
             debug!("Collection not required");
 
             match pr.get_new_pages(self.common().descriptor, pages_reserved, pages, tls) {
-                Ok(res) => {
-                    // The following code was guarded by a page resource lock in Java MMTk.
-                    // I think they are thread safe and we do not need a lock. So they
-                    // are no longer guarded by a lock. If we see any issue here, considering
-                    // adding a space lock here.
-                    let bytes = conversions::pages_to_bytes(res.pages);
-                    self.grow_space(res.start, bytes, res.new_chunk);
-                    // Mmap the pages and the side metadata, and handle error. In case of any error,
-                    // we will either call back to the VM for OOM, or simply panic.
-                    if let Err(mmap_error) = self
-                        .common()
-                        .mmapper
-                        .ensure_mapped(res.start, res.pages)
-                        .and(
-                            self.common()
-                                .metadata
-                                .try_map_metadata_space(res.start, bytes),
-                        )
-                    {
-                        memory::handle_mmap_error::<VM>(mmap_error, tls);
-                    }
-
-                    // TODO: Concurrent zeroing
-
-                    if self.common().zeroed {
-                        match VM::VMActivePlan::global().base().options.nursery_zeroing {
-                            NurseryZeroingOptions::Lazy => memory::zero(res.start, bytes),
-                            _ => (),
-                        };
-                    }
-
-                    debug!("Space.acquire(), returned = {}", res.start);
-                    res.start
-                }
+                Ok(res) => self.map_new_pages(tls, res),
                 Err(_) => {
                     // We thought we had memory to allocate, but somehow failed the allocation. Will force a GC.
                     if !allow_poll {
@@ -331,6 +309,66 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
         }
     }
 
+    /// Map the pages that the page resource has given to the space, with their side metadata, and zero them
+    /// if needed. Return the start of the pages.
+    fn map_new_pages(&self, tls: VMThread, res: PRAllocResult) -> Address {
+        // The following code was guarded by a page resource lock in Java MMTk.
+        // I think they are thread safe and we do not need a lock. So they
+        // are no longer guarded by a lock. If we see any issue here, considering
+        // adding a space lock here.
+        let bytes = conversions::pages_to_bytes(res.pages);
+        self.grow_space(res.start, bytes, res.new_chunk);
+        // Mmap the pages and the side metadata, and handle error. In case of any error,
+        // we will either call back to the VM for OOM, or simply panic.
+        if let Err(mmap_error) = self.common().mmapper.ensure_mapped(res.start, res.pages) {
+            memory::handle_mmap_error_in_ranges::<VM>(mmap_error, tls, &[(res.start, bytes)]);
+        }
+        if let Err(mmap_error) = self
+            .common()
+            .metadata
+            .try_map_metadata_space(res.start, bytes)
+        {
+            let ranges = self
+                .common()
+                .metadata
+                .metadata_address_ranges(res.start, bytes);
+            memory::handle_mmap_error_in_ranges::<VM>(mmap_error, tls, &ranges);
+        }
+        // Prefer the NUMA node of the acquiring thread (a mutator, or a GC worker when copying).
+        crate::util::numa::bind_memory_to_current_node(res.start, bytes);
+
+        // TODO: Concurrent zeroing
+
+        if self.common().zeroed {
+            match VM::VMActivePlan::global().base().options.nursery_zeroing {
+                NurseryZeroingOptions::Lazy => memory::zero(res.start, bytes),
+                // The memory may be poisoned since it was zeroed.
+                _ if self.get_page_resource().common().poison_memory_on_release => {
+                    memory::zero(res.start, bytes)
+                }
+                _ => (),
+            };
+        }
+
+        debug!("Space.acquire(), returned = {}", res.start);
+        res.start
+    }
+
+    /// Acquire pages for the objects copied in a GC. Unlike `acquire()`, this never polls for a GC, and it returns
+    /// zero instead of panicking if the page resource cannot provide the pages, so the caller can keep the object
+    /// in place.
+    fn acquire_for_copy(&self, tls: VMThread, pages: usize) -> Address {
+        let pr = self.get_page_resource();
+        let pages_reserved = pr.reserve_pages(pages);
+        match pr.get_new_pages(self.common().descriptor, pages_reserved, pages, tls) {
+            Ok(res) => self.map_new_pages(tls, res),
+            Err(_) => {
+                pr.clear_request(pages_reserved);
+                unsafe { Address::zero() }
+            }
+        }
+    }
+
     fn address_in_space(&self, start: Address) -> bool {
         if !self.common().descriptor.is_contiguous() {
             self.common().vm_map().get_descriptor_for_address(start) == self.common().descriptor
@@ -406,6 +444,31 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
 
     fn release_multiple_pages(&mut self, start: Address);
 
+    /// Get the page usage and address ranges of this space. See [`SpaceStats`].
+    fn space_stats(&self) -> SpaceStats {
+        let common = self.common();
+        let pr = self.get_page_resource();
+        SpaceStats {
+            name: common.name,
+            reserved_pages: pr.reserved_pages(),
+            committed_pages: pr.committed_pages(),
+            contiguous: common.contiguous,
+            regions: self.regions(),
+        }
+    }
+
+    /// The address ranges of this space. See [`SpaceStats::regions`].
+    fn regions(&self) -> Vec<(Address, Address)> {
+        let common = self.common();
+        if common.contiguous {
+            vec![(common.start, common.start + common.extent)]
+        } else {
+            self.get_page_resource()
+                .common()
+                .get_discontiguous_regions()
+        }
+    }
+
     fn print_vm_map(&self) {
         let common = self.common();
         print!("{} ", common.name);
@@ -451,6 +514,33 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
         println!();
     }
 
+    /// Map the first `bytes` of the space and its side metadata (see the `pretouch_heap` option), and return
+    /// the address ranges (start and size) that are mapped. Only a contiguous space is mapped eagerly, as a
+    /// discontiguous space does not know its addresses until it acquires chunks.
+    fn eagerly_map(&self, bytes: usize) -> Vec<(Address, usize)> {
+        let common = self.common();
+        if !common.contiguous {
+            return vec![];
+        }
+        let start = common.start;
+        let size = raw_align_up(bytes, BYTES_IN_CHUNK).min(common.extent);
+        if let Err(e) = common
+            .mmapper
+            .eagerly_mmap_all_spaces(&[start, start + size])
+        {
+            panic!("Failed to eagerly map {}: {}", common.name, e);
+        }
+        if let Err(e) = common.metadata.try_map_metadata_space(start, size) {
+            panic!(
+                "Failed to eagerly map side metadata for {}: {}",
+                common.name, e
+            );
+        }
+        let mut ranges = vec![(start, size)];
+        ranges.extend(common.metadata.metadata_address_ranges(start, size));
+        ranges
+    }
+
     /// Ensure that the current space's metadata context does not have any issues.
     /// Panics with a suitable message if any issue is detected.
     /// It also initialises the sanity maps which will then be used if the `extreme_assertions` feature is active.
@@ -468,6 +558,22 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
 
 impl_downcast!(Space<VM> where VM: VMBinding);
 
+/// The page usage and address ranges of a space, returned by `memory_manager::space_stats()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpaceStats {
+    /// The name of the space.
+    pub name: &'static str,
+    /// Pages reserved by the page resource of the space (excluding side metadata).
+    pub reserved_pages: usize,
+    /// Pages committed by the page resource of the space (excluding side metadata).
+    pub committed_pages: usize,
+    /// Is the space contiguous? A discontiguous space gets chunks from a shared address range on demand.
+    pub contiguous: bool,
+    /// The address ranges (`[start, end)`) of the space. For a contiguous space, this is the whole range
+    /// of the space. For a discontiguous space, these are the regions currently owned by the space.
+    pub regions: Vec<(Address, Address)>,
+}
+
 pub struct CommonSpace<VM: VMBinding> {
     pub name: &'static str,
     pub descriptor: SpaceDescriptor,
@@ -608,6 +714,10 @@ impl<VM: VMBinding> CommonSpace<VM> {
             }
             SFT_MAP.update(space.as_sft(), self.start, bytes_to_chunks_up(self.extent));
         }
+        space
+            .get_page_resource()
+            .common()
+            .bind_side_metadata(self.metadata.clone());
     }
 
     pub fn vm_map(&self) -> &'static VMMap {
@@ -616,10 +726,11 @@ impl<VM: VMBinding> CommonSpace<VM> {
 }
 
 fn get_frac_available(frac: f32) -> usize {
-    trace!("AVAILABLE_START={}", AVAILABLE_START);
-    trace!("AVAILABLE_END={}", AVAILABLE_END);
-    let bytes = (frac * AVAILABLE_BYTES as f32) as usize;
-    trace!("bytes={}*{}={}", frac, AVAILABLE_BYTES, bytes);
+    let layout = vm_layout();
+    trace!("available_start={}", layout.available_start());
+    trace!("available_end={}", layout.available_end());
+    let bytes = (frac * layout.available_bytes() as f32) as usize;
+    trace!("bytes={}*{}={}", frac, layout.available_bytes(), bytes);
     let mb = bytes >> LOG_BYTES_IN_MBYTE;
     let rtn = mb << LOG_BYTES_IN_MBYTE;
     trace!("rtn={}", rtn);
@@ -0,0 +1,177 @@
+//! Pre-populated memory regions, such as a boot image or a heap snapshot, that a binding registers at runtime.
+//!
+//! MMTk does not allocate into or collect a VM region. Objects in a region are treated as immortal, and they
+//! are not traced through. A reference from a region into the MMTk heap is only allowed in an object that the
+//! binding has recorded with `record_write()`. The recorded objects are taken as roots at the start of the next GC,
+//! and an object is recorded again in the GC if it still refers into the heap, so it is scanned in the GC after that as well.
+
+use crate::mmtk::SFT_MAP;
+use crate::policy::space::SFT;
+use crate::util::heap::layout::vm_layout_constants::{vm_layout, BYTES_IN_CHUNK, MAX_CHUNKS};
+use crate::util::{Address, ObjectReference};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Mutex, RwLock};
+
+/// The reason why a VM region cannot be registered.
+#[derive(Debug)]
+pub enum VMRegionError {
+    /// The start or the size of the region is not aligned to chunks (`BYTES_IN_CHUNK`).
+    Unaligned(Address, usize),
+    /// The region is beyond the address range that MMTk manages, or overlaps the MMTk heap range.
+    InvalidRange(Address, usize),
+    /// The region overlaps another VM region or a space.
+    Overlap(Address, usize),
+    /// Failed to read or map the image file.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for VMRegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VMRegionError::Unaligned(start, size) => write!(
+                f,
+                "VM region at {} with size {} is not aligned to {} bytes",
+                start, size, BYTES_IN_CHUNK
+            ),
+            VMRegionError::InvalidRange(start, size) => write!(
+                f,
+                "VM region at {} with size {} is outside the address range for VM regions",
+                start, size
+            ),
+            VMRegionError::Overlap(start, size) => write!(
+                f,
+                "VM region at {} with size {} overlaps memory that is already in use",
+                start, size
+            ),
+            VMRegionError::Io(e) => write!(f, "Failed to load VM region: {}", e),
+        }
+    }
+}
+
+/// A registered VM region.
+pub struct VMRegion {
+    name: &'static str,
+    start: Address,
+    size: usize,
+}
+
+impl VMRegion {
+    pub fn contains(&self, addr: Address) -> bool {
+        self.start <= addr && addr < self.start + self.size
+    }
+}
+
+impl SFT for VMRegion {
+    fn name(&self) -> &str {
+        self.name
+    }
+    fn is_live(&self, _object: ObjectReference) -> bool {
+        true
+    }
+    fn is_movable(&self) -> bool {
+        false
+    }
+    #[cfg(feature = "sanity")]
+    fn is_sane(&self) -> bool {
+        true
+    }
+    fn initialize_object_metadata(&self, _object: ObjectReference, _alloc: bool) {}
+}
+
+/// The VM regions of a plan.
+#[derive(Default)]
+pub struct VMRegions {
+    regions: RwLock<Vec<Box<VMRegion>>>,
+    /// Objects in the regions that may contain references into the heap.
+    dirty_objects: Mutex<HashSet<ObjectReference>>,
+}
+
+impl VMRegions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a region that the binding has mapped and populated. The region needs to be chunk aligned,
+    /// outside the MMTk heap range, and not overlap any space or other region.
+    pub fn register(&self, name: &str, start: Address, size: usize) -> Result<(), VMRegionError> {
+        let mut regions = self.regions.write().unwrap();
+        self.check(start, size)?;
+        let chunks = size / BYTES_IN_CHUNK;
+        let region = Box::new(VMRegion {
+            // A region is never unregistered.
+            name: Box::leak(name.to_string().into_boxed_str()),
+            start,
+            size,
+        });
+        // The region lives in the box, which is kept until the MMTk instance is dropped.
+        let sft: &'static VMRegion = unsafe { &*(region.as_ref() as *const VMRegion) };
+        SFT_MAP.update(sft, start, chunks);
+        info!(
+            "Registered VM region {} at [{}, {})",
+            name,
+            start,
+            start + size
+        );
+        regions.push(region);
+        Ok(())
+    }
+
+    /// Check if a region can be registered at the given range.
+    pub fn check(&self, start: Address, size: usize) -> Result<(), VMRegionError> {
+        if !start.is_aligned_to(BYTES_IN_CHUNK) || size == 0 || size % BYTES_IN_CHUNK != 0 {
+            return Err(VMRegionError::Unaligned(start, size));
+        }
+        let chunks = size / BYTES_IN_CHUNK;
+        if start.chunk_index() + chunks > MAX_CHUNKS {
+            return Err(VMRegionError::InvalidRange(start, size));
+        }
+        let layout = vm_layout();
+        if start < layout.heap_end && layout.heap_start < start + size {
+            return Err(VMRegionError::InvalidRange(start, size));
+        }
+        if !SFT_MAP.is_empty_range(start, chunks) {
+            return Err(VMRegionError::Overlap(start, size));
+        }
+        Ok(())
+    }
+
+    /// Is the address in any VM region?
+    pub fn contains(&self, addr: Address) -> bool {
+        self.regions
+            .read()
+            .unwrap()
+            .iter()
+            .any(|region| region.contains(addr))
+    }
+
+    /// The address ranges of the registered regions.
+    pub fn ranges(&self) -> Vec<(Address, Address)> {
+        self.regions
+            .read()
+            .unwrap()
+            .iter()
+            .map(|region| (region.start, region.start + region.size))
+            .collect()
+    }
+
+    /// Record that a reference field in the object has been written. The object will be scanned as a root
+    /// in the next GC, and in the GCs after that as long as it refers into the heap.
+    pub fn record_write(&self, object: ObjectReference) {
+        debug_assert!(self.contains(object.to_address()));
+        self.dirty_objects.lock().unwrap().insert(object);
+    }
+
+    /// The objects that need to be scanned as roots.
+    pub fn dirty_objects(&self) -> Vec<ObjectReference> {
+        self.dirty_objects.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Take the objects that need to be scanned as roots in this GC. No object is recorded afterwards until the
+    /// GC scans the objects and records the ones that still refer into the heap (see `ScanVMRegionObjects`).
+    pub fn take_dirty_objects(&self) -> Vec<ObjectReference> {
+        std::mem::take(&mut *self.dirty_objects.lock().unwrap())
+            .into_iter()
+            .collect()
+    }
+}
@@ -15,6 +15,12 @@ pub struct ScheduleCollection;
 impl<VM: VMBinding> GCWork<VM> for ScheduleCollection {
     fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
         mmtk.plan.schedule_collection(worker.scheduler());
+        if mmtk.options.verify_heap {
+            crate::util::verifier::HeapVerifier::schedule(worker.scheduler());
+        }
+        if mmtk.options.verify_remset && mmtk.plan.is_current_gc_nursery() {
+            crate::util::verifier::HeapVerifier::schedule_remset_check(worker.scheduler());
+        }
     }
 }
 
@@ -222,6 +228,8 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for StopMutators<E> {
             }
             mmtk.scheduler.work_buckets[WorkBucketStage::Prepare]
                 .add(ScanVMSpecificRoots::<E>::new());
+            let vm_region_objects = mmtk.plan.base().vm_regions.take_dirty_objects();
+            schedule_vm_region_roots::<E>(mmtk, vm_region_objects, WorkBucketStage::Prepare);
         } else {
             mmtk.scheduler
                 .add_coordinator_work(StopMutators::<E>::new(), worker);
@@ -244,8 +252,17 @@ impl<VM: VMBinding> GCWork<VM> for EndOfGC {
             crate::util::edge_logger::reset();
         }
 
+        // Report the failures of the heap verification, if any, before the mutators resume.
+        mmtk.verifier.report();
+
         mmtk.plan.base().set_gc_status(GcStatus::NotInGC);
+        // Inform the GC trigger of the GC we just finished, so it can adjust the heap size.
+        mmtk.plan.base().gc_trigger().on_gc_end(
+            mmtk.plan.base().stats.last_gc_timing(),
+            mmtk.plan.get_pages_used(),
+        );
         <VM as VMBinding>::VMCollection::resume_mutators(worker.tls);
+        mmtk.plan.end_of_gc(&mmtk.scheduler);
     }
 }
 
@@ -359,6 +376,69 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanVMSpecificRoots<E> {
     }
 }
 
+/// Scan the objects in VM regions that may have references into the heap (see `VMRegions::record_write()`) as roots,
+/// in the given stage.
+pub(crate) fn schedule_vm_region_roots<E: ProcessEdgesWork>(
+    mmtk: &'static MMTK<E::VM>,
+    objects: Vec<ObjectReference>,
+    stage: WorkBucketStage,
+) {
+    for objects in objects.chunks(E::CAPACITY) {
+        mmtk.scheduler.work_buckets[stage].add(ScanVMRegionObjects::<E>::new(objects.to_vec()));
+    }
+}
+
+/// Scan objects in VM regions as roots. The recorded objects are taken from `VMRegions` at the start of a GC, so an
+/// object that still refers into the heap is recorded again, and it will be scanned in the next GC as well.
+pub struct ScanVMRegionObjects<E: ProcessEdgesWork> {
+    objects: Vec<ObjectReference>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: ProcessEdgesWork> ScanVMRegionObjects<E> {
+    pub fn new(objects: Vec<ObjectReference>) -> Self {
+        Self {
+            objects,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanVMRegionObjects<E> {
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        trace!("ScanVMRegionObjects");
+        let vm_regions = &mmtk.plan.base().vm_regions;
+        let mut edges = vec![];
+        for object in self.objects.iter() {
+            let mut closure = EdgeBuffer(vec![]);
+            <E::VM as VMBinding>::VMScanning::scan_object(&mut closure, *object, worker.tls);
+            let refers_to_heap = closure.0.iter().any(|slot| {
+                let target = unsafe { slot.load::<ObjectReference>() };
+                !target.is_null() && !vm_regions.contains(target.to_address())
+            });
+            if refers_to_heap {
+                vm_regions.record_write(*object);
+            }
+            edges.extend(closure.0);
+        }
+        for edges in edges.chunks(E::CAPACITY) {
+            worker.add_work(WorkBucketStage::Closure, E::new(edges.to_vec(), true, mmtk));
+        }
+    }
+}
+
+/// Collect the edges of the objects that are scanned.
+struct EdgeBuffer(Vec<Address>);
+
+impl TransitiveClosure for EdgeBuffer {
+    fn process_edge(&mut self, slot: Address) {
+        self.0.push(slot);
+    }
+    fn process_node(&mut self, _object: ObjectReference) {
+        unreachable!()
+    }
+}
+
 pub struct ProcessEdgesBase<E: ProcessEdgesWork> {
     pub edges: Vec<Address>,
     pub nodes: Vec<ObjectReference>,
@@ -3,11 +3,12 @@ use super::work_bucket::*;
 use super::worker::{GCWorker, WorkerGroup};
 use super::*;
 use crate::mmtk::MMTK;
+use crate::util::numa;
 use crate::util::opaque_pointer::*;
 use crate::vm::VMBinding;
 use enum_map::{enum_map, EnumMap};
 use std::collections::HashMap;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Condvar, Mutex, RwLock};
 
@@ -21,6 +22,10 @@ pub struct GCWorkScheduler<VM: VMBinding> {
     pub work_buckets: EnumMap<WorkBucketStage, WorkBucket<VM>>,
     /// Work for the coordinator thread
     pub coordinator_work: WorkBucket<VM>,
+    /// Work that runs concurrently with the mutators, between GCs (e.g. lazy sweeping). It is always open, and it is
+    /// polled after the other buckets. Workers do not report it to the coordinator, so it neither opens buckets nor
+    /// finishes a GC. A GC still waits for the workers that are running such work before it opens the next stage.
+    pub background_work: WorkBucket<VM>,
     /// workers
     worker_group: Option<Arc<WorkerGroup<VM>>>,
     /// Condition Variable for worker synchronization
@@ -44,6 +49,8 @@ pub struct GCWorkScheduler<VM: VMBinding> {
     /// the `Closure` bucket multiple times to iteratively discover and process
     /// more ephemeron objects.
     closure_end: Mutex<Option<Box<dyn Send + Fn() -> bool>>>,
+    /// Is the coordinator running a GC? Workers only notify the coordinator when they park during a GC.
+    in_gc: AtomicBool,
 }
 
 // The 'channel' inside Scheduler disallows Sync for Scheduler. We have to make sure we use channel properly:
@@ -63,10 +70,12 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
                 WorkBucketStage::Closure => WorkBucket::new(false, worker_monitor.clone()),
                 WorkBucketStage::RefClosure => WorkBucket::new(false, worker_monitor.clone()),
                 WorkBucketStage::RefForwarding => WorkBucket::new(false, worker_monitor.clone()),
+                WorkBucketStage::Verify => WorkBucket::new(false, worker_monitor.clone()),
                 WorkBucketStage::Release => WorkBucket::new(false, worker_monitor.clone()),
                 WorkBucketStage::Final => WorkBucket::new(false, worker_monitor.clone()),
             },
             coordinator_work: WorkBucket::new(true, worker_monitor.clone()),
+            background_work: WorkBucket::new(true, worker_monitor.clone()),
             worker_group: None,
             worker_monitor,
             mmtk: None,
@@ -75,6 +84,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             startup: Mutex::new(None),
             finalizer: Mutex::new(None),
             closure_end: Mutex::new(None),
+            in_gc: AtomicBool::new(false),
         })
     }
 
@@ -100,6 +110,16 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         let self_mut = unsafe { Arc::get_mut_unchecked(&mut self_mut) };
 
         self_mut.mmtk = Some(mmtk);
+        let numa_nodes = if numa::numa_enabled() {
+            numa::num_nodes()
+        } else {
+            1
+        };
+        if numa_nodes > 1 {
+            for bucket in self_mut.work_buckets.values_mut() {
+                bucket.set_numa_nodes(numa_nodes);
+            }
+        }
         self_mut.coordinator_worker = Some(RwLock::new(GCWorker::new(
             0,
             Arc::downgrade(self),
@@ -108,6 +128,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         )));
         self_mut.worker_group = Some(WorkerGroup::new(
             num_workers,
+            numa_nodes,
             Arc::downgrade(self),
             self.channel.0.clone(),
         ));
@@ -140,6 +161,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             open_next(Closure);
             open_next(RefClosure);
             open_next(RefForwarding);
+            open_next(Verify);
             open_next(Release);
             open_next(Final);
         }
@@ -199,6 +221,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
 
     /// Drain the message queue and execute coordinator work. Only the coordinator should call this.
     pub fn wait_for_completion(&self) {
+        self.in_gc.store(true, Ordering::SeqCst);
         // At the start of a GC, we probably already have received a `ScheduleCollection` work. Run it now.
         if let Some(initializer) = self.startup.lock().unwrap().take() {
             self.process_coordinator_work(initializer);
@@ -218,6 +241,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
                 break;
             }
         }
+        self.in_gc.store(false, Ordering::SeqCst);
         for message in self.channel.1.try_iter() {
             if let CoordinatorMessage::Work(work) = message {
                 self.process_coordinator_work(work);
@@ -236,6 +260,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         debug_assert!(!self.work_buckets[WorkBucketStage::Closure].is_activated());
         debug_assert!(!self.work_buckets[WorkBucketStage::RefClosure].is_activated());
         debug_assert!(!self.work_buckets[WorkBucketStage::RefForwarding].is_activated());
+        debug_assert!(!self.work_buckets[WorkBucketStage::Verify].is_activated());
         debug_assert!(!self.work_buckets[WorkBucketStage::Release].is_activated());
         debug_assert!(!self.work_buckets[WorkBucketStage::Final].is_activated());
     }
@@ -245,6 +270,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         self.work_buckets[WorkBucketStage::Closure].deactivate();
         self.work_buckets[WorkBucketStage::RefClosure].deactivate();
         self.work_buckets[WorkBucketStage::RefForwarding].deactivate();
+        self.work_buckets[WorkBucketStage::Verify].deactivate();
         self.work_buckets[WorkBucketStage::Release].deactivate();
         self.work_buckets[WorkBucketStage::Final].deactivate();
     }
@@ -254,6 +280,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         self.work_buckets[WorkBucketStage::Closure].deactivate();
         self.work_buckets[WorkBucketStage::RefClosure].deactivate();
         self.work_buckets[WorkBucketStage::RefForwarding].deactivate();
+        self.work_buckets[WorkBucketStage::Verify].deactivate();
         self.work_buckets[WorkBucketStage::Release].deactivate();
         self.work_buckets[WorkBucketStage::Final].deactivate();
     }
@@ -271,11 +298,12 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             return Some((work, worker.local_work_bucket.is_empty()));
         }
         for work_bucket in self.work_buckets.values() {
-            if let Some(work) = work_bucket.poll() {
+            if let Some(work) = work_bucket.poll_on_node(worker.numa_node) {
                 return Some((work, work_bucket.is_empty()));
             }
         }
-        None
+        // The background bucket is never reported as drained.
+        self.background_work.poll().map(|work| (work, false))
     }
 
     /// Get a scheduable work. Called by workers
@@ -312,7 +340,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             }
             // Park this worker
             worker.parked.store(true, Ordering::SeqCst);
-            if self.worker_group().all_parked() {
+            if self.worker_group().all_parked() && self.in_gc.load(Ordering::SeqCst) {
                 worker
                     .sender
                     .send(CoordinatorMessage::AllWorkerParked)
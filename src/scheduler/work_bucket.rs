@@ -58,6 +58,9 @@ pub struct WorkBucket<VM: VMBinding> {
     active: AtomicBool,
     /// A priority queue
     queue: RwLock<BinaryHeap<PrioritizedWork<VM>>>,
+    /// A priority queue for each NUMA node (see `set_numa_nodes()`). Workers add the packets they generate to
+    /// the queue of their node, and prefer the packets from that queue. This is empty if NUMA is disabled.
+    node_queues: Vec<RwLock<BinaryHeap<PrioritizedWork<VM>>>>,
     monitor: Arc<(Mutex<()>, Condvar)>,
     can_open: Option<Box<dyn (Fn() -> bool) + Send>>,
 }
@@ -68,6 +71,7 @@ impl<VM: VMBinding> WorkBucket<VM> {
         Self {
             active: AtomicBool::new(active),
             queue: Default::default(),
+            node_queues: vec![],
             monitor,
             can_open: None,
         }
@@ -87,26 +91,39 @@ impl<VM: VMBinding> WorkBucket<VM> {
     pub fn activate(&self) {
         self.active.store(true, Ordering::SeqCst);
     }
+    /// Create a queue for each NUMA node. This needs to be called before any work is added.
+    pub fn set_numa_nodes(&mut self, nodes: usize) {
+        debug_assert!(self.is_empty());
+        self.node_queues = (0..nodes).map(|_| Default::default()).collect();
+    }
     /// Test if the bucket is drained
     pub fn is_empty(&self) -> bool {
-        self.queue.read().len() == 0
+        self.queue.read().len() == 0 && self.node_queues.iter().all(|q| q.read().is_empty())
     }
     pub fn is_drained(&self) -> bool {
         self.is_activated() && self.is_empty()
     }
     /// Disable the bucket
     pub fn deactivate(&self) {
-        debug_assert!(
-            self.queue.read().is_empty(),
-            "Bucket not drained before close"
-        );
+        debug_assert!(self.is_empty(), "Bucket not drained before close");
         self.active.store(false, Ordering::SeqCst);
     }
     /// Add a work packet to this bucket, with a given priority
     pub fn add_with_priority(&self, priority: usize, work: Box<dyn GCWork<VM>>) {
-        self.queue
-            .write()
-            .push(PrioritizedWork::new(priority, work));
+        self.add_with_priority_on_node(priority, work, None);
+    }
+    /// Add a work packet to the queue of a NUMA node, with a given priority. The packet is added to
+    /// the shared queue if `node` is `None` or NUMA is disabled.
+    pub fn add_with_priority_on_node(
+        &self,
+        priority: usize,
+        work: Box<dyn GCWork<VM>>,
+        node: Option<usize>,
+    ) {
+        let queue = node
+            .and_then(|node| self.node_queues.get(node))
+            .unwrap_or(&self.queue);
+        queue.write().push(PrioritizedWork::new(priority, work));
         self.notify_one_worker(); // FIXME: Performance
     }
     /// Add a work packet to this bucket, with a default priority (1000)
@@ -127,10 +144,29 @@ impl<VM: VMBinding> WorkBucket<VM> {
     }
     /// Get a work packet (with the greatest priority) from this bucket
     pub fn poll(&self) -> Option<Box<dyn GCWork<VM>>> {
+        self.poll_on_node(None)
+    }
+    /// Get a work packet for a worker on the given NUMA node. We take the packet with the greater priority from
+    /// the node's queue and the shared queue. If both are empty, we steal a packet from other nodes.
+    pub fn poll_on_node(&self, node: Option<usize>) -> Option<Box<dyn GCWork<VM>>> {
         if !self.active.load(Ordering::SeqCst) {
             return None;
         }
-        self.queue.write().pop().map(|v| v.work)
+        if let Some(node_queue) = node.and_then(|node| self.node_queues.get(node)) {
+            let mut node_queue = node_queue.write();
+            let node_priority = node_queue.peek().map(|w| w.priority);
+            let shared_priority = self.queue.read().peek().map(|w| w.priority);
+            if node_priority.is_some() && node_priority >= shared_priority {
+                return node_queue.pop().map(|v| v.work);
+            }
+        }
+        if let Some(work) = self.queue.write().pop() {
+            return Some(work.work);
+        }
+        self.node_queues
+            .iter()
+            .find_map(|queue| queue.write().pop())
+            .map(|v| v.work)
     }
     pub fn set_open_condition(&mut self, pred: impl Fn() -> bool + Send + 'static) {
         self.can_open = Some(box pred);
@@ -155,6 +191,8 @@ pub enum WorkBucketStage {
     // we may need more than one buckets for each reference strength.
     RefClosure,
     RefForwarding,
+    /// Check the heap after the transitive closure, before any space is released (see the `verify_heap` option).
+    Verify,
     Release,
     Final,
 }
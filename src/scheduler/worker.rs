@@ -2,6 +2,7 @@ use super::stat::WorkerLocalStat;
 use super::work_bucket::*;
 use super::*;
 use crate::mmtk::MMTK;
+use crate::util::numa;
 use crate::util::opaque_pointer::*;
 use crate::vm::{Collection, VMBinding};
 use std::ffi::c_void;
@@ -56,6 +57,9 @@ const LOCALLY_CACHED_WORKS: usize = 1;
 pub struct GCWorker<VM: VMBinding> {
     pub tls: VMWorkerThread,
     pub ordinal: usize,
+    /// The NUMA node of the worker, if the `numa` option is enabled. The worker binds itself to the CPUs of the node
+    /// when it starts running, and prefers work packets generated on the node.
+    pub numa_node: Option<usize>,
     pub parked: AtomicBool,
     scheduler: Arc<GCWorkScheduler<VM>>,
     local: GCWorkerLocalPtr,
@@ -81,6 +85,7 @@ impl<VM: VMBinding> GCWorker<VM> {
         Self {
             tls: VMWorkerThread(VMThread::UNINITIALIZED),
             ordinal,
+            numa_node: None,
             parked: AtomicBool::new(true),
             local: GCWorkerLocalPtr::UNINITIALIZED,
             local_work_bucket: WorkBucket::new(true, scheduler.worker_monitor.clone()),
@@ -96,7 +101,11 @@ impl<VM: VMBinding> GCWorker<VM> {
     #[inline]
     pub fn add_work(&mut self, bucket: WorkBucketStage, work: impl GCWork<VM>) {
         if !self.scheduler().work_buckets[bucket].is_activated() {
-            self.scheduler.work_buckets[bucket].add_with_priority(1000, box work);
+            self.scheduler.work_buckets[bucket].add_with_priority_on_node(
+                1000,
+                box work,
+                self.numa_node,
+            );
             return;
         }
         self.local_work_buffer.push((bucket, box work));
@@ -110,7 +119,11 @@ impl<VM: VMBinding> GCWorker<VM> {
         let mut buffer = Vec::with_capacity(LOCALLY_CACHED_WORKS);
         std::mem::swap(&mut buffer, &mut self.local_work_buffer);
         for (bucket, work) in buffer {
-            self.scheduler.work_buckets[bucket].add_with_priority(1000, work);
+            self.scheduler.work_buckets[bucket].add_with_priority_on_node(
+                1000,
+                work,
+                self.numa_node,
+            );
         }
     }
 
@@ -147,6 +160,14 @@ impl<VM: VMBinding> GCWorker<VM> {
 
     pub fn run(&mut self, mmtk: &'static MMTK<VM>) {
         self.mmtk = Some(mmtk);
+        if let Some(node) = self.numa_node {
+            if let Err(e) = numa::bind_current_thread_to_node(node) {
+                warn!(
+                    "Failed to bind GC worker {} to NUMA node {}: {}",
+                    self.ordinal, node, e
+                );
+            }
+        }
         self.parked.store(false, Ordering::SeqCst);
         loop {
             while let Some((bucket, mut work)) = self.local_work_buffer.pop() {
@@ -165,14 +186,23 @@ pub struct WorkerGroup<VM: VMBinding> {
 }
 
 impl<VM: VMBinding> WorkerGroup<VM> {
+    /// Create a group of workers. If `numa_nodes` is more than one, the workers are assigned to the
+    /// NUMA nodes in a round-robin fashion.
     pub fn new(
         workers: usize,
+        numa_nodes: usize,
         scheduler: Weak<GCWorkScheduler<VM>>,
         sender: Sender<CoordinatorMessage<VM>>,
     ) -> Arc<Self> {
         Arc::new(Self {
             workers: (0..workers)
-                .map(|i| GCWorker::new(i, scheduler.clone(), false, sender.clone()))
+                .map(|i| {
+                    let mut worker = GCWorker::new(i, scheduler.clone(), false, sender.clone());
+                    if numa_nodes > 1 {
+                        worker.numa_node = Some(i % numa_nodes);
+                    }
+                    worker
+                })
                 .collect(),
         })
     }
@@ -1,5 +1,4 @@
 use crate::util::address::Address;
-use crate::util::constants::DEFAULT_STRESS_FACTOR;
 
 use std::sync::atomic::Ordering;
 
@@ -121,8 +120,18 @@ pub trait Allocator<VM: VMBinding>: Downcast {
     fn alloc_slow_inline(&mut self, size: usize, align: usize, offset: isize) -> Address {
         let tls = self.get_tls();
         let plan = self.get_plan().base();
-        let stress_test = plan.options.stress_factor != DEFAULT_STRESS_FACTOR
-            || plan.options.analysis_factor != DEFAULT_STRESS_FACTOR;
+        let stress_test = plan.is_stress_test();
+
+        // A stress GC is forced before the allocation, as if the allocation had failed. The allocation is then
+        // done after the GC.
+        if plan.stress_gc.is_enabled()
+            && VM::VMActivePlan::is_mutator(tls)
+            && self.get_plan().is_initialized()
+            && plan.stress_gc.on_allocation()
+        {
+            plan.control_collector_context.request();
+            VM::VMCollection::block_for_gc(VMMutatorThread(tls)); // We have checked that this is mutator
+        }
 
         // Information about the previous collection.
         let mut emergency_collection = false;
@@ -3,17 +3,21 @@ use std::mem::MaybeUninit;
 use crate::plan::Plan;
 use crate::policy::largeobjectspace::LargeObjectSpace;
 use crate::policy::mallocspace::MallocSpace;
+use crate::policy::marksweepspace::MarkSweepSpace;
 use crate::policy::space::Space;
 use crate::util::alloc::LargeObjectAllocator;
 use crate::util::alloc::MallocAllocator;
-use crate::util::alloc::{Allocator, BumpAllocator, ImmixAllocator};
+use crate::util::alloc::{Allocator, BumpAllocator, FreeListAllocator, ImmixAllocator};
 use crate::util::VMMutatorThread;
 use crate::vm::VMBinding;
 
-const MAX_BUMP_ALLOCATORS: usize = 5;
+// This was 5 before the extra immortal spaces were added. The extra bump allocator changes the offsets of all the
+// allocators after `bump_pointer` in `Allocators` (and `Mutator`): a binding that mirrors the struct needs to update it.
+const MAX_BUMP_ALLOCATORS: usize = 6;
 const MAX_LARGE_OBJECT_ALLOCATORS: usize = 1;
 const MAX_MALLOC_ALLOCATORS: usize = 1;
 const MAX_IMMIX_ALLOCATORS: usize = 1;
+const MAX_FREE_LIST_ALLOCATORS: usize = 1;
 
 // The allocators set owned by each mutator. We provide a fixed number of allocators for each allocator type in the mutator,
 // and each plan will select part of the allocators to use.
@@ -25,6 +29,9 @@ pub struct Allocators<VM: VMBinding> {
     pub large_object: [MaybeUninit<LargeObjectAllocator<VM>>; MAX_LARGE_OBJECT_ALLOCATORS],
     pub malloc: [MaybeUninit<MallocAllocator<VM>>; MAX_MALLOC_ALLOCATORS],
     pub immix: [MaybeUninit<ImmixAllocator<VM>>; MAX_IMMIX_ALLOCATORS],
+    // New allocator types are appended, so the offsets of the allocators above do not change. This still makes
+    // the struct (and `Mutator`) larger: a binding that mirrors the struct needs to add this field.
+    pub free_list: [MaybeUninit<FreeListAllocator<VM>>; MAX_FREE_LIST_ALLOCATORS],
 }
 
 impl<VM: VMBinding> Allocators<VM> {
@@ -40,6 +47,7 @@ impl<VM: VMBinding> Allocators<VM> {
             }
             AllocatorSelector::Malloc(index) => self.malloc[index as usize].assume_init_ref(),
             AllocatorSelector::Immix(index) => self.immix[index as usize].assume_init_ref(),
+            AllocatorSelector::FreeList(index) => self.free_list[index as usize].assume_init_ref(),
         }
     }
 
@@ -58,6 +66,7 @@ impl<VM: VMBinding> Allocators<VM> {
             }
             AllocatorSelector::Malloc(index) => self.malloc[index as usize].assume_init_mut(),
             AllocatorSelector::Immix(index) => self.immix[index as usize].assume_init_mut(),
+            AllocatorSelector::FreeList(index) => self.free_list[index as usize].assume_init_mut(),
         }
     }
 
@@ -71,6 +80,7 @@ impl<VM: VMBinding> Allocators<VM> {
             large_object: unsafe { MaybeUninit::uninit().assume_init() },
             malloc: unsafe { MaybeUninit::uninit().assume_init() },
             immix: unsafe { MaybeUninit::uninit().assume_init() },
+            free_list: unsafe { MaybeUninit::uninit().assume_init() },
         };
 
         for &(selector, space) in space_mapping.iter() {
@@ -104,6 +114,13 @@ impl<VM: VMBinding> Allocators<VM> {
                         false,
                     ));
                 }
+                AllocatorSelector::FreeList(index) => {
+                    ret.free_list[index as usize].write(FreeListAllocator::new(
+                        mutator_tls.0,
+                        space.downcast_ref::<MarkSweepSpace<VM>>().unwrap(),
+                        plan,
+                    ));
+                }
             }
         }
 
@@ -130,4 +147,5 @@ pub enum AllocatorSelector {
     LargeObject(u8),
     Malloc(u8),
     Immix(u8),
+    FreeList(u8),
 }
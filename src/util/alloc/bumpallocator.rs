@@ -1,4 +1,3 @@
-use crate::util::constants::DEFAULT_STRESS_FACTOR;
 use std::sync::atomic::Ordering;
 
 use super::allocator::{align_allocation_no_fill, fill_alignment_gap};
@@ -77,9 +76,7 @@ impl<VM: VMBinding> Allocator<VM> for BumpAllocator<VM> {
         // TODO: internalLimit etc.
         let base = &self.plan.base();
 
-        if base.options.stress_factor == DEFAULT_STRESS_FACTOR
-            && base.options.analysis_factor == DEFAULT_STRESS_FACTOR
-        {
+        if !base.is_stress_test() {
             self.acquire_block(size, align, offset, false)
         } else {
             self.alloc_slow_once_stress_test(size, align, offset)
@@ -126,12 +123,12 @@ impl<VM: VMBinding> BumpAllocator<VM> {
             let is_mutator = VM::VMActivePlan::is_mutator(self.tls) && self.plan.is_initialized();
 
             if is_mutator
-                && base.allocation_bytes.load(Ordering::SeqCst) > base.options.stress_factor
+                && base.allocation_bytes.load(Ordering::SeqCst) > base.options.stress_factor.get()
             {
                 trace!(
                     "Stress GC: allocation_bytes = {} more than stress_factor = {}",
                     base.allocation_bytes.load(Ordering::Relaxed),
-                    base.options.stress_factor
+                    base.options.stress_factor.get()
                 );
                 return self.acquire_block(size, align, offset, true);
             }
@@ -140,12 +137,12 @@ impl<VM: VMBinding> BumpAllocator<VM> {
             // an analysis counter specific allocation hook, then here is the place to do so
             #[cfg(feature = "analysis")]
             if is_mutator
-                && base.allocation_bytes.load(Ordering::SeqCst) > base.options.analysis_factor
+                && base.allocation_bytes.load(Ordering::SeqCst) > base.options.analysis_factor.get()
             {
                 trace!(
                     "Analysis: allocation_bytes = {} more than analysis_factor = {}",
                     base.allocation_bytes.load(Ordering::Relaxed),
-                    base.options.analysis_factor
+                    base.options.analysis_factor.get()
                 );
 
                 base.analysis_manager.alloc_hook(size, align, offset);
@@ -186,14 +183,17 @@ impl<VM: VMBinding> BumpAllocator<VM> {
             );
             if !stress_test {
                 self.set_limit(acquired_start, acquired_start + block_size);
+                self.alloc(size, align, offset)
             } else {
                 // For a stress test, we artificially make the fastpath fail by
                 // manipulating the limit as below.
                 // The assumption here is that we use an address range such that
                 // cursor > block_size always.
                 self.set_limit(acquired_start, unsafe { Address::from_usize(block_size) });
+                // Allocate from the new block here instead of going through alloc() and the slow path
+                // again, so that the allocation is only counted once.
+                self.alloc_slow_once_stress_test(size, align, offset)
             }
-            self.alloc(size, align, offset)
         }
     }
 }
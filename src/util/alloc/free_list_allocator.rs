@@ -0,0 +1,158 @@
+use super::allocator::{align_allocation, get_maximum_aligned_size};
+use crate::plan::Plan;
+use crate::policy::marksweepspace::block::{size_class, Block, NUM_SIZE_CLASSES};
+use crate::policy::marksweepspace::MarkSweepSpace;
+use crate::policy::space::Space;
+use crate::util::alloc::Allocator;
+use crate::util::conversions::raw_align_up;
+use crate::util::opaque_pointer::VMThread;
+use crate::util::Address;
+use crate::vm::*;
+
+/// Segregated free-list allocator for `MarkSweepSpace`. Each mutator keeps a free list
+/// for each size class, and refills an empty list from the blocks of the space.
+#[repr(C)]
+pub struct FreeListAllocator<VM: VMBinding> {
+    pub tls: VMThread,
+    space: &'static MarkSweepSpace<VM>,
+    plan: &'static dyn Plan<VM = VM>,
+    /// The first free cell of each size class
+    free_lists: [Address; NUM_SIZE_CLASSES],
+    /// The free lists in a stress test. `free_lists` is then kept empty, so that every allocation (including
+    /// the ones inlined by the binding) takes the slow path.
+    // This field is appended, so the offsets of the fields above do not change. It still makes the struct larger,
+    // and a binding that mirrors the struct needs to add this field.
+    stress_free_lists: [Address; NUM_SIZE_CLASSES],
+}
+
+impl<VM: VMBinding> FreeListAllocator<VM> {
+    pub fn new(
+        tls: VMThread,
+        space: &'static MarkSweepSpace<VM>,
+        plan: &'static dyn Plan<VM = VM>,
+    ) -> Self {
+        FreeListAllocator {
+            tls,
+            space,
+            plan,
+            free_lists: [Address::ZERO; NUM_SIZE_CLASSES],
+            stress_free_lists: [Address::ZERO; NUM_SIZE_CLASSES],
+        }
+    }
+
+    /// Drop all the free lists. The cells are linked again when the blocks are swept.
+    pub fn reset(&mut self) {
+        self.free_lists = [Address::ZERO; NUM_SIZE_CLASSES];
+        self.stress_free_lists = [Address::ZERO; NUM_SIZE_CLASSES];
+    }
+
+    /// Get the size class for an allocation request.
+    #[inline(always)]
+    fn size_class_for(size: usize, align: usize) -> usize {
+        let bytes = get_maximum_aligned_size::<VM>(
+            raw_align_up(size, VM::MIN_ALIGNMENT),
+            align,
+            VM::MIN_ALIGNMENT,
+        );
+        size_class(bytes).unwrap_or_else(|| {
+            panic!(
+                "{} bytes cannot be allocated by the free-list allocator",
+                bytes
+            )
+        })
+    }
+}
+
+impl<VM: VMBinding> Allocator<VM> for FreeListAllocator<VM> {
+    fn get_space(&self) -> &'static dyn Space<VM> {
+        self.space as _
+    }
+
+    fn get_plan(&self) -> &'static dyn Plan<VM = VM> {
+        self.plan
+    }
+
+    fn get_tls(&self) -> VMThread {
+        self.tls
+    }
+
+    #[inline(always)]
+    fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        let class = Self::size_class_for(size, align);
+        let cell = self.free_lists[class];
+        if cell.is_zero() {
+            trace!(
+                "Free list of size class {} is empty, go to alloc slow path",
+                class
+            );
+            return self.alloc_slow(size, align, offset);
+        }
+        self.free_lists[class] = unsafe { take_cell(cell) };
+        align_allocation::<VM>(cell, align, offset, VM::MIN_ALIGNMENT, true)
+    }
+
+    /// Refill the free list with the free cells of an available block, or a clean block.
+    fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        let class = Self::size_class_for(size, align);
+        if self.plan.base().is_stress_test() {
+            return self.alloc_slow_once_stress_test(size, align, offset, class);
+        }
+        match self.acquire_block(class) {
+            None => Address::ZERO,
+            Some(block) => {
+                self.free_lists[class] = block.take_free_list();
+                self.alloc(size, align, offset)
+            }
+        }
+    }
+}
+
+impl<VM: VMBinding> FreeListAllocator<VM> {
+    fn acquire_block(&self, class: usize) -> Option<Block> {
+        let block = self
+            .space
+            .get_available_block(class)
+            .or_else(|| self.space.get_clean_block(self.tls, class))?;
+        trace!("Acquired block {:?} for size class {}", block, class);
+        debug_assert_eq!(block.size_class(), class);
+        Some(block)
+    }
+
+    /// Allocation slow path in a stress test. Take a cell from the hidden free list, and refill the list if it is
+    /// empty. This does not go through alloc(), so each allocation reaches the slow path once.
+    fn alloc_slow_once_stress_test(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        class: usize,
+    ) -> Address {
+        // Move any cells that were taken before the stress test started to the hidden list.
+        if !self.free_lists[class].is_zero() {
+            debug_assert!(self.stress_free_lists[class].is_zero());
+            self.stress_free_lists[class] =
+                std::mem::replace(&mut self.free_lists[class], Address::ZERO);
+        }
+        if self.stress_free_lists[class].is_zero() {
+            match self.acquire_block(class) {
+                None => return Address::ZERO,
+                Some(block) => self.stress_free_lists[class] = block.take_free_list(),
+            }
+        }
+        let cell = self.stress_free_lists[class];
+        self.stress_free_lists[class] = unsafe { take_cell(cell) };
+        align_allocation::<VM>(cell, align, offset, VM::MIN_ALIGNMENT, true)
+    }
+}
+
+/// Unlink the first cell of a free list, and return the rest of the list.
+///
+/// # Safety
+/// The cell needs to be the first cell of a free list.
+#[inline(always)]
+unsafe fn take_cell(cell: Address) -> Address {
+    let next = cell.load::<Address>();
+    // Clear the link so the cell is zeroed for the new object.
+    cell.store(Address::ZERO);
+    next
+}
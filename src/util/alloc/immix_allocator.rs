@@ -30,6 +30,12 @@ pub struct ImmixAllocator<VM: VMBinding> {
     request_for_large: bool,
     /// Hole-searching cursor
     line: Option<Line>,
+    /// The real limit of the bump pointer in a stress test. `limit` is then kept at the cursor,
+    /// so that every allocation takes the slow path.
+    // This field is appended, so the offsets of the fields above do not change. It still makes the struct one word
+    // larger, which moves the allocators after the Immix allocators in `Allocators` (and `Mutator`): a binding that
+    // mirrors the struct needs to add this field.
+    stress_limit: Address,
 }
 
 impl<VM: VMBinding> ImmixAllocator<VM> {
@@ -40,6 +46,7 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
         self.large_limit = Address::ZERO;
         self.request_for_large = false;
         self.line = None;
+        self.stress_limit = Address::ZERO;
     }
 }
 
@@ -84,6 +91,9 @@ impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
 
     /// Acquire a clean block from ImmixSpace for allocation.
     fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        if self.in_stress_test() {
+            return self.alloc_slow_once_stress_test(size, align, offset);
+        }
         match self.immix_space().get_clean_block(self.tls, self.copy) {
             None => Address::ZERO,
             Some(block) => {
@@ -124,6 +134,7 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
             large_limit: Address::ZERO,
             request_for_large: false,
             line: None,
+            stress_limit: Address::ZERO,
         }
     }
 
@@ -136,7 +147,7 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
     fn overflow_alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
         let start = align_allocation_no_fill::<VM>(self.large_cursor, align, offset);
         let end = start + size;
-        if end > self.large_limit {
+        if end > self.large_limit || self.in_stress_test() {
             self.request_for_large = true;
             let rtn = self.alloc_slow_inline(size, align, offset);
             self.request_for_large = false;
@@ -151,6 +162,9 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
     /// Bump allocate small objects into recyclable lines (i.e. holes).
     #[cold]
     fn alloc_slow_hot(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        if self.in_stress_test() {
+            return self.alloc_slow_inline(size, align, offset);
+        }
         if self.acquire_recyclable_lines(size, align, offset) {
             self.alloc(size, align, offset)
         } else {
@@ -158,6 +172,71 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
         }
     }
 
+    /// Do we hide the limit so that every allocation goes to the slow path? Copy allocators are not stressed.
+    #[inline(always)]
+    fn in_stress_test(&self) -> bool {
+        !self.copy && self.plan.base().is_stress_test()
+    }
+
+    /// Allocation slow path in a stress test. Bump allocate against the real limit, and get new lines or
+    /// a new block if the object does not fit. This does not go through alloc(), so each allocation reaches
+    /// the slow path once.
+    fn alloc_slow_once_stress_test(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        let result = match self.stress_bump(size, align, offset) {
+            Some(result) => result,
+            None => {
+                if !self.stress_refill(size, align, offset) {
+                    return Address::ZERO;
+                }
+                self.stress_bump(size, align, offset).unwrap()
+            }
+        };
+        self.limit = self.cursor;
+        result
+    }
+
+    fn stress_bump(&mut self, size: usize, align: usize, offset: isize) -> Option<Address> {
+        let (cursor, limit) = if self.request_for_large {
+            (self.large_cursor, self.large_limit)
+        } else {
+            (self.cursor, self.stress_limit)
+        };
+        let result = align_allocation_no_fill::<VM>(cursor, align, offset);
+        let new_cursor = result + size;
+        if new_cursor > limit {
+            return None;
+        }
+        fill_alignment_gap::<VM>(cursor, result);
+        if self.request_for_large {
+            self.large_cursor = new_cursor;
+        } else {
+            self.cursor = new_cursor;
+        }
+        Some(result)
+    }
+
+    /// Get new lines (for small objects) or a new block for the stress test allocation. Return false if a GC
+    /// happened instead.
+    fn stress_refill(&mut self, size: usize, align: usize, offset: isize) -> bool {
+        if !self.request_for_large && self.acquire_recyclable_lines(size, align, offset) {
+            self.stress_limit = self.limit;
+            return true;
+        }
+        match self.immix_space().get_clean_block(self.tls, self.copy) {
+            None => false,
+            Some(block) => {
+                if self.request_for_large {
+                    self.large_cursor = block.start();
+                    self.large_limit = block.end();
+                } else {
+                    self.cursor = block.start();
+                    self.stress_limit = block.end();
+                }
+                true
+            }
+        }
+    }
+
     /// Search for recyclable lines.
     fn acquire_recyclable_lines(&mut self, size: usize, align: usize, offset: isize) -> bool {
         while self.line.is_some() || self.acquire_recyclable_block() {
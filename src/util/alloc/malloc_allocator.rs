@@ -33,22 +33,18 @@ impl<VM: VMBinding> Allocator<VM> for MallocAllocator<VM> {
     }
 
     fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
-        // TODO: We currently ignore the offset field. This is wrong.
-        // assert!(offset == 0);
-        assert!(align <= 16);
-
         #[cfg(feature = "analysis")]
         {
             let base = &self.plan.base();
             let is_mutator = VM::VMActivePlan::is_mutator(self.tls) && self.plan.is_initialized();
 
             if is_mutator
-                && base.allocation_bytes.load(Ordering::SeqCst) > base.options.analysis_factor
+                && base.allocation_bytes.load(Ordering::SeqCst) > base.options.analysis_factor.get()
             {
                 trace!(
                     "Analysis: allocation_bytes = {} more than analysis_factor = {}",
                     base.allocation_bytes.load(Ordering::Relaxed),
-                    base.options.analysis_factor
+                    base.options.analysis_factor.get()
                 );
 
                 base.allocation_bytes.store(0, Ordering::SeqCst);
@@ -56,7 +52,7 @@ impl<VM: VMBinding> Allocator<VM> for MallocAllocator<VM> {
             }
         }
 
-        let ret = self.space.alloc(self.tls, size);
+        let ret = self.space.alloc(self.tls, size, align, offset);
 
         trace!(
             "MallocSpace.alloc size = {}, align = {}, offset = {}, res = {}",
@@ -198,7 +198,7 @@ impl<VM: VMBinding> FreeListPageResource<VM> {
         let common_flpr = unsafe {
             let mut common_flpr = Box::new(CommonFreeListPageResource {
                 free_list: MaybeUninit::uninit().assume_init(),
-                start: AVAILABLE_START,
+                start: vm_layout().available_start(),
             });
             ::std::ptr::write(
                 &mut common_flpr.free_list,
@@ -316,6 +316,10 @@ impl<VM: VMBinding> FreeListPageResource<VM> {
                     PAGES_IN_CHUNK - self.meta_data_pages_per_region;
             }
         }
+        if self.protect_memory_on_release {
+            // Another space may take the chunks from the global pool, and it expects them to be accessible.
+            self.munprotect(chunk, num_chunks * PAGES_IN_CHUNK);
+        }
         /* now return the address space associated with the chunk for global reuse */
         self.common.release_discontiguous_chunks(chunk);
     }
@@ -346,13 +350,20 @@ impl<VM: VMBinding> FreeListPageResource<VM> {
         debug_assert!(conversions::is_page_aligned(first));
         let page_offset = conversions::bytes_to_pages(first - self.start);
         let pages = self.free_list.size(page_offset as _);
-        // if (VM.config.ZERO_PAGES_ON_RELEASE)
-        //     VM.memory.zero(false, first, Conversions.pagesToBytes(pages));
         debug_assert!(pages as usize <= self.common.accounting.get_committed_pages());
 
+        if self.common.poison_memory_on_release {
+            memory::poison(first, conversions::pages_to_bytes(pages as _));
+        }
         if self.protect_memory_on_release {
             self.mprotect(first, pages as _);
         }
+        // The chunks of a discontiguous space are not reused until they are returned to the global pool, which releases
+        // their metadata. The pages of a contiguous space may be allocated again as soon as they are freed.
+        if self.common.contiguous {
+            self.common
+                .release_side_metadata(first, conversions::pages_to_bytes(pages as _));
+        }
 
         // FIXME
         #[allow(clippy::cast_ref_to_mut)]
@@ -370,6 +381,72 @@ impl<VM: VMBinding> FreeListPageResource<VM> {
         }
     }
 
+    /// Release multiple allocations at once. The allocations are sorted, and adjacent allocations are released
+    /// as a run: the memory of a run is protected with a single call (if `protect_memory_on_release` is set),
+    /// the lock is only taken once for a run, and we only look for free chunks after the whole run is freed.
+    pub fn release_pages_bulk(&self, mut firsts: Vec<Address>) {
+        firsts.sort_unstable();
+        // FIXME
+        #[allow(clippy::cast_ref_to_mut)]
+        let me = unsafe { &mut *(self as *const _ as *mut Self) };
+        let mut i = 0;
+        while i < firsts.len() {
+            // Find the run of adjacent allocations that starts at firsts[i]. A run ends at a unit that cannot be
+            // coalesced (e.g. the boundary of a contiguous region), so the run is coalesced into one free block.
+            let run_start = firsts[i];
+            let mut run_end = run_start;
+            let mut last = run_start;
+            let mut allocations = vec![];
+            while i < firsts.len() && firsts[i] == run_end {
+                let first = firsts[i];
+                debug_assert!(conversions::is_page_aligned(first));
+                let page_offset = conversions::bytes_to_pages(first - self.start);
+                if !allocations.is_empty() && !self.free_list.is_coalescable(page_offset as _) {
+                    break;
+                }
+                let pages = self.free_list.size(page_offset as _) as usize;
+                allocations.push((page_offset, pages));
+                last = first;
+                run_end = first + conversions::pages_to_bytes(pages);
+                i += 1;
+            }
+
+            if self.common.poison_memory_on_release {
+                memory::poison(run_start, run_end - run_start);
+            }
+            if self.protect_memory_on_release {
+                self.mprotect(run_start, conversions::bytes_to_pages(run_end - run_start));
+            }
+            if self.common.contiguous {
+                self.common
+                    .release_side_metadata(run_start, run_end - run_start);
+            }
+
+            let freed = {
+                let mut sync = self.sync.lock().unwrap();
+                let mut freed = 0;
+                for (page_offset, pages) in allocations {
+                    debug_assert!(pages <= self.common.accounting.get_committed_pages());
+                    self.common.accounting.release(pages);
+                    freed = me.free_list.free(page_offset as _, true);
+                    sync.pages_currently_on_freelist += pages;
+                }
+                freed
+            };
+            if !self.common.contiguous {
+                // only discontiguous spaces use chunks
+                me.release_free_chunks(last, freed as _);
+            }
+        }
+    }
+
+    /// Get the number of pages of the allocation that starts at `first`.
+    pub fn get_allocated_pages(&self, first: Address) -> usize {
+        debug_assert!(conversions::is_page_aligned(first));
+        let page_offset = conversions::bytes_to_pages(first - self.start);
+        self.free_list.size(page_offset as _) as usize
+    }
+
     fn release_free_chunks(&mut self, freed_page: Address, pages_freed: usize) {
         let page_offset = conversions::bytes_to_pages(freed_page - self.start);
 
@@ -0,0 +1,273 @@
+//! GC trigger policies. A GC trigger policy decides the current heap size, i.e. how many pages
+//! a plan can reserve before [`Plan::poll()`](crate::plan::Plan::poll) triggers a GC.
+//! The heap size chosen by a policy never exceeds the maximum heap size given in `gc_init()`.
+
+use crate::util::constants::LOG_BYTES_IN_PAGE;
+use crate::util::heap::layout::vm_layout_constants::LOG_BYTES_IN_CHUNK;
+use crate::util::options::{GCTriggerSelector, Options};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// The timing of a GC, measured by `Stats`. This is recorded regardless of
+/// whether we are gathering statistics (i.e. inside a harness).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GCTiming {
+    /// The wall-clock time of the GC pause.
+    pub pause: Duration,
+    /// The wall-clock time mutators ran between the end of the previous GC and the start of this GC.
+    pub mutator: Duration,
+}
+
+/// A GC trigger policy. MMTk creates one policy per plan based on the `gc_trigger` option. A binding
+/// can also provide its own policy with `memory_manager::set_gc_trigger()` before `gc_init()`.
+pub trait GCTriggerPolicy: Sync + Send {
+    /// Initialize the policy with the maximum heap size (in pages). This is called once in `gc_init()`.
+    fn init(&mut self, max_heap_pages: usize);
+    /// Inform the policy that a GC has finished.
+    ///
+    /// Arguments:
+    /// * `timing`: The pause time of the GC and the mutator time before the GC.
+    /// * `live_pages`: The pages used by the plan after the GC.
+    fn on_gc_end(&self, _timing: GCTiming, _live_pages: usize) {}
+    /// Is the heap full with the given reserved pages? If so, the plan will trigger a GC.
+    fn is_heap_full(&self, pages_reserved: usize) -> bool {
+        pages_reserved > self.get_current_heap_size_in_pages()
+    }
+    /// The current heap size in pages.
+    fn get_current_heap_size_in_pages(&self) -> usize;
+    /// The maximum heap size in pages.
+    fn get_max_heap_size_in_pages(&self) -> usize;
+}
+
+/// Create the GC trigger policy selected by the options.
+pub fn create_gc_trigger(options: &Options) -> Box<dyn GCTriggerPolicy> {
+    match options.gc_trigger {
+        GCTriggerSelector::FixedHeapSize => Box::new(FixedHeapSizeTrigger::default()),
+        GCTriggerSelector::PauseTime => Box::new(PauseTimeTrigger::new(Duration::from_millis(
+            options.gc_pause_time_goal as u64,
+        ))),
+        GCTriggerSelector::Throughput => Box::new(GCTimeRatioTrigger::new(options.gc_time_ratio)),
+    }
+}
+
+/// The heap size is always the maximum heap size. A GC is only triggered when the heap is full.
+#[derive(Default)]
+pub struct FixedHeapSizeTrigger {
+    total_pages: usize,
+}
+
+impl GCTriggerPolicy for FixedHeapSizeTrigger {
+    fn init(&mut self, max_heap_pages: usize) {
+        self.total_pages = max_heap_pages;
+    }
+    fn get_current_heap_size_in_pages(&self) -> usize {
+        self.total_pages
+    }
+    fn get_max_heap_size_in_pages(&self) -> usize {
+        self.total_pages
+    }
+}
+
+/// We do not shrink the headroom (the pages allowed for allocation between two GCs) below this.
+const MIN_HEADROOM_PAGES: usize = 1 << (LOG_BYTES_IN_CHUNK - LOG_BYTES_IN_PAGE as usize);
+/// The maximum factor that we grow or shrink the headroom by after each GC.
+const MAX_HEADROOM_ADJUSTMENT: f64 = 2.0;
+
+/// A heap size that is the live pages after the last GC plus a headroom, capped by the maximum heap size.
+/// Dynamic policies adjust the headroom after each GC.
+struct DynamicHeapSize {
+    max_heap_pages: usize,
+    live_pages: AtomicUsize,
+    headroom_pages: AtomicUsize,
+}
+
+impl DynamicHeapSize {
+    const fn new() -> Self {
+        DynamicHeapSize {
+            max_heap_pages: 0,
+            live_pages: AtomicUsize::new(0),
+            headroom_pages: AtomicUsize::new(0),
+        }
+    }
+
+    fn init(&mut self, max_heap_pages: usize) {
+        // Start with the maximum heap size. We adjust it once we get feedback from the first GC.
+        self.max_heap_pages = max_heap_pages;
+        self.headroom_pages.store(max_heap_pages, Ordering::Relaxed);
+    }
+
+    /// Scale the headroom by the given factor (clamped to `[1/MAX_HEADROOM_ADJUSTMENT, MAX_HEADROOM_ADJUSTMENT]`).
+    fn adjust(&self, live_pages: usize, factor: f64) {
+        let factor = factor.clamp(1f64 / MAX_HEADROOM_ADJUSTMENT, MAX_HEADROOM_ADJUSTMENT);
+        let old = self.headroom_pages.load(Ordering::Relaxed);
+        let max_headroom = self.max_heap_pages.saturating_sub(live_pages);
+        let new = ((old as f64 * factor) as usize)
+            .max(MIN_HEADROOM_PAGES)
+            .min(max_headroom);
+        trace!(
+            "GC trigger: live pages = {}, headroom = {} -> {} pages (factor = {})",
+            live_pages,
+            old,
+            new,
+            factor
+        );
+        self.live_pages.store(live_pages, Ordering::Relaxed);
+        self.headroom_pages.store(new, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> usize {
+        let pages =
+            self.live_pages.load(Ordering::Relaxed) + self.headroom_pages.load(Ordering::Relaxed);
+        pages.min(self.max_heap_pages)
+    }
+}
+
+/// Adjust the heap size to meet a pause time goal. If a GC takes longer than the goal, we shrink the
+/// headroom so the next GC has less work to do. If a GC is shorter than the goal, we grow the headroom
+/// so GCs happen less frequently.
+pub struct PauseTimeTrigger {
+    goal: Duration,
+    heap: DynamicHeapSize,
+}
+
+impl PauseTimeTrigger {
+    pub fn new(goal: Duration) -> Self {
+        PauseTimeTrigger {
+            goal,
+            heap: DynamicHeapSize::new(),
+        }
+    }
+}
+
+impl GCTriggerPolicy for PauseTimeTrigger {
+    fn init(&mut self, max_heap_pages: usize) {
+        self.heap.init(max_heap_pages);
+    }
+    fn on_gc_end(&self, timing: GCTiming, live_pages: usize) {
+        let pause = timing.pause.as_secs_f64();
+        let factor = if pause > 0f64 {
+            self.goal.as_secs_f64() / pause
+        } else {
+            MAX_HEADROOM_ADJUSTMENT
+        };
+        self.heap.adjust(live_pages, factor);
+    }
+    fn get_current_heap_size_in_pages(&self) -> usize {
+        self.heap.get()
+    }
+    fn get_max_heap_size_in_pages(&self) -> usize {
+        self.heap.max_heap_pages
+    }
+}
+
+/// Adjust the heap size to meet a throughput goal: MMTk aims to spend no more than `1/(1+ratio)`
+/// of the total time in GC. If we spend more time in GC than the goal, we grow the headroom so GCs
+/// happen less frequently. Otherwise, we shrink the headroom to reduce the memory footprint.
+pub struct GCTimeRatioTrigger {
+    ratio: usize,
+    heap: DynamicHeapSize,
+}
+
+impl GCTimeRatioTrigger {
+    pub fn new(ratio: usize) -> Self {
+        GCTimeRatioTrigger {
+            ratio,
+            heap: DynamicHeapSize::new(),
+        }
+    }
+
+    /// The target fraction of time spent in GC.
+    fn target_gc_fraction(&self) -> f64 {
+        1f64 / (1 + self.ratio) as f64
+    }
+}
+
+impl GCTriggerPolicy for GCTimeRatioTrigger {
+    fn init(&mut self, max_heap_pages: usize) {
+        self.heap.init(max_heap_pages);
+    }
+    fn on_gc_end(&self, timing: GCTiming, live_pages: usize) {
+        let pause = timing.pause.as_secs_f64();
+        let total = pause + timing.mutator.as_secs_f64();
+        let factor = if total > 0f64 {
+            (pause / total) / self.target_gc_fraction()
+        } else {
+            1f64
+        };
+        self.heap.adjust(live_pages, factor);
+    }
+    fn get_current_heap_size_in_pages(&self) -> usize {
+        self.heap.get()
+    }
+    fn get_max_heap_size_in_pages(&self) -> usize {
+        self.heap.max_heap_pages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_HEAP_PAGES: usize = 100 * MIN_HEADROOM_PAGES;
+
+    fn timing(pause_ms: u64, mutator_ms: u64) -> GCTiming {
+        GCTiming {
+            pause: Duration::from_millis(pause_ms),
+            mutator: Duration::from_millis(mutator_ms),
+        }
+    }
+
+    #[test]
+    fn fixed_heap_size() {
+        let mut trigger = FixedHeapSizeTrigger::default();
+        trigger.init(MAX_HEAP_PAGES);
+        trigger.on_gc_end(timing(1000, 1), 0);
+        assert_eq!(trigger.get_current_heap_size_in_pages(), MAX_HEAP_PAGES);
+        assert!(!trigger.is_heap_full(MAX_HEAP_PAGES));
+        assert!(trigger.is_heap_full(MAX_HEAP_PAGES + 1));
+    }
+
+    #[test]
+    fn pause_time_shrinks_for_long_pauses() {
+        let mut trigger = PauseTimeTrigger::new(Duration::from_millis(10));
+        trigger.init(MAX_HEAP_PAGES);
+        assert_eq!(trigger.get_current_heap_size_in_pages(), MAX_HEAP_PAGES);
+
+        let live = 10 * MIN_HEADROOM_PAGES;
+        trigger.on_gc_end(timing(100, 1000), live);
+        // The headroom is halved at most.
+        let shrunk = trigger.get_current_heap_size_in_pages();
+        assert_eq!(shrunk, live + MAX_HEAP_PAGES / 2);
+
+        // Short pauses grow the headroom again, but never beyond the max heap size.
+        for _ in 0..10 {
+            trigger.on_gc_end(timing(1, 1000), live);
+        }
+        assert_eq!(trigger.get_current_heap_size_in_pages(), MAX_HEAP_PAGES);
+    }
+
+    #[test]
+    fn pause_time_keeps_min_headroom() {
+        let mut trigger = PauseTimeTrigger::new(Duration::from_millis(1));
+        trigger.init(MAX_HEAP_PAGES);
+        for _ in 0..20 {
+            trigger.on_gc_end(timing(1000, 1000), 0);
+        }
+        assert_eq!(trigger.get_current_heap_size_in_pages(), MIN_HEADROOM_PAGES);
+    }
+
+    #[test]
+    fn gc_time_ratio() {
+        let mut trigger = GCTimeRatioTrigger::new(19);
+        trigger.init(MAX_HEAP_PAGES);
+
+        // 1% of time in GC, below the 5% goal: shrink.
+        trigger.on_gc_end(timing(10, 990), 0);
+        let shrunk = trigger.get_current_heap_size_in_pages();
+        assert_eq!(shrunk, MAX_HEAP_PAGES / 2);
+
+        // 50% of time in GC, above the 5% goal: grow.
+        trigger.on_gc_end(timing(500, 500), 0);
+        assert_eq!(trigger.get_current_heap_size_in_pages(), MAX_HEAP_PAGES);
+    }
+}
@@ -1,6 +1,15 @@
+use crate::util::heap::layout::vm_layout_constants::vm_layout;
 use crate::util::Address;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// The part of the heap range that has not been reserved by any MMTk instance. All the instances in the
+    /// process reserve their spaces from this range, so spaces of different instances never overlap.
+    static ref UNRESERVED_HEAP: Mutex<(Address, Address)> =
+        Mutex::new((vm_layout().heap_start, vm_layout().heap_end));
+}
 
 pub struct HeapMeta {
     pub heap_cursor: Address,
@@ -18,6 +27,11 @@ impl HeapMeta {
     }
 
     pub fn reserve(&mut self, extent: usize, top: bool) -> Address {
+        // Skip the ranges that other MMTk instances have reserved.
+        let mut unreserved = UNRESERVED_HEAP.lock().unwrap();
+        self.heap_cursor = self.heap_cursor.max(unreserved.0);
+        self.heap_limit = self.heap_limit.min(unreserved.1);
+
         let ret = if top {
             self.heap_limit -= extent;
             self.heap_limit
@@ -36,6 +50,11 @@ impl HeapMeta {
             );
         }
 
+        if top {
+            unreserved.1 = unreserved.1.min(self.heap_limit);
+        } else {
+            unreserved.0 = unreserved.0.max(self.heap_cursor);
+        }
         ret
     }
 
@@ -32,10 +32,6 @@ impl fmt::Debug for ByteMapMmapper {
 }
 
 impl Mmapper for ByteMapMmapper {
-    fn eagerly_mmap_all_spaces(&self, _space_map: &[Address]) {
-        unimplemented!()
-    }
-
     fn mark_as_mapped(&self, start: Address, bytes: usize) {
         let start_chunk = Self::address_to_mmap_chunks_down(start);
         let end_chunk = Self::address_to_mmap_chunks_up(start + bytes) - 1;
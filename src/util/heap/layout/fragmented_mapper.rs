@@ -57,8 +57,6 @@ impl fmt::Debug for FragmentedMapper {
 }
 
 impl Mmapper for FragmentedMapper {
-    fn eagerly_mmap_all_spaces(&self, _space_map: &[Address]) {}
-
     fn mark_as_mapped(&self, mut start: Address, bytes: usize) {
         let end = start + bytes;
         // Iterate over the slabs covered
@@ -1,17 +1,32 @@
 /**
  * log_2 of the maximum number of spaces a Plan can support.
+ * The default is 16 spaces. The `max_spaces_32` and `max_spaces_64` features allow more spaces.
  */
+#[cfg(all(feature = "max_spaces_32", feature = "max_spaces_64"))]
+compile_error!("Only one of the features max_spaces_32 and max_spaces_64 can be enabled.");
+
+#[cfg(not(any(feature = "max_spaces_32", feature = "max_spaces_64")))]
 pub const LOG_MAX_SPACES: usize = 4;
+#[cfg(feature = "max_spaces_32")]
+pub const LOG_MAX_SPACES: usize = 5;
+#[cfg(feature = "max_spaces_64")]
+pub const LOG_MAX_SPACES: usize = 6;
 
 /**
  * Maximum number of spaces a Plan can support.
  */
 pub const MAX_SPACES: usize = 1 << LOG_MAX_SPACES;
 
+/**
+ * log_2 of the address space that the spaces in a 64-bit addressing model divide.
+ */
+pub const LOG_SPACES_ADDRESS_SPACE_64: usize = 45;
+
 /**
  * In a 64-bit addressing model, each space is the same size, given
  * by this constant.  At the moment, we require that the number of
  * pages in a space fit into a 32-bit signed int, so the maximum
- * size of this constant is 41 (assuming 4k pages).
+ * size of this constant is 41 (assuming 4k pages). With more spaces,
+ * each space is smaller.
  */
-pub const LOG_SPACE_SIZE_64: usize = 41;
+pub const LOG_SPACE_SIZE_64: usize = LOG_SPACES_ADDRESS_SPACE_64 - LOG_MAX_SPACES;
@@ -156,6 +156,11 @@ impl Map for Map32 {
         // This is only called during boot process by a single thread calling gc_init().
         // It is fine to get a mutable reference.
         let self_mut: &mut Self = unsafe { self.mut_self() };
+        // The discontiguous range is shared by all the discontiguous spaces, and cannot be split between instances.
+        assert!(
+            !self.finalized,
+            "Multiple MMTk instances are not supported on 32-bit targets"
+        );
         /* establish bounds of discontiguous space */
         let start_address = from;
         let first_chunk = self.get_chunk_index(start_address);
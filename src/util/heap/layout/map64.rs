@@ -19,6 +19,12 @@ pub struct Map64 {
     descriptor_map: Vec<SpaceDescriptor>,
     base_address: Vec<Address>,
     high_water: Vec<Address>,
+    /// The address range of each space. This is only used if spaces do not occupy fixed slots
+    /// (see `VMLayout::space_slots`), in which case the index of a space is not decided by its address.
+    space_ranges: Vec<(Address, Address)>,
+    /// Whether the free list of each space has been booted. Each MMTk instance boots the map when it is initialized,
+    /// and the spaces of other instances that have been booted before must not be booted again.
+    booted: Vec<bool>,
 
     // TODO: Is this the right place for this field?
     // This used to be a global variable. When we remove global states, this needs to be put somewhere.
@@ -44,6 +50,8 @@ impl Map for Map64 {
             descriptor_map: vec![SpaceDescriptor::UNINITIALIZED; MAX_CHUNKS],
             high_water,
             base_address,
+            space_ranges: vec![(Address::ZERO, Address::ZERO); MAX_SPACES],
+            booted: vec![false; MAX_SPACES],
             fl_page_resources: vec![None; MAX_SPACES],
             fl_map: vec![None; MAX_SPACES],
             finalized: false,
@@ -52,12 +60,26 @@ impl Map for Map64 {
     }
 
     fn insert(&self, start: Address, extent: usize, descriptor: SpaceDescriptor) {
-        debug_assert!(Self::is_space_start(start));
-        debug_assert!(extent <= SPACE_SIZE_64);
         // Each space will call this on exclusive address ranges. It is fine to mutate the descriptor map,
         // as each space will update different indices.
         let self_mut = unsafe { self.mut_self() };
-        let index = Self::space_index(start).unwrap();
+        let index = if vm_layout().space_slots {
+            debug_assert!(Self::is_space_start(start));
+            debug_assert!(extent <= SPACE_SIZE_64);
+            self.space_index(start).unwrap()
+        } else {
+            // Use the index that was given to the same range before (by a previous MMTk instance), or a free index.
+            let index = self
+                .space_ranges
+                .iter()
+                .position(|&(s, _)| s == start)
+                .or_else(|| self.space_ranges.iter().position(|&(s, _)| s.is_zero()))
+                .unwrap_or_else(|| panic!("More than {} spaces are created", MAX_SPACES));
+            self_mut.space_ranges[index] = (start, start + extent);
+            self_mut.base_address[index] = start;
+            self_mut.high_water[index] = start;
+            index
+        };
         self_mut.descriptor_map[index] = descriptor;
     }
 
@@ -75,7 +97,7 @@ impl Map for Map64 {
         // This is only called during creating a page resource/space/plan/mmtk instance, which is single threaded.
         let self_mut = unsafe { self.mut_self() };
         let start = pr.get_start();
-        let index = Self::space_index(start).unwrap();
+        let index = self.space_index(start).unwrap();
 
         units = (units as f64 * NON_MAP_FRACTION) as _;
         let list_extent =
@@ -114,18 +136,22 @@ impl Map for Map64 {
         chunks: usize,
         _head: Address,
     ) -> Address {
-        debug_assert!(Self::space_index(descriptor.get_start()).unwrap() == descriptor.get_index());
         // Each space will call this on exclusive address ranges. It is fine to mutate the descriptor map,
         // as each space will update different indices.
         let self_mut = unsafe { self.mut_self() };
 
-        let index = descriptor.get_index();
+        let index = self.space_index(descriptor.get_start()).unwrap();
+        debug_assert!(!vm_layout().space_slots || index == descriptor.get_index());
         let rtn = self.high_water[index];
         let extent = chunks << LOG_BYTES_IN_CHUNK;
+        if !vm_layout().space_slots && rtn + extent > self.space_ranges[index].1 {
+            // The space has used up its extent.
+            return Address::ZERO;
+        }
         self_mut.high_water[index] = rtn + extent;
 
         /* Grow the free list to accommodate the new chunks */
-        let free_list = self.fl_map[Self::space_index(descriptor.get_start()).unwrap()];
+        let free_list = self.fl_map[index];
         if let Some(free_list) = free_list {
             let free_list =
                 unsafe { &mut *(free_list as *const _ as usize as *mut RawMemoryFreeList) };
@@ -165,6 +191,9 @@ impl Map for Map64 {
         // It is fine to get a mutable reference.
         let self_mut: &mut Self = unsafe { self.mut_self() };
         for pr in 0..MAX_SPACES {
+            if self.booted[pr] {
+                continue;
+            }
             if let Some(fl) = self_mut.fl_map[pr] {
                 #[allow(clippy::cast_ref_to_mut)]
                 let fl_mut: &mut RawMemoryFreeList = unsafe { &mut *(fl as *const _ as *mut _) };
@@ -178,6 +207,9 @@ impl Map for Map64 {
         // It is fine to get a mutable reference.
         let self_mut: &mut Self = unsafe { self.mut_self() };
         for pr in 0..MAX_SPACES {
+            if self.booted[pr] {
+                continue;
+            }
             if let Some(fl) = self_mut.fl_page_resources[pr] {
                 #[allow(clippy::cast_ref_to_mut)]
                 let fl_mut: &mut CommonFreeListPageResource =
@@ -185,6 +217,7 @@ impl Map for Map64 {
                 fl_mut.resize_freelist(conversions::chunk_align_up(
                     self.fl_map[pr].unwrap().get_limit(),
                 ));
+                self_mut.booted[pr] = true;
             }
         }
         self_mut.finalized = true;
@@ -200,7 +233,14 @@ impl Map for Map64 {
 
     #[inline]
     fn get_descriptor_for_address(&self, address: Address) -> SpaceDescriptor {
-        let index = Self::space_index(address).unwrap();
+        if !vm_layout().space_slots {
+            return self
+                .space_index(address)
+                .map_or(SpaceDescriptor::UNINITIALIZED, |index| {
+                    self.descriptor_map[index]
+                });
+        }
+        let index = self.space_index(address).unwrap();
         self.descriptor_map[index]
     }
 
@@ -222,11 +262,18 @@ impl Map64 {
         &mut *(self as *const _ as *mut _)
     }
 
-    fn space_index(addr: Address) -> Option<usize> {
-        if addr > HEAP_END {
+    fn space_index(&self, addr: Address) -> Option<usize> {
+        let layout = vm_layout();
+        if addr > layout.heap_end {
             return None;
         }
-        Some(addr >> SPACE_SHIFT_64)
+        if layout.space_slots {
+            Some(addr >> SPACE_SHIFT_64)
+        } else {
+            self.space_ranges
+                .iter()
+                .position(|&(start, end)| start <= addr && addr < end)
+        }
     }
 
     fn is_space_start(base: Address) -> bool {
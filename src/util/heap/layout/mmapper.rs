@@ -1,3 +1,4 @@
+use crate::util::conversions::bytes_to_pages_up;
 use crate::util::heap::layout::vm_layout_constants::*;
 use crate::util::memory::*;
 use crate::util::Address;
@@ -12,7 +13,16 @@ pub trait Mmapper {
     /// Arguments:
     /// * `spaceMap`: An address array containing a pairs of start and end
     ///   addresses for each of the regions to be mapped
-    fn eagerly_mmap_all_spaces(&self, space_map: &[Address]);
+    fn eagerly_mmap_all_spaces(&self, space_map: &[Address]) -> Result<()> {
+        debug_assert!(space_map.len() % 2 == 0);
+        for region in space_map.chunks(2) {
+            let (start, end) = (region[0], region[1]);
+            if start < end {
+                self.ensure_mapped(start, bytes_to_pages_up(end - start))?;
+            }
+        }
+        Ok(())
+    }
 
     /// Mark a number of pages as mapped, without making any
     /// request to the operating system.  Used to mark pages
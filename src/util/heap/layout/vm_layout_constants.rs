@@ -2,7 +2,7 @@ use super::heap_parameters::*;
 use crate::util::constants::*;
 use crate::util::Address;
 
-use crate::util::conversions::{chunk_align_down, chunk_align_up};
+use crate::util::conversions::{chunk_align_down, chunk_align_up, raw_is_aligned};
 
 /** log_2 of the addressable virtual space */
 #[cfg(target_pointer_width = "64")]
@@ -72,7 +72,8 @@ pub const VM_SPACE_SIZE: usize =
 /**
  * Lowest virtual address available for MMTk to manage.  The address space between
  * HEAP_START and AVAILABLE_START comprises memory directly managed by the VM,
- * and not available to MMTk.
+ * and not available to MMTk. This is for the default layout. Use `vm_layout().available_start()`
+ * for the layout in use.
  */
 #[cfg(feature = "vm_space")]
 pub const AVAILABLE_START: Address = HEAP_START.add(VM_SPACE_SIZE);
@@ -82,11 +83,12 @@ pub const AVAILABLE_START: Address = HEAP_START;
 /**
  * Highest virtual address available for MMTk to manage.  The address space between
  * HEAP_END and AVAILABLE_END comprises memory directly managed by the VM,
- * and not available to MMTk.
+ * and not available to MMTk. This is for the default layout. Use `vm_layout().available_end()`
+ * for the layout in use.
 */
 pub const AVAILABLE_END: Address = HEAP_END;
 
-/** Size of the address space available to the MMTk heap. */
+/** Size of the address space available to the MMTk heap in the default layout (see `VMLayout::available_bytes()`). */
 pub const AVAILABLE_BYTES: usize = AVAILABLE_END.get_extent(AVAILABLE_START);
 
 /** Granularity at which we map and unmap virtual address space in the heap */
@@ -145,3 +147,314 @@ pub const SPACE_MASK_64: usize = ((1 << LOG_MAX_SPACES) - 1) << SPACE_SHIFT_64;
 pub const SPACE_SIZE_64: usize = 1 << LOG_SPACE_SIZE_64;
 #[cfg(target_pointer_width = "32")]
 pub const SPACE_SIZE_64: usize = MAX_SPACE_EXTENT;
+
+/// The layout of the heap in the virtual address space. By default, MMTk uses [`VMLayout::DEFAULT`], which is
+/// based on the constants above. A binding can choose a different layout with `memory_manager::set_vm_layout()`
+/// before creating an MMTk instance.
+#[derive(Clone, Copy, Debug)]
+pub struct VMLayout {
+    /// Lowest virtual address used by the heap.
+    pub heap_start: Address,
+    /// Highest virtual address used by the heap.
+    pub heap_end: Address,
+    /// Only used in the 64-bit layout. If true, each space occupies a fixed slot of `SPACE_SIZE_64` bytes, and
+    /// the space of an address is decided by its high bits (see `SPACE_SHIFT_64`). If false, spaces are placed
+    /// one after another in `[heap_start, heap_end)`, and each space gets an extent of `space_extent`.
+    pub space_slots: bool,
+    /// The extent of each space in the 64-bit layout. This must be `SPACE_SIZE_64` if `space_slots` is true.
+    pub space_extent: usize,
+}
+
+/// In a contiguous layout, the heap range is divided into this many spaces by default.
+pub const CONTIGUOUS_LAYOUT_SPACES: usize = 8;
+
+impl VMLayout {
+    /// The default layout, which uses `HEAP_START` and `HEAP_END`.
+    pub const DEFAULT: VMLayout = VMLayout {
+        heap_start: HEAP_START,
+        heap_end: HEAP_END,
+        space_slots: true,
+        space_extent: SPACE_SIZE_64,
+    };
+
+    /// A 64-bit layout that places all the spaces one after another in `[heap_start, heap_end)`. If the range is
+    /// below 32 GB, a binding can use 32-bit compressed pointers. The range is divided into
+    /// `CONTIGUOUS_LAYOUT_SPACES` spaces. A binding can change `space_extent` if its plan uses fewer or more spaces.
+    #[cfg(target_pointer_width = "64")]
+    pub fn contiguous(heap_start: Address, heap_end: Address) -> Self {
+        use crate::util::conversions::raw_align_down;
+        let space_extent = raw_align_down(
+            (heap_end - heap_start) / CONTIGUOUS_LAYOUT_SPACES,
+            BYTES_IN_CHUNK,
+        );
+        VMLayout {
+            heap_start,
+            heap_end,
+            space_slots: false,
+            space_extent,
+        }
+    }
+
+    /// Lowest virtual address available for MMTk to manage. With the `vm_space` feature, the VM space is at the start
+    /// of the heap range.
+    pub fn available_start(&self) -> Address {
+        if cfg!(feature = "vm_space") {
+            self.heap_start + VM_SPACE_SIZE
+        } else {
+            self.heap_start
+        }
+    }
+
+    /// Highest virtual address available for MMTk to manage.
+    pub fn available_end(&self) -> Address {
+        self.heap_end
+    }
+
+    /// Size of the address space available to MMTk.
+    pub fn available_bytes(&self) -> usize {
+        self.available_end() - self.available_start()
+    }
+
+    /// The extent given to a space that does not ask for a specific size.
+    pub fn max_space_extent(&self) -> usize {
+        if self.space_slots {
+            MAX_SPACE_EXTENT
+        } else {
+            self.space_extent
+        }
+    }
+
+    fn validate(&self) {
+        assert!(
+            raw_is_aligned(self.heap_start.as_usize(), BYTES_IN_CHUNK)
+                && raw_is_aligned(self.heap_end.as_usize(), BYTES_IN_CHUNK),
+            "The heap range [{}, {}) is not chunk aligned",
+            self.heap_start,
+            self.heap_end
+        );
+        assert!(
+            !self.heap_start.is_zero() && self.heap_start < self.heap_end,
+            "Invalid heap range [{}, {})",
+            self.heap_start,
+            self.heap_end
+        );
+        // The SFT map and the side metadata only cover this much address space.
+        assert!(
+            1usize
+                .checked_shl(LOG_ADDRESS_SPACE as u32)
+                .map_or(true, |limit| self.heap_end.as_usize() <= limit),
+            "The heap range [{}, {}) is beyond the address space that MMTk supports",
+            self.heap_start,
+            self.heap_end
+        );
+        if self.space_slots {
+            assert_eq!(self.space_extent, SPACE_SIZE_64);
+        } else {
+            assert!(
+                cfg!(target_pointer_width = "64"),
+                "Only 64-bit layouts can place spaces contiguously"
+            );
+            assert!(
+                self.space_extent > 0 && raw_is_aligned(self.space_extent, BYTES_IN_CHUNK),
+                "The space extent {} is not chunk aligned",
+                self.space_extent
+            );
+        }
+    }
+}
+
+/// The layout is set once: by the binding, by relocating the heap, or to the default layout when it is first used.
+static VM_LAYOUT: spin::Once<VMLayout> = spin::Once::new();
+
+/// The layout of the heap. The layout cannot be changed after this is called.
+pub fn vm_layout() -> &'static VMLayout {
+    VM_LAYOUT.call_once(|| VMLayout::DEFAULT)
+}
+
+/// Set the layout of the heap. This can only be called once, before the layout is used (e.g. by creating an MMTk instance).
+pub(crate) fn set_vm_layout(layout: VMLayout) {
+    layout.validate();
+    let mut set = false;
+    VM_LAYOUT.call_once(|| {
+        set = true;
+        layout
+    });
+    assert!(
+        set,
+        "The VM layout cannot be changed after it is set or used (e.g. by creating an MMTk instance)"
+    );
+}
+
+/// Prevent the layout from being changed. This is called when an MMTk instance is created.
+pub(crate) fn freeze_vm_layout() {
+    vm_layout();
+}
+
+/// log_2 of the smallest heap range that we relocate the heap to. With `CONTIGUOUS_LAYOUT_SPACES` spaces, each space gets 4 GB.
+#[cfg(target_pointer_width = "64")]
+const LOG_MIN_RELOCATED_HEAP_BYTES: usize = 35;
+
+/// We only relocate the side metadata below this address, which is the end of the user address space with 4-level page tables
+/// on x86_64 and aarch64.
+#[cfg(target_pointer_width = "64")]
+const RELOCATION_LIMIT: usize = 1 << 47;
+
+/// If something else in the process (e.g. ASan, or another runtime) has mapped memory in the heap range of the default layout,
+/// relocate the heap to a free range, and use a contiguous layout (see `VMLayout::contiguous()`) there. If the address range
+/// reserved for the side metadata is occupied, or overlaps the relocated heap, the side metadata is moved to a free range as well
+/// (see `side_metadata::side_metadata_relocation()`). This is called before the first MMTk instance is created, and does nothing
+/// if the binding has chosen a layout.
+#[cfg(target_pointer_width = "64")]
+pub(crate) fn relocate_heap_if_occupied() {
+    use crate::util::memory;
+    use crate::util::metadata::side_metadata::{
+        self, GLOBAL_SIDE_METADATA_BASE_ADDRESS, SIDE_METADATA_END_ADDRESS,
+    };
+
+    if VM_LAYOUT.r#try().is_some() {
+        return;
+    }
+    let mut heap = (VMLayout::DEFAULT.heap_start, VMLayout::DEFAULT.heap_end);
+    let heap_occupied = !memory::is_range_unmapped(heap.0, heap.1 - heap.0);
+    if heap_occupied {
+        warn!(
+            "The heap range [{}, {}) is occupied by other mappings:\n{}",
+            heap.0,
+            heap.1,
+            memory::get_overlapping_mappings(&[(heap.0, heap.1 - heap.0)]).join("\n")
+        );
+        // The SFT map and the side metadata only cover this much address space.
+        let limit = unsafe { Address::from_usize(1 << LOG_ADDRESS_SPACE) };
+        match find_free_heap_range(limit) {
+            Some(range) => {
+                warn!("Relocating the heap to [{}, {})", range.0, range.1);
+                let layout = VMLayout::contiguous(range.0, range.1);
+                if VM_LAYOUT.call_once(|| layout).heap_start != range.0 {
+                    warn!("The VM layout was set by another thread. The heap is not relocated");
+                    return;
+                }
+                heap = range;
+            }
+            None => {
+                warn!("Failed to find a free range to relocate the heap to");
+                return;
+            }
+        }
+    }
+
+    // The side metadata is inside the heap range of the default layout, and is mapped by MMTk on demand. We only need to move
+    // it if the rest of the range is occupied, or if the heap has been moved onto it.
+    let metadata = (GLOBAL_SIDE_METADATA_BASE_ADDRESS, SIDE_METADATA_END_ADDRESS);
+    let metadata_bytes = metadata.1 - metadata.0;
+    let overlaps_heap = heap_occupied && metadata.0 < heap.1 && heap.0 < metadata.1;
+    if !overlaps_heap && memory::is_range_unmapped(metadata.0, metadata_bytes) {
+        return;
+    }
+    if !overlaps_heap {
+        warn!(
+            "The side metadata range [{}, {}) is occupied by other mappings:\n{}",
+            metadata.0,
+            metadata.1,
+            memory::get_overlapping_mappings(&[(metadata.0, metadata_bytes)]).join("\n")
+        );
+    }
+    let limit = unsafe { Address::from_usize(RELOCATION_LIMIT) };
+    match find_free_range(metadata_bytes.next_power_of_two(), limit, heap) {
+        Some(start) => {
+            warn!(
+                "Relocating the side metadata to [{}, {})",
+                start,
+                start + metadata_bytes
+            );
+            side_metadata::set_side_metadata_relocation(start - metadata.0);
+        }
+        None => warn!("Failed to find a free range to relocate the side metadata to"),
+    }
+}
+
+/// There are no fixed slots for spaces in the 32-bit layout, and we do not relocate the heap.
+#[cfg(target_pointer_width = "32")]
+pub(crate) fn relocate_heap_if_occupied() {}
+
+/// Find the largest free range below `limit`. The ranges we probe are aligned to their size.
+#[cfg(target_pointer_width = "64")]
+fn find_free_heap_range(limit: Address) -> Option<(Address, Address)> {
+    let log_max = BITS_IN_WORD - 1 - limit.as_usize().leading_zeros() as usize;
+    (LOG_MIN_RELOCATED_HEAP_BYTES..=log_max)
+        .rev()
+        .find_map(|log_bytes| {
+            let bytes = 1usize << log_bytes;
+            find_free_range(bytes, limit, (Address::ZERO, Address::ZERO))
+                .map(|start| (start, start + bytes))
+        })
+}
+
+/// Find a free range of `bytes` below `limit` that does not overlap the range `avoid`, by probing with `MAP_FIXED_NOREPLACE`.
+/// The ranges we probe are aligned to `bytes`, which must be a power of two.
+#[cfg(target_pointer_width = "64")]
+fn find_free_range(bytes: usize, limit: Address, avoid: (Address, Address)) -> Option<Address> {
+    debug_assert!(bytes.is_power_of_two());
+    // Skip the range from 0, so we never place anything at the null address.
+    let mut start = unsafe { Address::from_usize(bytes) };
+    while start.as_usize() < limit.as_usize() && limit - start >= bytes {
+        let overlaps = start < avoid.1 && avoid.0 < start + bytes;
+        if !overlaps && crate::util::memory::is_range_unmapped(start, bytes) {
+            return Some(start);
+        }
+        start += bytes;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout() {
+        let layout = VMLayout::DEFAULT;
+        layout.validate();
+        assert_eq!(layout.max_space_extent(), MAX_SPACE_EXTENT);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn contiguous_layout_below_32g() {
+        let layout = VMLayout::contiguous(unsafe { Address::from_usize(0x1_0000_0000) }, unsafe {
+            Address::from_usize(0x8_0000_0000)
+        });
+        layout.validate();
+        assert!(!layout.space_slots);
+        assert_eq!(
+            layout.max_space_extent(),
+            0x7_0000_0000 / CONTIGUOUS_LAYOUT_SPACES
+        );
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn find_free_heap_range_below_limit() {
+        let limit = unsafe { Address::from_usize(1 << LOG_ADDRESS_SPACE) };
+        let (start, end) = find_free_heap_range(limit).unwrap();
+        assert!(!start.is_zero() && end <= limit);
+        assert!(start.is_aligned_to(end - start));
+        assert!(end - start >= 1 << LOG_MIN_RELOCATED_HEAP_BYTES);
+        VMLayout::contiguous(start, end).validate();
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn find_free_range_avoids_range() {
+        use crate::util::metadata::side_metadata::{
+            GLOBAL_SIDE_METADATA_BASE_ADDRESS, SIDE_METADATA_END_ADDRESS,
+        };
+        let bytes =
+            (SIDE_METADATA_END_ADDRESS - GLOBAL_SIDE_METADATA_BASE_ADDRESS).next_power_of_two();
+        let limit = unsafe { Address::from_usize(RELOCATION_LIMIT) };
+        let first = find_free_range(bytes, limit, (Address::ZERO, Address::ZERO)).unwrap();
+        assert!(first.is_aligned_to(bytes) && first + bytes <= limit);
+        // The range that we avoid is never returned, even if it is free.
+        let second = find_free_range(bytes, limit, (first, first + bytes)).unwrap();
+        assert!(second >= first + bytes || second + bytes <= first);
+    }
+}
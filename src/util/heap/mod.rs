@@ -2,9 +2,11 @@ mod accounting;
 #[macro_use]
 pub mod layout;
 pub mod freelistpageresource;
+pub mod gc_trigger;
 mod heap_meta;
 pub mod monotonepageresource;
 pub mod pageresource;
+pub(crate) mod pretouch;
 pub mod space_descriptor;
 mod vmrequest;
 
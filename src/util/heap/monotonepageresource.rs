@@ -14,6 +14,7 @@ use super::pageresource::{PRAllocFail, PRAllocResult};
 use super::PageResource;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::space_descriptor::SpaceDescriptor;
+use crate::util::memory;
 use crate::vm::VMBinding;
 use std::marker::PhantomData;
 
@@ -23,6 +24,9 @@ pub struct MonotonePageResource<VM: VMBinding> {
     /** Number of pages to reserve at the start of every allocation */
     meta_data_pages_per_region: usize,
     sync: Mutex<MonotonePageResourceSync>,
+    /// Protect released pages (see the `protect_on_release` option). Only a contiguous page resource protects its pages.
+    /// The chunks of a discontiguous page resource are returned to the VM map, and another space may take them.
+    pub(crate) protect_memory_on_release: bool,
     _p: PhantomData<VM>,
 }
 
@@ -34,6 +38,9 @@ struct MonotonePageResourceSync {
     /** Base address of the current chunk of addresses */
     current_chunk: Address,
     conditional: MonotonePageResourceConditional,
+    /// The end of the released pages that are protected (see the `protect_on_release` option). The pages from the start
+    /// of a contiguous space up to here are protected, unless they have been allocated again.
+    protected_end: Address,
 }
 
 pub enum MonotonePageResourceConditional {
@@ -140,6 +147,16 @@ impl<VM: VMBinding> PageResource<VM> for MonotonePageResource<VM> {
             Result::Err(PRAllocFail)
         } else {
             //debug!("tmp={:?} <= sync.sentinel={:?}", tmp, sync.sentinel);
+            if sync.cursor < sync.protected_end {
+                // Unprotect the released pages that we allocate again.
+                let end = std::cmp::min(tmp, sync.protected_end);
+                if let Err(e) = memory::munprotect(sync.cursor, end - sync.cursor) {
+                    panic!(
+                        "Failed at unprotecting memory (starting at {}): {:?}",
+                        sync.cursor, e
+                    );
+                }
+            }
             sync.cursor = tmp;
             debug!("update cursor = {}", tmp);
 
@@ -186,7 +203,9 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
                     zeroing_cursor: sentinel,
                     zeroing_sentinel: start,
                 },
+                protected_end: start,
             }),
+            protect_memory_on_release: false,
             _p: PhantomData,
         }
     }
@@ -201,7 +220,9 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
                 current_chunk: unsafe { Address::zero() },
                 sentinel: unsafe { Address::zero() },
                 conditional: MonotonePageResourceConditional::Discontiguous,
+                protected_end: unsafe { Address::zero() },
             }),
+            protect_memory_on_release: false,
             _p: PhantomData,
         }
     }
@@ -279,10 +300,24 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
     unsafe fn release_pages(&self, guard: &mut MutexGuard<MonotonePageResourceSync>) {
         // TODO: concurrent zeroing
         if self.common().contiguous {
-            guard.cursor = match guard.conditional {
+            let start = match guard.conditional {
                 MonotonePageResourceConditional::Contiguous { start: _start, .. } => _start,
                 _ => unreachable!(),
             };
+            self.release_pages_extent(start, guard.cursor - start);
+            // The chunks of a discontiguous space release their metadata when they are returned to the global pool.
+            self.common
+                .release_side_metadata(start, guard.cursor - start);
+            if self.protect_memory_on_release {
+                if let Err(e) = memory::mprotect(start, guard.cursor - start) {
+                    panic!(
+                        "Failed at protecting memory (starting at {}): {:?}",
+                        start, e
+                    );
+                }
+                guard.protected_end = std::cmp::max(guard.protected_end, guard.cursor);
+            }
+            guard.cursor = start;
         } else if !guard.cursor.is_zero() {
             let bytes = guard.cursor - guard.current_chunk;
             self.release_pages_extent(guard.current_chunk, bytes);
@@ -298,11 +333,12 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
         }
     }
 
-    fn release_pages_extent(&self, _first: Address, bytes: usize) {
+    fn release_pages_extent(&self, first: Address, bytes: usize) {
         let pages = crate::util::conversions::bytes_to_pages(bytes);
         debug_assert!(bytes == crate::util::conversions::pages_to_bytes(pages));
-        // FIXME ZERO_PAGES_ON_RELEASE
-        // FIXME Options.protectOnRelease
+        if self.common.poison_memory_on_release {
+            memory::poison(first, bytes);
+        }
         // FIXME VM.events.tracePageReleased
     }
 
@@ -8,6 +8,7 @@ use super::layout::map::Map;
 use crate::util::heap::layout::heap_layout::VMMap;
 use crate::util::heap::space_descriptor::SpaceDescriptor;
 use crate::util::heap::PageAccounting;
+use crate::util::metadata::side_metadata::SideMetadataContext;
 use crate::vm::VMBinding;
 
 pub trait PageResource<VM: VMBinding>: 'static {
@@ -113,6 +114,12 @@ pub struct CommonPageResource {
 
     vm_map: &'static VMMap,
     head_discontiguous_region: Mutex<Address>,
+    /// The side metadata of the space. When pages are released (for contiguous page resources) or chunks are returned
+    /// to the global pool, their side metadata is released as well (see `bind_side_metadata()`).
+    metadata: spin::Once<SideMetadataContext>,
+    /// Fill released pages with `memory::POISON_PATTERN` (see the `poison_on_release` option). This is set by the space
+    /// when it creates the page resource.
+    pub poison_memory_on_release: bool,
 }
 
 impl CommonPageResource {
@@ -125,6 +132,22 @@ impl CommonPageResource {
             vm_map,
 
             head_discontiguous_region: Mutex::new(Address::ZERO),
+            metadata: spin::Once::new(),
+            poison_memory_on_release: false,
+        }
+    }
+
+    /// Set the side metadata of the space that owns this page resource. This is called when the space is initialized.
+    pub fn bind_side_metadata(&self, metadata: SideMetadataContext) {
+        self.metadata.call_once(|| metadata);
+    }
+
+    /// Release the side metadata for the pages that are released, or the chunks that are returned to the global pool.
+    /// The pages will be allocated again (by another space if the chunks are returned), and the metadata for them is
+    /// expected to be zeroed. This must be called before the pages are available for allocation again.
+    pub fn release_side_metadata(&self, start: Address, bytes: usize) {
+        if let Some(metadata) = self.metadata.r#try() {
+            metadata.release_metadata_space(start, bytes);
         }
     }
 
@@ -159,11 +182,17 @@ impl CommonPageResource {
         if chunk == *head_discontiguous_region {
             *head_discontiguous_region = self.vm_map.get_next_contiguous_region(chunk);
         }
+        self.release_side_metadata(chunk, self.vm_map.get_contiguous_region_size(chunk));
         self.vm_map.free_contiguous_chunks(chunk);
     }
 
     pub fn release_all_chunks(&self) {
         let mut head_discontiguous_region = self.head_discontiguous_region.lock().unwrap();
+        let mut region = *head_discontiguous_region;
+        while !region.is_zero() {
+            self.release_side_metadata(region, self.vm_map.get_contiguous_region_size(region));
+            region = self.vm_map.get_next_contiguous_region(region);
+        }
         self.vm_map.free_all_chunks(*head_discontiguous_region);
         *head_discontiguous_region = Address::ZERO;
     }
@@ -171,4 +200,20 @@ impl CommonPageResource {
     pub fn get_head_discontiguous_region(&self) -> Address {
         *self.head_discontiguous_region.lock().unwrap()
     }
+
+    /// Get the address ranges (start and end) of the chunks of a discontiguous space. The chunk list is walked
+    /// with the lock held, so chunks cannot be added or released by other threads during the walk.
+    pub fn get_discontiguous_regions(&self) -> Vec<(Address, Address)> {
+        let head_discontiguous_region = self.head_discontiguous_region.lock().unwrap();
+        let mut regions = vec![];
+        let mut region = *head_discontiguous_region;
+        while !region.is_zero() {
+            regions.push((
+                region,
+                region + self.vm_map.get_contiguous_region_size(region),
+            ));
+            region = self.vm_map.get_next_contiguous_region(region);
+        }
+        regions
+    }
 }
@@ -0,0 +1,160 @@
+//! Pre-touching the heap. With the `pretouch_heap` option, MMTk maps the heap and its side metadata
+//! in `gc_init()`, and writes to every page so the page faults happen at start-up rather than when
+//! the spaces grow later.
+
+use crate::plan::Plan;
+use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::heap::layout::vm_layout_constants::{BYTES_IN_CHUNK, LOG_BYTES_IN_CHUNK};
+use crate::util::Address;
+use crate::vm::VMBinding;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// Map and touch the first pages of each contiguous space in the plan, and the side metadata for them.
+/// The spaces share `heap_size` bytes (see `split_heap()`), so at most `heap_size` bytes of the spaces are
+/// touched in total. The side metadata of the touched pages is touched in addition.
+pub(crate) fn pretouch_heap<VM: VMBinding>(
+    plan: &dyn Plan<VM = VM>,
+    heap_size: usize,
+    threads: usize,
+) {
+    let mut num_spaces = 0;
+    plan.for_each_space(&mut |space| {
+        if space.common().contiguous {
+            num_spaces += 1;
+        }
+    });
+    let mut shares = split_heap(heap_size, num_spaces).into_iter();
+    let mut ranges = vec![];
+    plan.for_each_space(&mut |space| {
+        if space.common().contiguous {
+            let bytes = shares.next().unwrap();
+            if bytes != 0 {
+                ranges.extend(space.eagerly_map(bytes));
+            }
+        }
+    });
+    let bytes: usize = ranges.iter().map(|(_, size)| size).sum();
+    info!(
+        "Pre-touching {} bytes in {} ranges with {} threads",
+        bytes,
+        ranges.len(),
+        threads
+    );
+    touch_ranges(&ranges, threads);
+}
+
+/// Split `heap_size` bytes between `num_spaces` spaces in whole chunks. The spaces get the same number of
+/// chunks, and the first spaces get one more chunk each if the chunks cannot be split evenly. The remainder
+/// of the heap size that is smaller than a chunk is not given to any space.
+fn split_heap(heap_size: usize, num_spaces: usize) -> Vec<usize> {
+    if num_spaces == 0 {
+        return vec![];
+    }
+    let chunks = heap_size >> LOG_BYTES_IN_CHUNK;
+    (0..num_spaces)
+        .map(|i| {
+            let extra = if i < chunks % num_spaces { 1 } else { 0 };
+            (chunks / num_spaces + extra) << LOG_BYTES_IN_CHUNK
+        })
+        .collect()
+}
+
+/// Touch every page in the ranges. The ranges are split into chunk-sized work units which are shared
+/// between `threads` threads. The GC workers are not used, as they may not have been spawned yet.
+fn touch_ranges(ranges: &[(Address, usize)], threads: usize) {
+    let mut units = vec![];
+    for &(start, size) in ranges {
+        let end = start + size;
+        let mut cursor = start;
+        while cursor < end {
+            let limit = std::cmp::min(cursor + BYTES_IN_CHUNK, end);
+            units.push((cursor, limit));
+            cursor = limit;
+        }
+    }
+
+    let units = Arc::new(units);
+    let next = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let units = units.clone();
+            let next = next.clone();
+            thread::spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= units.len() {
+                    break;
+                }
+                let (start, end) = units[i];
+                let mut page = start.align_down(BYTES_IN_PAGE);
+                while page < end {
+                    // An atomic add of zero commits the page without changing its content.
+                    unsafe { (*page.to_ptr::<AtomicU8>()).fetch_add(0, Ordering::Relaxed) };
+                    page += BYTES_IN_PAGE;
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::memory;
+    use crate::util::test_util::MEMORY_TEST_REGION;
+    use crate::util::test_util::{serial_test, with_cleanup};
+
+    const START: Address = MEMORY_TEST_REGION.start;
+    const SIZE: usize = BYTES_IN_PAGE * 16;
+
+    #[test]
+    fn test_split_heap() {
+        let heap_size = 10 * BYTES_IN_CHUNK + BYTES_IN_PAGE;
+        let shares = split_heap(heap_size, 4);
+        assert_eq!(
+            shares,
+            vec![
+                3 * BYTES_IN_CHUNK,
+                3 * BYTES_IN_CHUNK,
+                2 * BYTES_IN_CHUNK,
+                2 * BYTES_IN_CHUNK
+            ]
+        );
+        // The spaces never get more than the heap size in total.
+        for spaces in 1..20 {
+            let total: usize = split_heap(heap_size, spaces).iter().sum();
+            assert_eq!(total, 10 * BYTES_IN_CHUNK);
+        }
+        assert_eq!(split_heap(BYTES_IN_CHUNK - 1, 2), vec![0, 0]);
+        assert!(split_heap(heap_size, 0).is_empty());
+    }
+
+    #[test]
+    fn test_touch_ranges() {
+        serial_test(|| {
+            with_cleanup(
+                || {
+                    assert!(memory::dzmmap_noreplace(START, SIZE).is_ok());
+                    unsafe { START.store::<u8>(42) };
+                    touch_ranges(
+                        &[
+                            (START, BYTES_IN_PAGE),
+                            (START + BYTES_IN_PAGE, SIZE - BYTES_IN_PAGE),
+                        ],
+                        4,
+                    );
+                    // Touching does not change the memory.
+                    assert_eq!(unsafe { START.load::<u8>() }, 42);
+                    assert_eq!(unsafe { (START + BYTES_IN_PAGE).load::<u8>() }, 0);
+                },
+                || {
+                    assert!(memory::munmap(START, SIZE).is_ok());
+                },
+            )
+        })
+    }
+}
@@ -26,6 +26,17 @@ const BASE_EXPONENT: usize = BITS_IN_INT - MANTISSA_BITS;
 const INDEX_MASK: usize = !TYPE_MASK;
 const INDEX_SHIFT: usize = TYPE_BITS;
 
+// In a 64-bit layout without space slots (see `VMLayout::space_slots`), a contiguous descriptor
+// records the start chunk and the number of chunks of the space.
+#[cfg(target_pointer_width = "64")]
+const START_CHUNK_SHIFT: usize = TYPE_BITS;
+#[cfg(target_pointer_width = "64")]
+const CHUNK_FIELD_BITS: usize = 31;
+#[cfg(target_pointer_width = "64")]
+const CHUNK_FIELD_MASK: usize = (1 << CHUNK_FIELD_BITS) - 1;
+#[cfg(target_pointer_width = "64")]
+const EXTENT_CHUNKS_SHIFT: usize = START_CHUNK_SHIFT + CHUNK_FIELD_BITS;
+
 static DISCONTIGUOUS_SPACE_INDEX: AtomicUsize = AtomicUsize::new(DISCONTIG_INDEX_INCREMENT);
 const DISCONTIG_INDEX_INCREMENT: usize = 1 << TYPE_BITS;
 
@@ -36,9 +47,13 @@ impl SpaceDescriptor {
     pub const UNINITIALIZED: Self = SpaceDescriptor(0);
 
     pub fn create_descriptor_from_heap_range(start: Address, end: Address) -> SpaceDescriptor {
-        let top = end == vm_layout_constants::HEAP_END;
+        let layout = vm_layout_constants::vm_layout();
+        let top = end == layout.heap_end;
+        if cfg!(target_pointer_width = "64") && !layout.space_slots {
+            return Self::create_descriptor_from_chunks(start, end, top);
+        }
         if cfg!(target_pointer_width = "64") {
-            let space_index = if start > vm_layout_constants::HEAP_END {
+            let space_index = if start > layout.heap_end {
                 ::std::usize::MAX
             } else {
                 start >> vm_layout_constants::SPACE_SHIFT_64
@@ -74,6 +89,31 @@ impl SpaceDescriptor {
         )
     }
 
+    #[cfg(target_pointer_width = "64")]
+    fn create_descriptor_from_chunks(start: Address, end: Address, top: bool) -> SpaceDescriptor {
+        let start_chunk = start >> vm_layout_constants::LOG_BYTES_IN_CHUNK;
+        let chunks = (end - start) >> vm_layout_constants::LOG_BYTES_IN_CHUNK;
+        debug_assert!(start_chunk <= CHUNK_FIELD_MASK && chunks <= CHUNK_FIELD_MASK);
+        SpaceDescriptor(
+            (chunks << EXTENT_CHUNKS_SHIFT)
+                | (start_chunk << START_CHUNK_SHIFT)
+                | (if top {
+                    TYPE_CONTIGUOUS_HI
+                } else {
+                    TYPE_CONTIGUOUS
+                }),
+        )
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    fn create_descriptor_from_chunks(
+        _start: Address,
+        _end: Address,
+        _top: bool,
+    ) -> SpaceDescriptor {
+        unreachable!("Only 64-bit layouts can place spaces contiguously")
+    }
+
     pub fn create_descriptor() -> SpaceDescriptor {
         let next =
             DISCONTIGUOUS_SPACE_INDEX.fetch_add(DISCONTIG_INDEX_INCREMENT, Ordering::Relaxed);
@@ -97,6 +137,12 @@ impl SpaceDescriptor {
     #[cfg(target_pointer_width = "64")]
     pub fn get_start(self) -> Address {
         use crate::util::heap::layout::heap_parameters;
+        if !vm_layout_constants::vm_layout().space_slots {
+            let start_chunk = (self.0 >> START_CHUNK_SHIFT) & CHUNK_FIELD_MASK;
+            return unsafe {
+                Address::from_usize(start_chunk << vm_layout_constants::LOG_BYTES_IN_CHUNK)
+            };
+        }
         unsafe { Address::from_usize(self.get_index() << heap_parameters::LOG_SPACE_SIZE_64) }
     }
 
@@ -112,6 +158,10 @@ impl SpaceDescriptor {
 
     #[cfg(target_pointer_width = "64")]
     pub fn get_extent(self) -> usize {
+        if !vm_layout_constants::vm_layout().space_slots {
+            let chunks = (self.0 >> EXTENT_CHUNKS_SHIFT) & CHUNK_FIELD_MASK;
+            return chunks << vm_layout_constants::LOG_BYTES_IN_CHUNK;
+        }
         vm_layout_constants::SPACE_SIZE_64
     }
 
@@ -122,6 +172,7 @@ impl SpaceDescriptor {
         chunks << vm_layout_constants::LOG_BYTES_IN_CHUNK
     }
 
+    /// The index of the space slot. This is only meaningful if spaces occupy slots (see `VMLayout::space_slots`).
     #[cfg(target_pointer_width = "64")]
     pub fn get_index(self) -> usize {
         (self.0 & INDEX_MASK) >> INDEX_SHIFT
@@ -27,7 +27,7 @@ impl VMRequest {
 
     pub fn common64bit(top: bool) -> Self {
         VMRequest::Extent {
-            extent: MAX_SPACE_EXTENT,
+            extent: vm_layout().max_space_extent(),
             top,
         }
     }
@@ -0,0 +1,157 @@
+use crate::util::Address;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+
+#[derive(Default)]
+struct LazySweepState {
+    /// Chunks that are not yet swept.
+    pending: Vec<Address>,
+    /// Chunks that are being swept.
+    sweeping: Vec<Address>,
+}
+
+/// Chunks to be swept after mutators resume. A GC queues its chunks here instead of sweeping
+/// them in the `Release` stage. A chunk is then swept by a mutator that needs it (e.g. it allocates
+/// into the chunk, or it needs free memory), or by a GC worker in the background. Each chunk is swept
+/// by exactly one thread. All the chunks must be swept before the next GC starts marking.
+#[derive(Default)]
+pub struct LazySweeper {
+    state: Mutex<LazySweepState>,
+    /// Notified when a chunk has been swept.
+    swept: Condvar,
+    /// The number of chunks that are pending or being swept.
+    outstanding: AtomicUsize,
+}
+
+impl LazySweeper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue chunks to be swept lazily.
+    pub fn add_chunks(&self, chunks: impl IntoIterator<Item = Address>) {
+        let mut state = self.state.lock().unwrap();
+        let before = state.pending.len();
+        state.pending.extend(chunks);
+        self.outstanding
+            .fetch_add(state.pending.len() - before, Ordering::SeqCst);
+    }
+
+    /// Have all the queued chunks been swept?
+    #[inline(always)]
+    pub fn is_complete(&self) -> bool {
+        self.outstanding.load(Ordering::SeqCst) == 0
+    }
+
+    /// Sweep one pending chunk with `sweep`. Return false if no chunk is pending.
+    pub fn sweep_one(&self, sweep: impl FnOnce(Address)) -> bool {
+        if self.is_complete() {
+            return false;
+        }
+        let chunk = {
+            let mut state = self.state.lock().unwrap();
+            match state.pending.pop() {
+                Some(chunk) => {
+                    state.sweeping.push(chunk);
+                    chunk
+                }
+                None => return false,
+            }
+        };
+        sweep(chunk);
+        self.finish(chunk);
+        true
+    }
+
+    /// Make sure the given chunk is swept before it is used. If the chunk is pending, sweep it with `sweep`.
+    /// If another thread is sweeping the chunk, wait for it.
+    pub fn ensure_swept(&self, chunk: Address, sweep: impl FnOnce(Address)) {
+        if self.is_complete() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if let Some(index) = state.pending.iter().position(|c| *c == chunk) {
+            state.pending.swap_remove(index);
+            state.sweeping.push(chunk);
+            drop(state);
+            sweep(chunk);
+            self.finish(chunk);
+        } else {
+            while state.sweeping.contains(&chunk) {
+                state = self.swept.wait(state).unwrap();
+            }
+        }
+    }
+
+    /// Sweep all the pending chunks with `sweep`, and wait for the chunks that are being swept by other threads.
+    pub fn complete(&self, mut sweep: impl FnMut(Address)) {
+        while self.sweep_one(&mut sweep) {}
+        let mut state = self.state.lock().unwrap();
+        while !state.sweeping.is_empty() {
+            state = self.swept.wait(state).unwrap();
+        }
+        debug_assert!(self.is_complete());
+    }
+
+    fn finish(&self, chunk: Address) {
+        let mut state = self.state.lock().unwrap();
+        let index = state.sweeping.iter().position(|c| *c == chunk).unwrap();
+        state.sweeping.swap_remove(index);
+        self.outstanding.fetch_sub(1, Ordering::SeqCst);
+        self.swept.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn chunk(i: usize) -> Address {
+        unsafe { Address::from_usize(i << 22) }
+    }
+
+    #[test]
+    fn sweep_each_chunk_once() {
+        let sweeper = LazySweeper::new();
+        assert!(sweeper.is_complete());
+        sweeper.add_chunks((1..=4).map(chunk));
+        assert!(!sweeper.is_complete());
+
+        let mut swept = vec![];
+        sweeper.ensure_swept(chunk(2), |c| swept.push(c));
+        // A swept chunk is not swept again.
+        sweeper.ensure_swept(chunk(2), |c| swept.push(c));
+        assert!(sweeper.sweep_one(|c| swept.push(c)));
+        sweeper.complete(|c| swept.push(c));
+        assert!(sweeper.is_complete());
+        assert!(!sweeper.sweep_one(|c| swept.push(c)));
+
+        swept.sort();
+        assert_eq!(swept, (1..=4).map(chunk).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn concurrent_sweep() {
+        let sweeper = Arc::new(LazySweeper::new());
+        sweeper.add_chunks((1..=64).map(chunk));
+        let swept = Arc::new(Mutex::new(vec![]));
+        let threads: Vec<_> =
+            (0..4)
+                .map(|_| {
+                    let sweeper = sweeper.clone();
+                    let swept = swept.clone();
+                    std::thread::spawn(move || {
+                        while sweeper.sweep_one(|c| swept.lock().unwrap().push(c)) {}
+                    })
+                })
+                .collect();
+        sweeper.complete(|c| swept.lock().unwrap().push(c));
+        for t in threads {
+            t.join().unwrap();
+        }
+        let mut swept = swept.lock().unwrap().clone();
+        swept.sort();
+        assert_eq!(swept, (1..=64).map(chunk).collect::<Vec<_>>());
+    }
+}
@@ -3,6 +3,55 @@ use crate::util::Address;
 use crate::vm::{Collection, VMBinding};
 use libc::{PROT_EXEC, PROT_NONE, PROT_READ, PROT_WRITE};
 use std::io::{Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The size of a transparent huge page (2MB on x86_64 and aarch64 with 4K pages).
+pub const LOG_BYTES_IN_HUGE_PAGE: usize = 21;
+pub const BYTES_IN_HUGE_PAGE: usize = 1 << LOG_BYTES_IN_HUGE_PAGE;
+
+/// Whether we advise the OS to back our mappings with transparent huge pages. This is set from the
+/// `transparent_hugepages` option in `gc_init()`.
+static TRANSPARENT_HUGEPAGES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_transparent_hugepages(enabled: bool) {
+    TRANSPARENT_HUGEPAGES.store(enabled, Ordering::Relaxed);
+}
+
+pub fn transparent_hugepages() -> bool {
+    TRANSPARENT_HUGEPAGES.load(Ordering::Relaxed)
+}
+
+/// The largest huge page aligned range inside the given range, or None if there is no such range.
+fn huge_page_range(start: Address, size: usize) -> Option<(Address, usize)> {
+    let aligned_start = start.align_up(BYTES_IN_HUGE_PAGE);
+    let aligned_end = (start + size).align_down(BYTES_IN_HUGE_PAGE);
+    if aligned_start < aligned_end {
+        Some((aligned_start, aligned_end - aligned_start))
+    } else {
+        None
+    }
+}
+
+/// Advise the OS to use transparent huge pages for the huge page aligned part of a mapping, if the `transparent_hugepages`
+/// option is set. This is only a hint: a failure (e.g. the kernel is built without THP) is logged and ignored.
+fn advise_transparent_hugepages(start: Address, size: usize) {
+    if !transparent_hugepages() {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    if let Some((start, size)) = huge_page_range(start, size) {
+        let res = wrap_libc_call(
+            &|| unsafe { libc::madvise(start.to_mut_ptr(), size, libc::MADV_HUGEPAGE) },
+            0,
+        );
+        if let Err(e) = res {
+            warn!(
+                "Failed to madvise huge pages for {} of size {}: {}",
+                start, size, e
+            );
+        }
+    }
+}
 
 pub fn result_is_mapped(result: Result<()>) -> bool {
     match result {
@@ -16,6 +65,17 @@ pub fn zero(start: Address, len: usize) {
     wrap_libc_call(&|| unsafe { libc::memset(ptr, 0, len) }, ptr).unwrap()
 }
 
+/// The pattern that `poison()` fills memory with.
+pub const POISON_PATTERN: u32 = 0xdead_beef;
+
+/// Fill released memory with `POISON_PATTERN` (see the `poison_on_release` option), so a dangling reference
+/// into the memory reads a recognizable value. The start and the length need to be 4-byte aligned.
+pub fn poison(start: Address, len: usize) {
+    debug_assert!(start.is_aligned_to(4) && len % 4 == 0);
+    let words = unsafe { std::slice::from_raw_parts_mut(start.to_mut_ptr::<u32>(), len >> 2) };
+    words.fill(POISON_PATTERN);
+}
+
 /// Demand-zero mmap:
 /// This function mmaps the memory and guarantees to zero all mapped memory.
 /// This function WILL overwrite existing memory mapping. The user of this function
@@ -30,6 +90,9 @@ pub unsafe fn dzmmap(start: Address, size: usize) -> Result<()> {
     let prot = PROT_READ | PROT_WRITE | PROT_EXEC;
     let flags = libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED;
     let ret = mmap_fixed(start, size, prot, flags);
+    if ret.is_ok() {
+        advise_transparent_hugepages(start, size);
+    }
     // We do not need to explicitly zero for Linux (memory is guaranteed to be zeroed)
     #[cfg(not(target_os = "linux"))]
     if ret.is_ok() {
@@ -46,6 +109,9 @@ pub fn dzmmap_noreplace(start: Address, size: usize) -> Result<()> {
     let prot = PROT_READ | PROT_WRITE | PROT_EXEC;
     let flags = libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED_NOREPLACE;
     let ret = mmap_fixed(start, size, prot, flags);
+    if ret.is_ok() {
+        advise_transparent_hugepages(start, size);
+    }
     // We do not need to explicitly zero for Linux (memory is guaranteed to be zeroed)
     #[cfg(not(target_os = "linux"))]
     if ret.is_ok() {
@@ -62,7 +128,25 @@ pub fn mmap_noreserve(start: Address, size: usize) -> Result<()> {
     let prot = PROT_NONE;
     let flags =
         libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED_NOREPLACE | libc::MAP_NORESERVE;
-    mmap_fixed(start, size, prot, flags)
+    let ret = mmap_fixed(start, size, prot, flags);
+    if ret.is_ok() {
+        advise_transparent_hugepages(start, size);
+    }
+    ret
+}
+
+/// Map a file privately (copy-on-write) at the given address. Writes to the mapping do not change the file.
+/// This function will not overwrite existing memory mapping, and it will result Err if there is an existing mapping.
+pub fn mmap_file_noreplace(start: Address, size: usize, file: &std::fs::File) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let prot = PROT_READ | PROT_WRITE;
+    let flags = libc::MAP_PRIVATE | libc::MAP_FIXED_NOREPLACE;
+    let fd = file.as_raw_fd();
+    let ptr = start.to_mut_ptr();
+    wrap_libc_call(
+        &|| unsafe { libc::mmap(start.to_mut_ptr(), size, prot, flags, fd, 0) },
+        ptr,
+    )
 }
 
 pub fn mmap_fixed(
@@ -78,12 +162,89 @@ pub fn mmap_fixed(
     )
 }
 
+/// Decommit the memory: the physical memory is returned to the OS, and the range reads as zero after this.
+/// The range stays mapped and accessible.
+pub fn decommit(start: Address, size: usize) -> Result<()> {
+    // MADV_DONTNEED only guarantees that private anonymous memory reads as zero afterwards on Linux.
+    #[cfg(target_os = "linux")]
+    return wrap_libc_call(
+        &|| unsafe { libc::madvise(start.to_mut_ptr(), size, libc::MADV_DONTNEED) },
+        0,
+    );
+    #[cfg(not(target_os = "linux"))]
+    {
+        zero(start, size);
+        Ok(())
+    }
+}
+
 pub fn munmap(start: Address, size: usize) -> Result<()> {
     wrap_libc_call(&|| unsafe { libc::munmap(start.to_mut_ptr(), size) }, 0)
 }
 
+/// Check if nothing is mapped in the range by mapping it with `MAP_FIXED_NOREPLACE`. The probing mapping is
+/// removed before returning, so the range is not reserved for the caller.
+pub fn is_range_unmapped(start: Address, size: usize) -> bool {
+    let prot = PROT_NONE;
+    let flags =
+        libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED_NOREPLACE | libc::MAP_NORESERVE;
+    let ret = unsafe { libc::mmap(start.to_mut_ptr(), size, prot, flags, -1, 0) };
+    if ret == libc::MAP_FAILED {
+        return false;
+    }
+    // Kernels before 4.17 do not know MAP_FIXED_NOREPLACE, and treat the address as a hint. They may map somewhere else.
+    unsafe { libc::munmap(ret, size) };
+    ret == start.to_mut_ptr()
+}
+
+/// The entries in `/proc/self/maps` that overlap with any of the ranges. This is used to report what occupies the
+/// address range when MMTk fails to map it, e.g. ASan or another runtime in the same process. This is always empty
+/// on other OSes than Linux.
+pub fn get_overlapping_mappings(ranges: &[(Address, usize)]) -> Vec<String> {
+    if !cfg!(target_os = "linux") {
+        return vec![];
+    }
+    match std::fs::read_to_string("/proc/self/maps") {
+        Ok(maps) => overlapping_mappings_in(&maps, ranges),
+        Err(e) => {
+            warn!("Failed to read /proc/self/maps: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Find the lines in the format of `/proc/self/maps` (e.g. `7f0000000000-7f0000021000 rw-p ...`) that overlap with any of the ranges.
+fn overlapping_mappings_in(maps: &str, ranges: &[(Address, usize)]) -> Vec<String> {
+    let parse_range = |line: &str| -> Option<(usize, usize)> {
+        let mut bounds = line.split_whitespace().next()?.splitn(2, '-');
+        let start = usize::from_str_radix(bounds.next()?, 16).ok()?;
+        let end = usize::from_str_radix(bounds.next()?, 16).ok()?;
+        Some((start, end))
+    };
+    maps.lines()
+        .filter(|line| {
+            parse_range(line).map_or(false, |(start, end)| {
+                ranges
+                    .iter()
+                    .any(|&(s, size)| start < (s + size).as_usize() && s.as_usize() < end)
+            })
+        })
+        .map(|line| line.to_string())
+        .collect()
+}
+
 /// Properly handle errors from a mmap Result, including invoking the binding code for an OOM error.
 pub fn handle_mmap_error<VM: VMBinding>(error: Error, tls: VMThread) -> ! {
+    handle_mmap_error_in_ranges::<VM>(error, tls, &[])
+}
+
+/// The same as `handle_mmap_error()`, but `ranges` are the address ranges that we tried to map. If they are
+/// already mapped by someone else, the conflicting mappings are included in the panic message.
+pub fn handle_mmap_error_in_ranges<VM: VMBinding>(
+    error: Error,
+    tls: VMThread,
+    ranges: &[(Address, usize)],
+) -> ! {
     use std::io::ErrorKind;
 
     match error.kind() {
@@ -104,7 +265,15 @@ pub fn handle_mmap_error<VM: VMBinding>(error: Error, tls: VMThread) -> ! {
                 }
             }
         }
-        ErrorKind::AlreadyExists => panic!("Failed to mmap, the address is already mapped. Should MMTk quanrantine the address range first?"),
+        ErrorKind::AlreadyExists if ranges.is_empty() => panic!("Failed to mmap, the address is already mapped. Should MMTk quanrantine the address range first?"),
+        ErrorKind::AlreadyExists => {
+            let conflicts = get_overlapping_mappings(ranges);
+            panic!(
+                "Failed to mmap, the address is already mapped. Should MMTk quanrantine the address range first?\nConflicting mappings in {:?}:\n{}",
+                ranges,
+                conflicts.join("\n")
+            )
+        }
         _ => {}
     }
     panic!("Unexpected mmap failure: {:?}", error)
@@ -162,6 +331,16 @@ mod tests {
     // In the tests, we will mmap this address. This address should not be in our heap (in case we mess up with other tests)
     const START: Address = MEMORY_TEST_REGION.start;
 
+    #[test]
+    fn test_poison() {
+        let mut words = vec![0u32; 8];
+        let start = Address::from_mut_ptr(words.as_mut_ptr());
+        poison(start + 4usize, 24);
+        assert_eq!(words[0], 0);
+        assert!(words[1..7].iter().all(|w| *w == POISON_PATTERN));
+        assert_eq!(words[7], 0);
+    }
+
     #[test]
     fn test_mmap() {
         serial_test(|| {
@@ -216,6 +395,24 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_huge_page_range() {
+        let chunk = START.align_up(BYTES_IN_HUGE_PAGE);
+        assert_eq!(
+            huge_page_range(chunk, BYTES_IN_HUGE_PAGE * 2),
+            Some((chunk, BYTES_IN_HUGE_PAGE * 2))
+        );
+        assert_eq!(
+            huge_page_range(chunk - BYTES_IN_PAGE, BYTES_IN_HUGE_PAGE * 2),
+            Some((chunk, BYTES_IN_HUGE_PAGE))
+        );
+        assert_eq!(huge_page_range(chunk, BYTES_IN_PAGE), None);
+        assert_eq!(
+            huge_page_range(chunk + BYTES_IN_PAGE, BYTES_IN_HUGE_PAGE),
+            None
+        );
+    }
+
     #[test]
     fn test_mmap_noreserve() {
         serial_test(|| {
@@ -305,4 +502,38 @@ mod tests {
             )
         })
     }
+
+    #[test]
+    fn test_is_range_unmapped() {
+        serial_test(|| {
+            with_cleanup(
+                || {
+                    assert!(is_range_unmapped(START, BYTES_IN_PAGE));
+                    // Probing does not leave a mapping behind.
+                    assert!(dzmmap_noreplace(START, BYTES_IN_PAGE).is_ok());
+                    assert!(!is_range_unmapped(START, BYTES_IN_PAGE));
+                },
+                || {
+                    assert!(munmap(START, BYTES_IN_PAGE).is_ok());
+                },
+            )
+        })
+    }
+
+    #[test]
+    fn test_overlapping_mappings_in() {
+        let maps = "00400000-00452000 r-xp 00000000 08:02 173521 /usr/bin/dbus-daemon\n\
+                    7f0000000000-7f0000021000 rw-p 00000000 00:00 0 [heap]\n";
+        let range = |start: usize, size: usize| (unsafe { Address::from_usize(start) }, size);
+        assert_eq!(
+            overlapping_mappings_in(maps, &[range(0x7f0000020000, 0x1000)]),
+            vec!["7f0000000000-7f0000021000 rw-p 00000000 00:00 0 [heap]".to_string()]
+        );
+        assert_eq!(
+            overlapping_mappings_in(maps, &[range(0x0, 0x401000), range(0x7f0000021000, 0x1000)])
+                .len(),
+            1
+        );
+        assert!(overlapping_mappings_in(maps, &[range(0x452000, 0x1000)]).is_empty());
+    }
 }
@@ -0,0 +1,228 @@
+//! Accounting of the memory mapped for side metadata. The estimate from `SideMetadataContext::calculate_reserved_pages()`
+//! is used for GC triggering, while the numbers here are what MMTk actually mapped for each side metadata spec.
+//! Side metadata is shared by all MMTk instances, so the numbers are for the whole process.
+
+use super::SideMetadataSpec;
+use crate::util::constants::{BYTES_IN_PAGE, LOG_BYTES_IN_PAGE};
+use crate::util::conversions::pages_to_bytes;
+use crate::util::heap::layout::vm_layout_constants::MMAP_CHUNK_BYTES;
+use crate::util::heap::layout::Mmapper;
+use crate::util::Address;
+use crate::MMAPPER;
+use std::collections::HashMap;
+use std::io::Result;
+use std::sync::Mutex;
+
+/// The memory mapped for a side metadata spec.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SideMetadataStats {
+    /// The name of the spec.
+    pub name: &'static str,
+    /// Is the spec global metadata?
+    pub is_global: bool,
+    /// The bytes of the metadata pages mapped for the spec. MMTk maps side metadata in mmap chunks (`MMAP_CHUNK_BYTES`),
+    /// but only the pages for the data that MMTk has used are counted. The pages are only backed by physical memory when
+    /// they are touched. Pages are no longer counted once they are released (see `SideMetadataContext::release_metadata_space()`).
+    pub committed_bytes: usize,
+}
+
+const PAGES_IN_MMAP_CHUNK: usize = MMAP_CHUNK_BYTES >> LOG_BYTES_IN_PAGE;
+
+/// The memory that is accounted to the side metadata specs.
+#[derive(Default)]
+struct Accounting {
+    stats: Vec<SideMetadataStats>,
+    /// The accounted pages of contiguous metadata in each mmap chunk. The entry for a page is 0 if it is not accounted,
+    /// or 1 + the index of the spec in `stats` that it is accounted to.
+    pages: HashMap<Address, Box<[u16]>>,
+    /// The local metadata of each chunk in 32 bits, keyed by its start address, and the bytes accounted to each spec.
+    #[cfg(target_pointer_width = "32")]
+    per_chunk: HashMap<Address, Vec<(usize, usize)>>,
+}
+
+impl Accounting {
+    fn stats_index(&mut self, spec: &SideMetadataSpec) -> usize {
+        match self
+            .stats
+            .iter()
+            .position(|s| s.name == spec.name && s.is_global == spec.is_global)
+        {
+            Some(index) => index,
+            None => {
+                self.stats.push(SideMetadataStats {
+                    name: spec.name,
+                    is_global: spec.is_global,
+                    committed_bytes: 0,
+                });
+                assert!(self.stats.len() < u16::MAX as usize);
+                self.stats.len() - 1
+            }
+        }
+    }
+
+    /// Account the pages that overlap with `[start, end)` to the spec at `index`, if they are not accounted yet.
+    fn account_pages(&mut self, index: usize, start: Address, end: Address) {
+        let stats = &mut self.stats;
+        let mut page = start.align_down(BYTES_IN_PAGE);
+        while page < end {
+            let chunk = page.align_down(MMAP_CHUNK_BYTES);
+            let pages = self
+                .pages
+                .entry(chunk)
+                .or_insert_with(|| vec![0; PAGES_IN_MMAP_CHUNK].into_boxed_slice());
+            let entry = &mut pages[(page - chunk) >> LOG_BYTES_IN_PAGE];
+            if *entry == 0 {
+                *entry = index as u16 + 1;
+                stats[index].committed_bytes += BYTES_IN_PAGE;
+            }
+            page += BYTES_IN_PAGE;
+        }
+    }
+
+    /// Stop accounting the pages that are entirely in `[start, end)`, as they are released.
+    fn unaccount_pages(&mut self, start: Address, end: Address) {
+        let stats = &mut self.stats;
+        let mut page = start.align_up(BYTES_IN_PAGE);
+        while page < end && end - page >= BYTES_IN_PAGE {
+            let chunk = page.align_down(MMAP_CHUNK_BYTES);
+            if let Some(pages) = self.pages.get_mut(&chunk) {
+                let entry = &mut pages[(page - chunk) >> LOG_BYTES_IN_PAGE];
+                if *entry != 0 {
+                    stats[*entry as usize - 1].committed_bytes -= BYTES_IN_PAGE;
+                    *entry = 0;
+                }
+            }
+            page += BYTES_IN_PAGE;
+        }
+    }
+}
+
+lazy_static! {
+    static ref ACCOUNTING: Mutex<Accounting> = Mutex::new(Accounting::default());
+}
+
+/// The memory mapped for each side metadata spec, in the order that they are first mapped.
+pub fn side_metadata_stats() -> Vec<SideMetadataStats> {
+    ACCOUNTING.lock().unwrap().stats.clone()
+}
+
+/// The total memory mapped for side metadata.
+pub fn side_metadata_committed_bytes() -> usize {
+    ACCOUNTING
+        .lock()
+        .unwrap()
+        .stats
+        .iter()
+        .map(|s| s.committed_bytes)
+        .sum()
+}
+
+/// Map the metadata memory for `spec` from `start`, and account the pages that are not accounted yet to the spec.
+/// Mapping is done with the accounting lock held, so the same page is only counted once.
+pub(super) fn ensure_mapped_and_account(
+    spec: &SideMetadataSpec,
+    start: Address,
+    pages: usize,
+) -> Result<()> {
+    let mut accounting = ACCOUNTING.lock().unwrap();
+    MMAPPER.ensure_mapped(start, pages)?;
+    let index = accounting.stats_index(spec);
+    accounting.account_pages(index, start, start + pages_to_bytes(pages));
+    Ok(())
+}
+
+/// The memory of contiguous metadata in `[start, end)` is released (decommitted or unmapped). Stop accounting the pages
+/// that are entirely in the range. They are accounted again when they are mapped for a spec.
+pub(super) fn release_and_account(start: Address, end: Address) {
+    ACCOUNTING.lock().unwrap().unaccount_pages(start, end);
+}
+
+/// Map the local metadata for the chunks in `[start, start + size)` with `map`, and account the metadata bytes
+/// (not the pages) to each local spec for the chunks whose metadata is not accounted yet. This is used for
+/// the chunked local metadata in 32 bits.
+#[cfg(target_pointer_width = "32")]
+pub(super) fn map_per_chunk_and_account(
+    specs: &[SideMetadataSpec],
+    start: Address,
+    size: usize,
+    map: impl FnOnce() -> Result<usize>,
+) -> Result<usize> {
+    use super::{address_to_meta_chunk_addr, metadata_bytes_per_chunk};
+    use crate::util::heap::layout::vm_layout_constants::BYTES_IN_CHUNK;
+
+    let mut accounting = ACCOUNTING.lock().unwrap();
+    let res = map()?;
+    let bytes: Vec<(usize, usize)> = specs
+        .iter()
+        .map(|spec| {
+            (
+                accounting.stats_index(spec),
+                metadata_bytes_per_chunk(spec.log_min_obj_size, spec.log_num_of_bits),
+            )
+        })
+        .collect();
+    let mut chunk = start.align_down(BYTES_IN_CHUNK);
+    while chunk < start + size {
+        let meta_chunk = address_to_meta_chunk_addr(chunk);
+        if !accounting.per_chunk.contains_key(&meta_chunk) {
+            for &(index, b) in bytes.iter() {
+                accounting.stats[index].committed_bytes += b;
+            }
+            accounting.per_chunk.insert(meta_chunk, bytes.clone());
+        }
+        chunk += BYTES_IN_CHUNK;
+    }
+    Ok(res)
+}
+
+/// The local metadata of a chunk at `meta_chunk` (see `address_to_meta_chunk_addr()`) is released or unmapped. Stop accounting it.
+#[cfg(target_pointer_width = "32")]
+pub(super) fn release_per_chunk_and_account(meta_chunk: Address) {
+    let mut accounting = ACCOUNTING.lock().unwrap();
+    if let Some(bytes) = accounting.per_chunk.remove(&meta_chunk) {
+        for (index, b) in bytes {
+            accounting.stats[index].committed_bytes -= b;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::metadata::side_metadata::GLOBAL_SIDE_METADATA_BASE_OFFSET;
+
+    const SPEC: SideMetadataSpec = SideMetadataSpec {
+        name: "AccountingTest",
+        is_global: true,
+        offset: GLOBAL_SIDE_METADATA_BASE_OFFSET,
+        log_num_of_bits: 0,
+        log_min_obj_size: 3,
+    };
+
+    #[test]
+    fn test_account_pages() {
+        let chunk = unsafe { Address::from_usize(MMAP_CHUNK_BYTES) };
+        let mut accounting = Accounting::default();
+        let index = accounting.stats_index(&SPEC);
+        assert_eq!(accounting.stats_index(&SPEC), index);
+
+        // A range is rounded up to pages, and each page is only counted once.
+        accounting.account_pages(index, chunk, chunk + 1usize);
+        accounting.account_pages(index, chunk, chunk + 2 * BYTES_IN_PAGE);
+        assert_eq!(
+            accounting.stats,
+            vec![SideMetadataStats {
+                name: "AccountingTest",
+                is_global: true,
+                committed_bytes: 2 * BYTES_IN_PAGE,
+            }]
+        );
+
+        // Only the whole pages in a released range are no longer counted, and releasing them again does nothing.
+        accounting.unaccount_pages(chunk + 1usize, chunk + 2 * BYTES_IN_PAGE);
+        accounting.unaccount_pages(chunk + 1usize, chunk + 2 * BYTES_IN_PAGE);
+        assert_eq!(accounting.stats[index].committed_bytes, BYTES_IN_PAGE);
+        accounting.unaccount_pages(chunk, chunk + BYTES_IN_PAGE);
+        assert_eq!(accounting.stats[index].committed_bytes, 0);
+    }
+}
@@ -54,6 +54,12 @@ pub const LOG_MAX_GLOBAL_SIDE_METADATA_SIZE: usize =
 pub(crate) const LOCAL_SIDE_METADATA_BASE_ADDRESS: Address =
     GLOBAL_SIDE_METADATA_BASE_ADDRESS.add(1usize << LOG_MAX_GLOBAL_SIDE_METADATA_SIZE);
 
+/// The end of the address range reserved for the side metadata in the 64-bit layout. The local side metadata is laid
+/// out after the global side metadata, and we assume it takes no more than `LOG_LOCAL_SIDE_METADATA_WORST_CASE_RATIO`.
+#[cfg(target_pointer_width = "64")]
+pub(crate) const SIDE_METADATA_END_ADDRESS: Address = LOCAL_SIDE_METADATA_BASE_ADDRESS
+    .add(1usize << (LOG_ADDRESS_SPACE - LOG_LOCAL_SIDE_METADATA_WORST_CASE_RATIO));
+
 // Local side metadata start offset
 
 #[cfg(target_pointer_width = "32")]
@@ -107,14 +113,22 @@ pub const GLOBAL_SIDE_METADATA_VM_BASE_OFFSET: SideMetadataOffset =
 //
 //  1 - MarkSweep Active Page byte:
 //      -
-//  2 - Immix line mark byte:
+//  2 - MarkSweep Offset Malloc bit:
 //      - Offset after MS Active Page byte
-//  3 - Immix block defrag byte:
+//  3 - Immix line mark byte:
+//      - Offset after MS Offset Malloc bit
+//  4 - Immix block defrag byte:
 //      - Offset after Immix block defrag byte
-//  4 - Immix block mark byte:
+//  5 - Immix block mark byte:
 //      - Offset after Immix block mark byte
-//  5 - Immix chumk-map mark byte:
+//  6 - Immix chumk-map mark byte:
 //      - Offset after Immix chumk-map mark byte
+//  7 - Native MarkSweep block state byte:
+//      - Offset after MS Offset Malloc bit (no plan uses both Immix and Native MarkSweep, so they share a range)
+//  8 - Native MarkSweep block size class byte:
+//      - Offset after Native MarkSweep block state byte
+//  9 - Native MarkSweep block free list word:
+//      - Offset after Native MarkSweep block size class byte
 //
 // --------------------------------------------------
 
@@ -3,8 +3,10 @@ use super::*;
 use crate::util::alloc_bit::ALLOC_SIDE_METADATA_SPEC;
 use crate::util::constants::{BYTES_IN_PAGE, LOG_BITS_IN_BYTE};
 use crate::util::heap::layout::vm_layout_constants::BYTES_IN_CHUNK;
+use crate::util::heap::layout::Mmapper;
 use crate::util::memory;
 use crate::util::{constants, Address};
+use crate::MMAPPER;
 use std::fmt;
 use std::io::Result;
 use std::sync::atomic::{AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
@@ -17,6 +19,8 @@ use std::sync::atomic::{AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
 /// For performance reasons, objects of this struct should be constants.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SideMetadataSpec {
+    /// The name of the metadata, used in logging and in the memory usage reports (see `side_metadata_stats()`).
+    pub name: &'static str,
     pub is_global: bool,
     pub offset: SideMetadataOffset,
     /// Number of bits needed per region. E.g. 0 = 1 bit, 1 = 2 bit.
@@ -53,12 +57,13 @@ impl SideMetadataSpec {
 impl fmt::Debug for SideMetadataSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_fmt(format_args!(
-            "SideMetadataSpec {{ \
+            "SideMetadataSpec {} {{ \
             **is_global: {:?} \
             **offset: {} \
             **log_num_of_bits: 0x{:x} \
             **log_min_obj_size: 0x{:x} \
             }}",
+            self.name,
             self.is_global,
             unsafe {
                 if self.is_absolute_offset() {
@@ -139,6 +144,7 @@ impl std::hash::Hash for SideMetadataOffset {
 
 /// This struct stores all the side metadata specs for a policy. Generally a policy needs to know its own
 /// side metadata spec as well as the plan's specs.
+#[derive(Clone)]
 pub struct SideMetadataContext {
     // For plans
     pub global: Vec<SideMetadataSpec>,
@@ -224,6 +230,44 @@ impl SideMetadataContext {
         self.map_metadata_internal(start, size, true)
     }
 
+    /// The page aligned metadata address ranges (start and size) for the specified data address range.
+    /// This is used to touch the metadata pages after they are mapped with `try_map_metadata_space`.
+    pub fn metadata_address_ranges(&self, start: Address, size: usize) -> Vec<(Address, usize)> {
+        let contiguous_range = |spec: &SideMetadataSpec| {
+            let meta_start = address_to_meta_address(spec, start).align_down(BYTES_IN_PAGE);
+            let meta_end = address_to_meta_address(spec, start + size).align_up(BYTES_IN_PAGE);
+            (meta_start, meta_end - meta_start)
+        };
+        let mut ranges: Vec<(Address, usize)> = self.global.iter().map(contiguous_range).collect();
+
+        #[cfg(target_pointer_width = "64")]
+        ranges.extend(self.local.iter().map(contiguous_range));
+
+        // Local side metadata is allocated per chunk in 32 bits (see `map_metadata_internal`).
+        #[cfg(target_pointer_width = "32")]
+        {
+            let lsize: usize = self
+                .local
+                .iter()
+                .map(|spec| metadata_bytes_per_chunk(spec.log_min_obj_size, spec.log_num_of_bits))
+                .sum();
+            if lsize > 0 {
+                let mut chunk = start.align_down(BYTES_IN_CHUNK);
+                while chunk < start + size {
+                    let meta_start = address_to_meta_chunk_addr(chunk);
+                    ranges.push((
+                        meta_start,
+                        crate::util::conversions::raw_align_up(lsize, BYTES_IN_PAGE),
+                    ));
+                    chunk += BYTES_IN_CHUNK;
+                }
+            }
+        }
+
+        ranges.retain(|(_, size)| *size > 0);
+        ranges
+    }
+
     /// The internal function to mmap metadata
     ///
     /// # Arguments
@@ -273,7 +317,14 @@ impl SideMetadataContext {
                 lsize,
                 max
             );
-            match try_map_per_chunk_metadata_space(start, size, lsize, no_reserve) {
+            let res = if no_reserve {
+                try_map_per_chunk_metadata_space(start, size, lsize, no_reserve)
+            } else {
+                super::accounting::map_per_chunk_and_account(&self.local, start, size, || {
+                    try_map_per_chunk_metadata_space(start, size, lsize, no_reserve)
+                })
+            };
+            match res {
                 Ok(_) => {}
                 Err(e) => return Result::Err(e),
             }
@@ -282,6 +333,58 @@ impl SideMetadataContext {
         Ok(())
     }
 
+    /// Release the metadata for a data address range that is no longer used, e.g. when its pages are released or its chunks
+    /// are returned to the global pool. The metadata stays mapped, as the mmapper never unmaps memory, but the whole pages in the
+    /// metadata are decommitted (their physical memory is freed and they read as zero afterwards), and the rest is zeroed.
+    /// Only the metadata bytes whose data is entirely in the range are released, so the metadata of the data next to the range
+    /// (e.g. the per-chunk metadata for the rest of a chunk) is kept. Nothing is done for metadata that has not been mapped.
+    pub fn release_metadata_space(&self, start: Address, size: usize) {
+        trace!("release_metadata_space({}, 0x{:x})", start, size);
+        debug_assert!(start.is_aligned_to(BYTES_IN_PAGE));
+        debug_assert!(size % BYTES_IN_PAGE == 0);
+        let end = start + size;
+
+        for spec in self.global.iter() {
+            if let Some((meta_start, meta_end)) = metadata_range_within(spec, start, end) {
+                release_metadata_range(meta_start, meta_end);
+            }
+        }
+
+        #[cfg(target_pointer_width = "64")]
+        for spec in self.local.iter() {
+            if let Some((meta_start, meta_end)) = metadata_range_within(spec, start, end) {
+                release_metadata_range(meta_start, meta_end);
+            }
+        }
+
+        // The local metadata of a chunk is stored together for all the local specs in 32 bits. The metadata of different
+        // chunks is not contiguous, so we release it chunk by chunk.
+        #[cfg(target_pointer_width = "32")]
+        {
+            use super::constants::LOCAL_SIDE_METADATA_PER_CHUNK;
+            let mut chunk = start.align_down(BYTES_IN_CHUNK);
+            while chunk < end {
+                let chunk_end = chunk + BYTES_IN_CHUNK;
+                let meta_chunk = address_to_meta_chunk_addr(chunk);
+                if start <= chunk && chunk_end <= end {
+                    // Another space that takes the chunk may use other local specs, so we release all the local metadata
+                    // reserved for the chunk, not only the metadata of our specs.
+                    release_metadata_range(meta_chunk, meta_chunk + LOCAL_SIDE_METADATA_PER_CHUNK);
+                    super::accounting::release_per_chunk_and_account(meta_chunk);
+                } else {
+                    let (from, to) = (start.max(chunk), end.min(chunk_end));
+                    for spec in self.local.iter() {
+                        if let Some((meta_start, meta_end)) = metadata_range_within(spec, from, to)
+                        {
+                            release_metadata_range(meta_start, meta_end);
+                        }
+                    }
+                }
+                chunk = chunk_end;
+            }
+        }
+    }
+
     /// Unmap the corresponding metadata space or panic.
     ///
     /// Note-1: This function is only used for test and debug right now.
@@ -309,6 +412,68 @@ impl SideMetadataContext {
                 ensure_munmap_chunked_metadata_space(start, size, spec);
             }
         }
+
+        #[cfg(target_pointer_width = "32")]
+        {
+            let mut chunk = start.align_down(BYTES_IN_CHUNK);
+            while chunk < start + size {
+                super::accounting::release_per_chunk_and_account(address_to_meta_chunk_addr(chunk));
+                chunk += BYTES_IN_CHUNK;
+            }
+        }
+    }
+}
+
+/// The metadata range of `spec` for the data in `[start, end)`. Only the metadata bytes whose data is entirely in the range are
+/// included. Returns `None` if there is no such byte. The data range must not cross a chunk boundary for chunked local metadata in 32 bits.
+fn metadata_range_within(
+    spec: &SideMetadataSpec,
+    start: Address,
+    end: Address,
+) -> Option<(Address, Address)> {
+    let log_bits_in_byte = LOG_BITS_IN_BYTE as usize;
+    // The data bytes covered by a metadata byte (or by the metadata bytes of a region if it has more than 8 bits),
+    // and the metadata bytes for them.
+    let (log_granule, meta_bytes) = if spec.log_num_of_bits <= log_bits_in_byte {
+        (
+            spec.log_min_obj_size + log_bits_in_byte - spec.log_num_of_bits,
+            1,
+        )
+    } else {
+        (
+            spec.log_min_obj_size,
+            1 << (spec.log_num_of_bits - log_bits_in_byte),
+        )
+    };
+    let granule = 1usize << log_granule;
+    let start = start.align_up(granule);
+    let end = end.align_down(granule);
+    if start >= end {
+        return None;
+    }
+    Some((
+        address_to_meta_address(spec, start),
+        address_to_meta_address(spec, end - granule) + meta_bytes,
+    ))
+}
+
+/// Release the metadata memory in `[meta_start, meta_end)`: decommit the whole pages in the range, and zero the rest.
+fn release_metadata_range(meta_start: Address, meta_end: Address) {
+    if meta_start >= meta_end || !MMAPPER.is_mapped_address(meta_start) {
+        return;
+    }
+    let pages_start = meta_start.align_up(BYTES_IN_PAGE);
+    let pages_end = meta_end.align_down(BYTES_IN_PAGE);
+    if pages_start < pages_end {
+        if let Err(e) = memory::decommit(pages_start, pages_end - pages_start) {
+            warn!("Failed to decommit side metadata at {}: {}", pages_start, e);
+            memory::zero(pages_start, pages_end - pages_start);
+        }
+        memory::zero(meta_start, pages_start - meta_start);
+        memory::zero(pages_end, meta_end - pages_end);
+        super::accounting::release_and_account(pages_start, pages_end);
+    } else {
+        memory::zero(meta_start, meta_end - meta_start);
     }
 }
 
@@ -860,6 +1025,7 @@ pub fn bzero_metadata(metadata_spec: &SideMetadataSpec, start: Address, size: us
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::heap::layout::vm_layout_constants::LOG_BYTES_IN_CHUNK;
     use crate::util::metadata::side_metadata::SideMetadataContext;
 
     // offset is not used in these tests.
@@ -869,6 +1035,7 @@ mod tests {
     fn calculate_reserved_pages_one_spec() {
         // 1 bit per 8 bytes - 1:64
         let spec = SideMetadataSpec {
+            name: "spec",
             is_global: true,
             offset: ZERO_OFFSET,
             log_num_of_bits: 0,
@@ -885,10 +1052,68 @@ mod tests {
         assert_eq!(side_metadata.calculate_reserved_pages(1024), 16);
     }
 
+    #[test]
+    fn metadata_range_within_whole_bytes() {
+        let addr = |a: usize| unsafe { Address::from_usize(a) };
+        // 1 bit per 8 bytes: a metadata byte covers 64 bytes of data.
+        let bit_spec = SideMetadataSpec {
+            name: "bit_spec",
+            is_global: true,
+            offset: SideMetadataOffset::addr(Address::ZERO),
+            log_num_of_bits: 0,
+            log_min_obj_size: 3,
+        };
+        assert_eq!(
+            metadata_range_within(&bit_spec, addr(0x1000), addr(0x2000)),
+            Some((addr(0x40), addr(0x80)))
+        );
+        // The metadata bytes shared with the data outside the range are not included.
+        assert_eq!(
+            metadata_range_within(&bit_spec, addr(0x1008), addr(0x10f8)),
+            Some((addr(0x41), addr(0x43)))
+        );
+        assert_eq!(
+            metadata_range_within(&bit_spec, addr(0x1008), addr(0x1040)),
+            None
+        );
+
+        // 1 byte per 4MB chunk: releasing a part of a chunk keeps its byte.
+        let chunk_spec = SideMetadataSpec {
+            name: "chunk_spec",
+            is_global: true,
+            offset: SideMetadataOffset::addr(Address::ZERO),
+            log_num_of_bits: 3,
+            log_min_obj_size: LOG_BYTES_IN_CHUNK,
+        };
+        let chunk = addr(BYTES_IN_CHUNK * 3);
+        assert_eq!(
+            metadata_range_within(&chunk_spec, chunk + 0x8000usize, chunk + BYTES_IN_CHUNK),
+            None
+        );
+        assert_eq!(
+            metadata_range_within(&chunk_spec, chunk, chunk + BYTES_IN_CHUNK),
+            Some((addr(3), addr(4)))
+        );
+
+        // 2 bytes per 512 bytes.
+        let word_spec = SideMetadataSpec {
+            name: "word_spec",
+            is_global: true,
+            offset: SideMetadataOffset::addr(Address::ZERO),
+            log_num_of_bits: 4,
+            log_min_obj_size: 9,
+        };
+        assert_eq!(
+            metadata_range_within(&word_spec, addr(0x1000), addr(0x1400)),
+            Some((addr(0x10), addr(0x14)))
+        );
+    }
+
     #[test]
     fn calculate_reserved_pages_multi_specs() {
         // 1 bit per 8 bytes - 1:64
         let gspec = SideMetadataSpec {
+            name: "gspec",
             is_global: true,
             offset: ZERO_OFFSET,
             log_num_of_bits: 0,
@@ -896,6 +1121,7 @@ mod tests {
         };
         // 2 bits per page - 2 / (4k * 8) = 1:16k
         let lspec = SideMetadataSpec {
+            name: "lspec",
             is_global: false,
             offset: ZERO_OFFSET,
             log_num_of_bits: 1,
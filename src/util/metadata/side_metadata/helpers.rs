@@ -10,6 +10,23 @@ use crate::util::{
 };
 use crate::MMAPPER;
 use std::io::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The distance that the side metadata has been moved from the addresses in its specs. This is 0 unless MMTk relocates
+/// the side metadata together with the heap because the default ranges are occupied (see the `relocate_heap_on_conflict` option).
+static SIDE_METADATA_RELOCATION: AtomicUsize = AtomicUsize::new(0);
+
+/// The distance that the side metadata has been moved from the addresses in its specs. Bindings that compute side metadata
+/// addresses from the constants (e.g. `ALLOC_SIDE_METADATA_ADDR` or the offset of the log bit spec) must add this to them.
+/// It is fixed once the first MMTk instance is created.
+pub fn side_metadata_relocation() -> usize {
+    SIDE_METADATA_RELOCATION.load(Ordering::Relaxed)
+}
+
+/// Move the side metadata by `delta` bytes. This can only be called before any side metadata is mapped.
+pub(crate) fn set_side_metadata_relocation(delta: usize) {
+    SIDE_METADATA_RELOCATION.store(delta, Ordering::SeqCst);
+}
 
 /// Performs address translation in contiguous metadata spaces (e.g. global and policy-specific in 64-bits, and global in 32-bits)
 #[inline(always)]
@@ -21,11 +38,12 @@ pub(crate) fn address_to_contiguous_meta_address(
     let log_min_obj_size = metadata_spec.log_min_obj_size as usize;
 
     let rshift = (LOG_BITS_IN_BYTE as i32) - log_bits_num;
+    let base = metadata_spec.get_absolute_offset() + side_metadata_relocation();
 
     if rshift >= 0 {
-        metadata_spec.get_absolute_offset() + ((data_addr >> log_min_obj_size) >> rshift)
+        base + ((data_addr >> log_min_obj_size) >> rshift)
     } else {
-        metadata_spec.get_absolute_offset() + ((data_addr >> log_min_obj_size) << (-rshift))
+        base + ((data_addr >> log_min_obj_size) << (-rshift))
     }
 }
 
@@ -35,7 +53,8 @@ pub(super) fn ensure_munmap_metadata(start: Address, size: usize) {
     use crate::util::memory;
     trace!("ensure_munmap_metadata({}, 0x{:x})", start, size);
 
-    assert!(memory::munmap(start, size).is_ok())
+    assert!(memory::munmap(start, size).is_ok());
+    super::accounting::release_and_account(start, start + size);
 }
 
 /// Unmaps a metadata space (`spec`) for the specified data address range (`start` and `size`)
@@ -76,7 +95,11 @@ pub(crate) fn try_mmap_contiguous_metadata_space(
         address_to_meta_address(spec, start + size).align_up(BYTES_IN_PAGE) - mmap_start;
     if mmap_size > 0 {
         if !no_reserve {
-            MMAPPER.ensure_mapped(mmap_start, mmap_size >> LOG_BYTES_IN_PAGE)
+            super::accounting::ensure_mapped_and_account(
+                spec,
+                mmap_start,
+                mmap_size >> LOG_BYTES_IN_PAGE,
+            )
         } else {
             MMAPPER.quarantine_address_range(mmap_start, mmap_size >> LOG_BYTES_IN_PAGE)
         }
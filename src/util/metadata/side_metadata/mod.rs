@@ -1,3 +1,4 @@
+mod accounting;
 mod constants;
 mod helpers;
 #[cfg(target_pointer_width = "32")]
@@ -7,6 +8,7 @@ mod global;
 mod sanity;
 mod side_metadata_tests;
 
+pub use accounting::{side_metadata_committed_bytes, side_metadata_stats, SideMetadataStats};
 pub use constants::*;
 pub use global::*;
 pub use helpers::*;
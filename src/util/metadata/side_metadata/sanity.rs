@@ -555,12 +555,14 @@ mod tests {
     #[test]
     fn test_side_metadata_sanity_verify_global_specs_total_size() {
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: true,
             offset: SideMetadataOffset::addr(Address::ZERO),
             log_num_of_bits: 0,
             log_min_obj_size: 0,
         };
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: true,
             offset: SideMetadataOffset::layout_after(&spec_1),
             log_num_of_bits: 0,
@@ -574,6 +576,7 @@ mod tests {
         assert!(verify_global_specs_total_size(&[spec_1, spec_2]).is_err());
 
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: true,
             offset: SideMetadataOffset::layout_after(&spec_1),
             log_num_of_bits: 3,
@@ -583,6 +586,7 @@ mod tests {
         assert!(verify_global_specs_total_size(&[spec_1, spec_2]).is_err());
 
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: true,
             offset: SideMetadataOffset::addr(Address::ZERO),
             log_num_of_bits: 1,
@@ -592,6 +596,7 @@ mod tests {
             log_min_obj_size: 2,
         };
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: true,
             offset: SideMetadataOffset::layout_after(&spec_1),
             log_num_of_bits: 3,
@@ -608,12 +613,14 @@ mod tests {
     #[test]
     fn test_side_metadata_sanity_verify_no_overlap_contiguous() {
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: true,
             offset: SideMetadataOffset::addr(Address::ZERO),
             log_num_of_bits: 0,
             log_min_obj_size: 0,
         };
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: true,
             offset: SideMetadataOffset::layout_after(&spec_1),
             log_num_of_bits: 0,
@@ -624,6 +631,7 @@ mod tests {
         assert!(verify_no_overlap_contiguous(&spec_1, &spec_2).is_ok());
 
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: true,
             offset: SideMetadataOffset::addr(unsafe { Address::from_usize(1) }),
             log_num_of_bits: 0,
@@ -633,12 +641,14 @@ mod tests {
         assert!(verify_no_overlap_contiguous(&spec_1, &spec_2).is_err());
 
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: true,
             offset: SideMetadataOffset::addr(Address::ZERO),
             log_num_of_bits: 0,
             log_min_obj_size: 0,
         };
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: true,
             // We specifically make up an invalid offset
             offset: SideMetadataOffset::addr(
@@ -655,12 +665,14 @@ mod tests {
     #[test]
     fn test_side_metadata_sanity_verify_no_overlap_chunked() {
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: false,
             offset: SideMetadataOffset::rel(0),
             log_num_of_bits: 0,
             log_min_obj_size: 0,
         };
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: false,
             offset: SideMetadataOffset::layout_after(&spec_1),
             log_num_of_bits: 0,
@@ -671,6 +683,7 @@ mod tests {
         assert!(verify_no_overlap_chunked(&spec_1, &spec_2).is_ok());
 
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: false,
             offset: SideMetadataOffset::rel(1),
             log_num_of_bits: 0,
@@ -680,12 +693,14 @@ mod tests {
         assert!(verify_no_overlap_chunked(&spec_1, &spec_2).is_err());
 
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: false,
             offset: SideMetadataOffset::rel(0),
             log_num_of_bits: 0,
             log_min_obj_size: 0,
         };
         let spec_2 = SideMetadataSpec {
+            name: "spec_2",
             is_global: false,
             // We make up an invalid offset
             offset: SideMetadataOffset::rel(
@@ -704,6 +719,7 @@ mod tests {
     #[test]
     fn test_side_metadata_sanity_verify_local_specs_size() {
         let spec_1 = SideMetadataSpec {
+            name: "spec_1",
             is_global: false,
             offset: SideMetadataOffset::rel(0),
             log_num_of_bits: 0,
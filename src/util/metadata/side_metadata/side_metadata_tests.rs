@@ -13,6 +13,7 @@ mod tests {
     #[test]
     fn test_side_metadata_address_to_meta_address() {
         let mut gspec = SideMetadataSpec {
+            name: "gspec",
             is_global: true,
             offset: SideMetadataOffset::addr(GLOBAL_SIDE_METADATA_BASE_ADDRESS),
             log_num_of_bits: 0,
@@ -20,6 +21,7 @@ mod tests {
         };
         #[cfg(target_pointer_width = "64")]
         let mut lspec = SideMetadataSpec {
+            name: "lspec",
             is_global: false,
             offset: SideMetadataOffset::addr(LOCAL_SIDE_METADATA_BASE_ADDRESS),
             log_num_of_bits: 0,
@@ -28,6 +30,7 @@ mod tests {
 
         #[cfg(target_pointer_width = "32")]
         let mut lspec = SideMetadataSpec {
+            name: "lspec",
             is_global: false,
             offset: SideMetadataOffset::rel(0),
             log_num_of_bits: 0,
@@ -125,6 +128,7 @@ mod tests {
     #[test]
     fn test_side_metadata_meta_byte_mask() {
         let mut spec = SideMetadataSpec {
+            name: "spec",
             is_global: true,
             offset: SideMetadataOffset::addr(GLOBAL_SIDE_METADATA_BASE_ADDRESS),
             log_num_of_bits: 0,
@@ -144,6 +148,7 @@ mod tests {
     #[test]
     fn test_side_metadata_meta_byte_lshift() {
         let mut spec = SideMetadataSpec {
+            name: "spec",
             is_global: true,
             offset: SideMetadataOffset::addr(GLOBAL_SIDE_METADATA_BASE_ADDRESS),
             log_num_of_bits: 0,
@@ -191,6 +196,7 @@ mod tests {
                     // We need to do this because of the static NO_METADATA
                     // sanity::reset();
                     let mut gspec = SideMetadataSpec {
+                        name: "gspec",
                         is_global: true,
                         offset: SideMetadataOffset::addr(GLOBAL_SIDE_METADATA_BASE_ADDRESS),
                         log_num_of_bits: 1,
@@ -198,6 +204,7 @@ mod tests {
                     };
                     #[cfg(target_pointer_width = "64")]
                     let mut lspec = SideMetadataSpec {
+                        name: "lspec",
                         is_global: false,
                         offset: SideMetadataOffset::addr(LOCAL_SIDE_METADATA_BASE_ADDRESS),
                         log_num_of_bits: 1,
@@ -205,6 +212,7 @@ mod tests {
                     };
                     #[cfg(target_pointer_width = "32")]
                     let mut lspec = SideMetadataSpec {
+                        name: "lspec",
                         is_global: false,
                         offset: SideMetadataOffset::rel(0),
                         log_num_of_bits: 1,
@@ -305,6 +313,7 @@ mod tests {
                     let data_addr = vm_layout_constants::HEAP_START;
 
                     let metadata_1_spec = SideMetadataSpec {
+                        name: "metadata_1_spec",
                         is_global: true,
                         offset: SideMetadataOffset::addr(GLOBAL_SIDE_METADATA_BASE_ADDRESS),
                         log_num_of_bits: 4,
@@ -312,6 +321,7 @@ mod tests {
                     };
 
                     let metadata_2_spec = SideMetadataSpec {
+                        name: "metadata_2_spec",
                         is_global: true,
                         offset: SideMetadataOffset::layout_after(&metadata_1_spec),
                         log_num_of_bits: 3,
@@ -377,6 +387,7 @@ mod tests {
                         + (vm_layout_constants::BYTES_IN_CHUNK << 1);
 
                     let metadata_1_spec = SideMetadataSpec {
+                        name: "metadata_1_spec",
                         is_global: true,
                         offset: SideMetadataOffset::addr(GLOBAL_SIDE_METADATA_BASE_ADDRESS),
                         log_num_of_bits: 1,
@@ -431,6 +442,7 @@ mod tests {
 
                     #[cfg(target_pointer_width = "64")]
                     let metadata_1_spec = SideMetadataSpec {
+                        name: "metadata_1_spec",
                         is_global: false,
                         offset: SideMetadataOffset::addr(LOCAL_SIDE_METADATA_BASE_ADDRESS),
                         log_num_of_bits: 4,
@@ -438,6 +450,7 @@ mod tests {
                     };
                     #[cfg(target_pointer_width = "64")]
                     let metadata_2_spec = SideMetadataSpec {
+                        name: "metadata_2_spec",
                         is_global: false,
                         offset: SideMetadataOffset::layout_after(&metadata_1_spec),
                         log_num_of_bits: 3,
@@ -446,6 +459,7 @@ mod tests {
 
                     #[cfg(target_pointer_width = "32")]
                     let metadata_1_spec = SideMetadataSpec {
+                        name: "metadata_1_spec",
                         is_global: false,
                         offset: SideMetadataOffset::rel(0),
                         log_num_of_bits: 4,
@@ -453,6 +467,7 @@ mod tests {
                     };
                     #[cfg(target_pointer_width = "32")]
                     let metadata_2_spec = SideMetadataSpec {
+                        name: "metadata_2_spec",
                         is_global: false,
                         offset: SideMetadataOffset::layout_after(&metadata_1_spec),
                         log_num_of_bits: 3,
@@ -507,4 +522,92 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_side_metadata_release_metadata_space() {
+        serial_test(|| {
+            with_cleanup(
+                || {
+                    let data_addr = vm_layout_constants::HEAP_START
+                        + (vm_layout_constants::BYTES_IN_CHUNK << 3);
+                    let chunk_size = vm_layout_constants::BYTES_IN_CHUNK;
+
+                    #[cfg(target_pointer_width = "64")]
+                    let metadata_1_spec = SideMetadataSpec {
+                        name: "release_metadata_spec",
+                        is_global: false,
+                        offset: SideMetadataOffset::addr(LOCAL_SIDE_METADATA_BASE_ADDRESS),
+                        log_num_of_bits: 4,
+                        log_min_obj_size: 9,
+                    };
+                    #[cfg(target_pointer_width = "32")]
+                    let metadata_1_spec = SideMetadataSpec {
+                        name: "release_metadata_spec",
+                        is_global: false,
+                        offset: SideMetadataOffset::rel(0),
+                        log_num_of_bits: 4,
+                        log_min_obj_size: 9,
+                    };
+
+                    let metadata = SideMetadataContext {
+                        global: vec![],
+                        local: vec![metadata_1_spec],
+                    };
+
+                    let mut metadata_sanity = SideMetadataSanity::new();
+                    metadata_sanity.verify_metadata_context("NoPolicy", &metadata);
+
+                    let committed = || {
+                        side_metadata_stats()
+                            .iter()
+                            .find(|s| s.name == "release_metadata_spec")
+                            .map_or(0, |s| s.committed_bytes)
+                    };
+
+                    assert!(metadata
+                        .try_map_metadata_space(data_addr, chunk_size)
+                        .is_ok());
+                    let mapped = committed();
+                    assert!(mapped > 0);
+
+                    let last_addr = data_addr + chunk_size - constants::BYTES_IN_PAGE;
+                    for addr in &[data_addr, last_addr] {
+                        store_atomic(&metadata_1_spec, *addr, 5, Ordering::SeqCst);
+                    }
+
+                    // The metadata for a page is less than a page, and it is zeroed.
+                    metadata.release_metadata_space(data_addr, constants::BYTES_IN_PAGE);
+                    assert_eq!(
+                        load_atomic(&metadata_1_spec, data_addr, Ordering::SeqCst),
+                        0
+                    );
+                    assert_eq!(
+                        load_atomic(&metadata_1_spec, last_addr, Ordering::SeqCst),
+                        5
+                    );
+                    assert_eq!(committed(), mapped);
+
+                    // The metadata for a chunk is decommitted, and no longer accounted. It is still accessible and reads as zero.
+                    metadata.release_metadata_space(data_addr, chunk_size);
+                    assert_eq!(committed(), 0);
+                    assert_eq!(
+                        load_atomic(&metadata_1_spec, last_addr, Ordering::SeqCst),
+                        0
+                    );
+                    store_atomic(&metadata_1_spec, last_addr, 3, Ordering::SeqCst);
+                    assert_eq!(
+                        load_atomic(&metadata_1_spec, last_addr, Ordering::SeqCst),
+                        3
+                    );
+
+                    metadata.ensure_unmap_metadata_space(data_addr, chunk_size);
+
+                    metadata_sanity.reset();
+                },
+                || {
+                    sanity::reset();
+                },
+            );
+        });
+    }
 }
@@ -35,12 +35,16 @@ pub(crate) mod edge_logger;
 pub(crate) mod finalizable_processor;
 /// Heap implementation, including page resource, mmapper, etc.
 pub(crate) mod heap;
+/// Chunks that are swept lazily after a GC.
+pub(crate) mod lazy_sweep;
 /// Logger initialization
 pub(crate) mod logger;
 /// Various malloc implementations (conditionally compiled by features)
 pub(crate) mod malloc;
 /// Metadata (OnSide or InHeader) implementation.
 pub mod metadata;
+/// NUMA topology, and thread and memory placement.
+pub(crate) mod numa;
 /// Forwarding word in object copying.
 pub(crate) mod object_forwarding;
 /// MMTk command line options.
@@ -52,11 +56,15 @@ pub(crate) mod rust_util;
 pub(crate) mod sanity;
 /// Utils for collecting statistics.
 pub(crate) mod statistics;
+/// Stress GCs at deterministic allocation points.
+pub(crate) mod stress_gc;
 /// Test utilities.
 #[cfg(test)]
 pub(crate) mod test_util;
 /// A treadmill implementation.
 pub(crate) mod treadmill;
+/// Heap verification after the transitive closure of a GC.
+pub(crate) mod verifier;
 
 // These modules are private. They are only used by other util modules.
 
@@ -72,6 +80,9 @@ mod synchronized_counter;
 
 pub use self::address::Address;
 pub use self::address::ObjectReference;
+pub use self::heap::gc_trigger::{GCTiming, GCTriggerPolicy};
+pub use self::heap::layout::vm_layout_constants::VMLayout;
 pub use self::opaque_pointer::*;
+pub use self::options::{OptionError, OptionInfo};
 pub use self::reference_processor::ReferenceProcessor;
 pub use self::synchronized_counter::SynchronizedCounter;
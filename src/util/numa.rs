@@ -0,0 +1,218 @@
+//! NUMA topology and placement. With the `numa` option, pages that a thread acquires from a space are
+//! preferably placed on the thread's node, and GC workers are bound to nodes in a round-robin fashion.
+//! On other OSes than Linux, or if the topology cannot be read, we assume a single node.
+
+use crate::util::Address;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether NUMA-aware placement is enabled. This is set from the `numa` option in `gc_init()`.
+static NUMA_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_numa_enabled(enabled: bool) {
+    NUMA_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Is NUMA-aware placement enabled, and is there more than one node?
+pub fn numa_enabled() -> bool {
+    NUMA_ENABLED.load(Ordering::Relaxed) && num_nodes() > 1
+}
+
+struct Topology {
+    /// The CPUs of each node.
+    node_cpus: Vec<Vec<usize>>,
+    /// The node of each CPU.
+    cpu_node: Vec<usize>,
+}
+
+lazy_static! {
+    static ref TOPOLOGY: Topology = Topology::read();
+}
+
+impl Topology {
+    const NODE_PATH: &'static str = "/sys/devices/system/node";
+
+    fn read() -> Self {
+        let node_cpus = Self::read_node_cpus().unwrap_or_default();
+        let node_cpus = if node_cpus.is_empty() {
+            vec![(0..num_cpus::get()).collect()]
+        } else {
+            node_cpus
+        };
+        let max_cpu = node_cpus.iter().flatten().copied().max().unwrap_or(0);
+        let mut cpu_node = vec![0; max_cpu + 1];
+        for (node, cpus) in node_cpus.iter().enumerate() {
+            for &cpu in cpus {
+                cpu_node[cpu] = node;
+            }
+        }
+        Topology {
+            node_cpus,
+            cpu_node,
+        }
+    }
+
+    fn read_node_cpus() -> Option<Vec<Vec<usize>>> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        let online = std::fs::read_to_string(format!("{}/online", Self::NODE_PATH)).ok()?;
+        // We assume the online nodes are numbered from 0 without gaps.
+        let nodes = parse_cpu_list(&online)?.into_iter().max()? + 1;
+        (0..nodes)
+            .map(|node| {
+                let list =
+                    std::fs::read_to_string(format!("{}/node{}/cpulist", Self::NODE_PATH, node))
+                        .ok()?;
+                parse_cpu_list(&list)
+            })
+            .collect()
+    }
+}
+
+/// Parse a list in the format of `/sys/devices/system/node/node0/cpulist`, e.g. `0-3,8,10-11`.
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = vec![];
+    for range in list.trim().split(',').filter(|s| !s.is_empty()) {
+        let mut bounds = range.splitn(2, '-');
+        let lo: usize = bounds.next()?.trim().parse().ok()?;
+        let hi: usize = match bounds.next() {
+            Some(hi) => hi.trim().parse().ok()?,
+            None => lo,
+        };
+        cpus.extend(lo..=hi);
+    }
+    Some(cpus)
+}
+
+/// The number of NUMA nodes.
+pub fn num_nodes() -> usize {
+    TOPOLOGY.node_cpus.len()
+}
+
+/// The NUMA node that the current thread is running on.
+pub fn current_node() -> usize {
+    #[cfg(target_os = "linux")]
+    {
+        let cpu = unsafe { libc::sched_getcpu() };
+        if cpu >= 0 {
+            return TOPOLOGY.cpu_node.get(cpu as usize).copied().unwrap_or(0);
+        }
+    }
+    0
+}
+
+/// The number of nodes that the node mask of `bind_memory_to_node()` can hold.
+const MAX_NODES: usize = 1024;
+
+fn invalid_input(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+/// Restrict the current thread to the CPUs of the given node. Returns an error if the node does not exist, or
+/// if it has a CPU that does not fit in a `cpu_set_t`.
+pub fn bind_current_thread_to_node(node: usize) -> Result<()> {
+    if node >= num_nodes() {
+        return Err(invalid_input(format!("NUMA node {} does not exist", node)));
+    }
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for &cpu in &TOPOLOGY.node_cpus[node] {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(invalid_input(format!(
+                    "CPU {} of NUMA node {} does not fit in a cpu_set_t",
+                    cpu, node
+                )));
+            }
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Prefer the given node for the pages in the range. This only affects pages that are not touched yet.
+/// Returns an error if the node does not fit in the node mask.
+pub fn bind_memory_to_node(start: Address, size: usize, node: usize) -> Result<()> {
+    if node >= MAX_NODES {
+        return Err(invalid_input(format!(
+            "NUMA node {} is out of the range of the node mask",
+            node
+        )));
+    }
+    #[cfg(target_os = "linux")]
+    {
+        const MPOL_PREFERRED: libc::c_long = 1;
+        const BITS_IN_MASK: usize = 64;
+        let nodemask: [u64; MAX_NODES / BITS_IN_MASK] = {
+            let mut mask = [0u64; MAX_NODES / BITS_IN_MASK];
+            mask[node / BITS_IN_MASK] |= 1 << (node % BITS_IN_MASK);
+            mask
+        };
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_mbind,
+                start.to_mut_ptr::<libc::c_void>(),
+                size,
+                MPOL_PREFERRED,
+                nodemask.as_ptr(),
+                nodemask.len() * BITS_IN_MASK,
+                0,
+            )
+        };
+        if ret != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Prefer the node of the current thread for the pages in the range, if NUMA-aware placement is enabled.
+/// A failure is logged and ignored, as the placement is only an optimization.
+pub fn bind_memory_to_current_node(start: Address, size: usize) {
+    if !numa_enabled() {
+        return;
+    }
+    let node = current_node();
+    if let Err(e) = bind_memory_to_node(start, size, node) {
+        warn!(
+            "Failed to bind {} of size {} to NUMA node {}: {}",
+            start, size, node, e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0"), Some(vec![0]));
+        assert_eq!(parse_cpu_list("0-3\n"), Some(vec![0, 1, 2, 3]));
+        assert_eq!(parse_cpu_list("0-1,8,10-11"), Some(vec![0, 1, 8, 10, 11]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("0-a"), None);
+    }
+
+    #[test]
+    fn test_topology() {
+        assert!(num_nodes() >= 1);
+        assert!(current_node() < num_nodes());
+        assert!(TOPOLOGY.node_cpus.iter().any(|cpus| !cpus.is_empty()));
+    }
+
+    #[test]
+    fn test_invalid_node() {
+        let err = bind_current_thread_to_node(num_nodes()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let mut memory = vec![0u8; 64];
+        let start = Address::from_mut_ptr(memory.as_mut_ptr());
+        let err = bind_memory_to_node(start, memory.len(), MAX_NODES).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
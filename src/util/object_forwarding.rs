@@ -134,6 +134,16 @@ pub fn is_forwarded_or_being_forwarded<VM: VMBinding>(object: ObjectReference) -
     ) != FORWARDING_NOT_TRIGGERED_YET
 }
 
+/// Get the forwarding bits of an object.
+pub fn get_forwarding_status<VM: VMBinding>(object: ObjectReference) -> usize {
+    load_metadata::<VM>(
+        &VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC,
+        object,
+        None,
+        Some(Ordering::SeqCst),
+    )
+}
+
 pub fn state_is_forwarded_or_being_forwarded(forwarding_bits: usize) -> bool {
     forwarding_bits != FORWARDING_NOT_TRIGGERED_YET
 }
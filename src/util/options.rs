@@ -1,9 +1,14 @@
+use crate::plan::MAX_EXTRA_SPACES;
 use crate::util::constants::DEFAULT_STRESS_FACTOR;
 use crate::util::constants::LOG_BYTES_IN_MBYTE;
 use std::cell::UnsafeCell;
 use std::default::Default;
+use std::fmt;
+use std::marker::PhantomData;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 custom_derive! {
     #[derive(Copy, Clone, EnumFromStr, Debug)]
@@ -27,9 +32,31 @@ custom_derive! {
         MarkSweep,
         PageProtect,
         Immix,
+        NativeMarkSweep,
     }
 }
 
+custom_derive! {
+    #[derive(Copy, Clone, EnumFromStr, Debug)]
+    pub enum GCTriggerSelector {
+        FixedHeapSize,
+        PauseTime,
+        Throughput,
+    }
+}
+
+// Print the enum options in the same form as they are parsed, e.g. `SemiSpace`.
+macro_rules! impl_display_by_debug {
+    ($($t:ty),*) => {
+        $(impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(self, f)
+            }
+        })*
+    };
+}
+impl_display_by_debug!(NurseryZeroingOptions, PlanSelector, GCTriggerSelector);
+
 /// MMTk option for perf events
 ///
 /// The format is
@@ -66,6 +93,18 @@ impl PerfEventOptions {
     }
 }
 
+impl fmt::Display for PerfEventOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, pid, cpu)) in self.events.iter().enumerate() {
+            if i != 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{},{},{}", name, pid, cpu)?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for PerfEventOptions {
     type Err = String;
 
@@ -74,6 +113,45 @@ impl FromStr for PerfEventOptions {
     }
 }
 
+/// MMTk option for the names of the extra immortal spaces. The names are separated by commas, and
+/// the binding allocates into the i-th space with the i-th `AllocationSemantics::EXTRA_SPACES`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraSpacesOptions {
+    pub names: Vec<String>,
+}
+
+impl ExtraSpacesOptions {
+    fn validate(&self) -> bool {
+        self.names.len() <= MAX_EXTRA_SPACES
+            && self
+                .names
+                .iter()
+                .enumerate()
+                .all(|(i, name)| !self.names[..i].contains(name))
+    }
+}
+
+impl fmt::Display for ExtraSpacesOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.names.join(","))
+    }
+}
+
+impl FromStr for ExtraSpacesOptions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ExtraSpacesOptions {
+            names: s
+                .split(',')
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
+}
+
 /// The default nursery space size.
 pub const NURSERY_SIZE: usize = 32 << LOG_BYTES_IN_MBYTE;
 /// The default min nursery size. This can be set through command line options.
@@ -83,26 +161,347 @@ pub const DEFAULT_MIN_NURSERY: usize = 32 << LOG_BYTES_IN_MBYTE;
 /// This does not affect the actual space we create as nursery. It is only used in GC trigger check.
 pub const DEFAULT_MAX_NURSERY: usize = 32 << LOG_BYTES_IN_MBYTE;
 
-pub struct UnsafeOptionsWrapper(UnsafeCell<Options>);
+/// The env var that points to an MMTk config file. See [`Options::set_from_config_file`].
+pub const CONFIG_FILE_ENV_VAR: &str = "MMTK_CONFIG_FILE";
+
+/// If this env var is set to `1` or `true`, MMTk prints the effective options to stderr at `gc_init()`.
+pub const PRINT_OPTIONS_ENV_VAR: &str = "MMTK_PRINT_OPTIONS";
+
+/// When an option can be changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OptionMode {
+    /// The option can only be changed before `gc_init()`.
+    InitOnly,
+    /// The option can be changed at any time, including while the program runs.
+    Dynamic,
+}
+
+impl fmt::Display for OptionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionMode::InitOnly => write!(f, "init-only"),
+            OptionMode::Dynamic => write!(f, "dynamic"),
+        }
+    }
+}
+
+/// A description of an option and its current value. See [`Options::describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionInfo {
+    /// The name of the option in snake case, e.g. `stress_factor`.
+    pub name: &'static str,
+    /// The current value of the option.
+    pub value: String,
+    /// The default value of the option, without considering env vars or config files.
+    pub default: String,
+    /// What the option does.
+    pub description: String,
+    /// When the option can be changed.
+    pub mode: OptionMode,
+}
+
+/// Print an option in the format of a config file (see [`Options::set_from_config_file`]), with its description
+/// and default value as a comment.
+impl fmt::Display for OptionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# {} (default: {}, {})",
+            self.description, self.default, self.mode
+        )?;
+        write!(f, "{} = {}", self.name, self.value)
+    }
+}
+
+/// Join the doc comment lines of an option into one line.
+fn join_doc_lines(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An error from setting an option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionError {
+    /// There is no option with the given name.
+    UnknownKey(String),
+    /// The value cannot be parsed as the option's type: (name, value).
+    ParseError(String, String),
+    /// The value is parsed, but rejected by the option's validator: (name, value).
+    InvalidValue(String, String),
+    /// The given string is not in the form of `name=value`.
+    MalformedPair(String),
+    /// The option cannot be changed at this point: (name, reason).
+    NotAllowed(String, &'static str),
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionError::UnknownKey(name) => write!(f, "unknown option {:?}", name),
+            OptionError::ParseError(name, value) => {
+                write!(f, "unable to set {}={:?}. Cant parse value", name, value)
+            }
+            OptionError::InvalidValue(name, value) => {
+                write!(f, "unable to set {}={:?}. Invalid value", name, value)
+            }
+            OptionError::MalformedPair(pair) => {
+                write!(f, "{:?} is not in the form of name=value", pair)
+            }
+            OptionError::NotAllowed(name, reason) => {
+                write!(f, "unable to set {}. {}", name, reason)
+            }
+        }
+    }
+}
+
+/// Convert an option name from camel case (e.g. `stressFactor`) to snake case (e.g. `stress_factor`).
+/// A name that is already in snake case is unchanged.
+fn camelcase_to_snakecase(s: &str) -> String {
+    let mut sr = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_uppercase() {
+            sr.push('_');
+            for c in c.to_lowercase() {
+                sr.push(c);
+            }
+        } else {
+            sr.push(c)
+        }
+    }
+    sr
+}
+
+/// Split a `name=value` pair, trimming whitespace around the name and the value. The value may be quoted.
+fn split_pair(pair: &str) -> Result<(&str, &str), OptionError> {
+    match pair.find('=') {
+        Some(i) => {
+            let name = pair[..i].trim();
+            let value = pair[i + 1..].trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            if name.is_empty() {
+                Err(OptionError::MalformedPair(pair.to_string()))
+            } else {
+                Ok((name, value))
+            }
+        }
+        None => Err(OptionError::MalformedPair(pair.to_string())),
+    }
+}
+
+/// Split a comma separated list of `name=value` pairs. A value that contains commas (such as perf events) needs
+/// to be in double quotes. A segment without `=` is an error.
+fn split_bulk(options: &str) -> Result<Vec<(&str, &str)>, OptionError> {
+    let mut pairs = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+    for (i, c) in options.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                push_pair(&mut pairs, &options[start..i])?;
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    push_pair(&mut pairs, &options[start..])?;
+    Ok(pairs)
+}
+
+fn push_pair<'a>(pairs: &mut Vec<(&'a str, &'a str)>, pair: &'a str) -> Result<(), OptionError> {
+    // Allow empty segments, e.g. a trailing comma.
+    if !pair.trim().is_empty() {
+        pairs.push(split_pair(pair)?);
+    }
+    Ok(())
+}
+
+/// A type that a dynamic option can have. The value is stored in an `AtomicUsize`.
+pub trait DynamicOptionValue: Copy {
+    fn to_usize(self) -> usize;
+    fn from_usize(v: usize) -> Self;
+}
+
+impl DynamicOptionValue for usize {
+    fn to_usize(self) -> usize {
+        self
+    }
+    fn from_usize(v: usize) -> Self {
+        v
+    }
+}
+
+impl DynamicOptionValue for bool {
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+    fn from_usize(v: usize) -> Self {
+        v != 0
+    }
+}
+
+/// The value of a dynamic option. A dynamic option can be changed by one thread while other threads
+/// (such as mutators in their allocation slow path) read it, so the value is kept in an atomic.
+pub struct DynamicOption<T: DynamicOptionValue> {
+    value: AtomicUsize,
+    _type: PhantomData<T>,
+}
+
+impl<T: DynamicOptionValue> DynamicOption<T> {
+    pub fn new(value: T) -> Self {
+        DynamicOption {
+            value: AtomicUsize::new(value.to_usize()),
+            _type: PhantomData,
+        }
+    }
+
+    /// Get the current value of the option.
+    pub fn get(&self) -> T {
+        T::from_usize(self.value.load(Ordering::Relaxed))
+    }
+
+    /// Set the option. Threads that read the option afterwards see the new value.
+    pub fn set(&self, value: T) {
+        self.value.store(value.to_usize(), Ordering::Relaxed)
+    }
+}
+
+impl<T: DynamicOptionValue + fmt::Display> fmt::Display for DynamicOption<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: DynamicOptionValue + fmt::Debug> fmt::Debug for DynamicOption<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+/// Parse and validate the value of an option.
+fn parse_option<T: FromStr, F: Fn(&T) -> bool>(
+    name: &str,
+    val: &str,
+    validate_fn: F,
+) -> Result<T, OptionError> {
+    match val.parse::<T>() {
+        Ok(parsed) if validate_fn(&parsed) => Ok(parsed),
+        Ok(_) => Err(OptionError::InvalidValue(name.to_string(), val.to_string())),
+        Err(_) => Err(OptionError::ParseError(name.to_string(), val.to_string())),
+    }
+}
+
+/// Turn the result of setting an option into a bool, and warn about the error. An unknown key is a bug in the caller.
+fn check_set_result(result: Result<(), OptionError>) -> bool {
+    match result {
+        Ok(()) => true,
+        Err(OptionError::UnknownKey(_)) => panic!("Invalid Options key"),
+        Err(e) => {
+            eprintln!("Warn: {}. Default value will be used.", e);
+            false
+        }
+    }
+}
+
+pub struct UnsafeOptionsWrapper {
+    options: UnsafeCell<Options>,
+    /// Set in `gc_init()`. After this, init-only options can no longer be changed.
+    initialized: AtomicBool,
+    /// Serializes changes to the options.
+    write_lock: Mutex<()>,
+}
 
 // TODO: We should carefully examine the unsync with UnsafeCell. We should be able to provide a safe implementation.
 unsafe impl Sync for UnsafeOptionsWrapper {}
 
 impl UnsafeOptionsWrapper {
-    pub const fn new(o: Options) -> UnsafeOptionsWrapper {
-        UnsafeOptionsWrapper(UnsafeCell::new(o))
+    pub fn new(o: Options) -> UnsafeOptionsWrapper {
+        UnsafeOptionsWrapper {
+            options: UnsafeCell::new(o),
+            initialized: AtomicBool::new(false),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Mark the options as initialized. After this, only dynamic options can be changed.
+    pub(crate) fn mark_initialized(&self) {
+        let _lock = self.write_lock.lock().unwrap();
+        self.initialized.store(true, Ordering::SeqCst);
     }
+
+    /// Check if the option (in snake case) can be changed at this point. Unknown options are not checked here,
+    /// and are reported when we try to set them.
+    fn check_mode(&self, name: &str) -> Result<(), OptionError> {
+        match Options::mode_of(name) {
+            // The plan is decided when an MMTk instance is created, so it cannot be changed by processing options.
+            Some(_) if name == "plan" => Err(OptionError::NotAllowed(
+                "plan".to_string(),
+                "The plan needs to be set before creating an MMTk instance (e.g. by MMTK_PLAN or MMTK_CONFIG_FILE)",
+            )),
+            Some(OptionMode::InitOnly) if self.initialized.load(Ordering::SeqCst) => {
+                Err(OptionError::NotAllowed(
+                    name.to_string(),
+                    "The option is init-only, and MMTk has been initialized",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// # Safety
-    /// This method is not thread safe, as internally it acquires a mutable reference to self.
-    /// It is supposed to be used by one thread during boot time.
+    /// Before `gc_init()`, this method is supposed to be used by one thread during boot time.
+    /// After `gc_init()`, only dynamic options can be changed. Dynamic options are stored in atomics
+    /// and set through a shared reference, so other threads may read them at the same time. A change
+    /// takes effect the next time MMTk reads the option.
     pub unsafe fn process(&self, name: &str, value: &str) -> bool {
-        (&mut *self.0.get()).set_from_camelcase_str(name, value)
+        let _lock = self.write_lock.lock().unwrap();
+        let snake_name = camelcase_to_snakecase(name);
+        if let Err(e) = self.check_mode(&snake_name) {
+            eprintln!("Warn: {}.", e);
+            return false;
+        }
+        if self.initialized.load(Ordering::SeqCst) {
+            check_set_result((&*self.options.get()).try_set_dynamic_from_str(&snake_name, value))
+        } else {
+            (&mut *self.options.get()).set_from_camelcase_str(name, value)
+        }
+    }
+
+    /// Process a comma separated list of `name=value` pairs. See [`Options::set_bulk_from_str`].
+    /// If any of the options cannot be changed at this point, no option is changed. The plan cannot be set
+    /// here, as it is chosen when the MMTk instance is created (see `MMTK::with_options()`).
+    ///
+    /// # Safety
+    /// The same as [`UnsafeOptionsWrapper::process`].
+    pub unsafe fn process_bulk(&self, options: &str) -> Result<(), OptionError> {
+        let _lock = self.write_lock.lock().unwrap();
+        let pairs = split_bulk(options)?;
+        for (name, _) in pairs.iter() {
+            self.check_mode(&camelcase_to_snakecase(name))?;
+        }
+        if self.initialized.load(Ordering::SeqCst) {
+            let shared = &*self.options.get();
+            for (name, value) in pairs {
+                trace!("Trying to process option pair: ({}, {})", name, value);
+                shared.try_set_dynamic_from_str(&camelcase_to_snakecase(name), value)?;
+            }
+            Ok(())
+        } else {
+            (&mut *self.options.get()).set_bulk_from_str(options)
+        }
     }
 }
 impl Deref for UnsafeOptionsWrapper {
     type Target = Options;
     fn deref(&self) -> &Options {
-        unsafe { &*self.0.get() }
+        unsafe { &*self.options.get() }
     }
 }
 
@@ -111,41 +510,92 @@ fn always_valid<T>(_: &T) -> bool {
 }
 
 macro_rules! options {
-    ($($name:ident: $type:ty[$validator:expr] = $default:expr),*,) => [
-        options!($($name: $type[$validator] = $default),*);
+    (@mode init_only) => [OptionMode::InitOnly];
+    (@mode dynamic) => [OptionMode::Dynamic];
+    // Dynamic options are stored in atomics, as they can be changed while other threads read them.
+    (@type init_only $type:ty) => [$type];
+    (@type dynamic $type:ty) => [DynamicOption<$type>];
+    (@new init_only $value:expr) => [$value];
+    (@new dynamic $value:expr) => [DynamicOption::new($value)];
+    (@set init_only $field:expr, $value:expr) => [$field = $value];
+    (@set dynamic $field:expr, $value:expr) => [$field.set($value)];
+    (@set_shared init_only $field:expr, $name:expr, $value:expr) => [
+        Err(OptionError::NotAllowed($name.to_string(), "The option is init-only"))
+    ];
+    (@set_shared dynamic $field:expr, $name:expr, $value:expr) => [$value.map(|v| $field.set(v))];
+    ($($(#[doc = $doc:literal])* $mode:ident $name:ident: $type:ty[$validator:expr] = $default:expr),*,) => [
+        options!($($(#[doc = $doc])* $mode $name: $type[$validator] = $default),*);
     ];
-    ($($name:ident: $type:ty[$validator:expr] = $default:expr),*) => [
+    ($($(#[doc = $doc:literal])* $mode:ident $name:ident: $type:ty[$validator:expr] = $default:expr),*) => [
         pub struct Options {
-            $(pub $name: $type),*
+            $($(#[doc = $doc])* pub $name: options!(@type $mode $type)),*
         }
         impl Options {
-            pub fn set_from_str(&mut self, s: &str, val: &str)->bool {
+            /// Options with the default values, without reading env vars or config files.
+            fn new_with_defaults() -> Self {
+                Options {
+                    $($name: options!(@new $mode $default)),*
+                }
+            }
+
+            /// Describe all the options with their current values, default values and descriptions,
+            /// in the order they are declared.
+            pub fn describe(&self) -> Vec<OptionInfo> {
+                let defaults = Options::new_with_defaults();
+                vec![$(OptionInfo {
+                    name: stringify!($name),
+                    value: self.$name.to_string(),
+                    default: defaults.$name.to_string(),
+                    description: join_doc_lines(&[$($doc),*]),
+                    mode: options!(@mode $mode),
+                }),*]
+            }
+
+            /// Get the mode of an option (in snake case), or `None` if there is no such option.
+            pub fn mode_of(name: &str) -> Option<OptionMode> {
+                match name {
+                    $(stringify!($name) => Some(options!(@mode $mode)),)*
+                    _ => None
+                }
+            }
+
+            /// Set an option from a string value. The option name is in snake case (e.g. `stress_factor`).
+            /// Returns an error if the name is unknown, or the value cannot be parsed or is invalid.
+            /// The option is unchanged if an error is returned.
+            pub fn try_set_from_str(&mut self, s: &str, val: &str) -> Result<(), OptionError> {
                 match s {
-                    // Parse the given value from str (by env vars or by calling process()) to the right type
-                    $(stringify!($name) => if let Ok(ref val) = val.parse::<$type>() {
-                        // Validate
-                        let validate_fn = $validator;
-                        let is_valid = validate_fn(val);
-                        if is_valid {
-                            // Only set value if valid.
-                            self.$name = val.clone();
-                        } else {
-                            eprintln!("Warn: unable to set {}={:?}. Invalid value. Default value will be used.", s, val);
-                        }
-                        is_valid
-                    } else {
-                        eprintln!("Warn: unable to set {}={:?}. Cant parse value. Default value will be used.", s, val);
-                        false
+                    // Parse the given value from str (by env vars or by calling process()) to the right type,
+                    // and only set the value if it is valid.
+                    $(stringify!($name) => {
+                        let parsed = parse_option::<$type, _>(s, val, $validator)?;
+                        options!(@set $mode self.$name, parsed);
+                        Ok(())
                     })*
-                    _ => panic!("Invalid Options key")
+                    _ => Err(OptionError::UnknownKey(s.to_string()))
+                }
+            }
+
+            /// Set a dynamic option from a string value through a shared reference, while other threads may read
+            /// the options. Returns an error for init-only options.
+            fn try_set_dynamic_from_str(&self, s: &str, val: &str) -> Result<(), OptionError> {
+                match s {
+                    $(stringify!($name) => options!(@set_shared $mode self.$name, s, parse_option::<$type, _>(s, val, $validator)),)*
+                    _ => Err(OptionError::UnknownKey(s.to_string()))
                 }
             }
+
+            pub fn set_from_str(&mut self, s: &str, val: &str)->bool {
+                check_set_result(self.try_set_from_str(s, val))
+            }
         }
         impl Default for Options {
             fn default() -> Self {
-                let mut options = Options {
-                    $($name: $default),*
-                };
+                let mut options = Options::new_with_defaults();
+
+                // If MMTK_CONFIG_FILE is set, we read options from the file first, so env vars can override them.
+                if let Ok(path) = std::env::var(CONFIG_FILE_ENV_VAR) {
+                    options.set_from_config_file(&path);
+                }
 
                 // If we have env vars that start with MMTK_ and match any option (such as MMTK_STRESS_FACTOR),
                 // we set the option to its value (if it is a valid value). Otherwise, use the default value.
@@ -165,66 +615,122 @@ macro_rules! options {
         }
     ]
 }
+// Each option is either `init_only` (it can only be changed before gc_init()) or `dynamic`
+// (it can be changed at any time through process()). A dynamic option is stored as a `DynamicOption` (so its type
+// needs to implement `DynamicOptionValue`), and must be read with `get()` by MMTk each time it is used, rather than
+// being cached at initialization.
 options! {
-    // The plan to use. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
-    plan:                  PlanSelector         [always_valid] = PlanSelector::NoGC,
-    // Number of GC threads.
-    threads:               usize                [|v: &usize| *v > 0]    = num_cpus::get(),
-    // Enable an optimization that only scans the part of the stack that has changed since the last GC (not supported)
-    use_short_stack_scans: bool                 [always_valid] = false,
-    // Enable a return barrier (not supported)
-    use_return_barrier:    bool                 [always_valid] = false,
-    // Should we eagerly finish sweeping at the start of a collection? (not supported)
-    eager_complete_sweep:  bool                 [always_valid] = false,
-    // Should we ignore GCs requested by the user (e.g. java.lang.System.gc)?
-    ignore_system_g_c:     bool                 [always_valid] = false,
-    // The upper bound of nursery size. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
-    max_nursery:           usize                [|v: &usize| *v > 0 ] = DEFAULT_MAX_NURSERY,
-    // The lower bound of nusery size. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
-    min_nursery:           usize                [|v: &usize| *v > 0 ] = DEFAULT_MIN_NURSERY,
-    // Should a major GC be performed when a system GC is required?
-    full_heap_system_gc:   bool                 [always_valid] = false,
-    // Should we shrink/grow the heap to adjust to application working set? (not supported)
-    variable_size_heap:    bool                 [always_valid] = true,
-    // Should finalization be disabled?
-    no_finalizer:          bool                 [always_valid] = false,
-    // Should reference type processing be disabled?
-    no_reference_types:    bool                 [always_valid] = false,
-    // The zeroing approach to use for new object allocations. Affects each plan differently. (not supported)
-    nursery_zeroing:       NurseryZeroingOptions[always_valid] = NurseryZeroingOptions::Temporal,
-    // How frequent (every X bytes) should we do a stress GC?
-    stress_factor:         usize                [always_valid] = DEFAULT_STRESS_FACTOR,
-    // How frequent (every X bytes) should we run analysis (a STW event that collects data)
-    analysis_factor:       usize                [always_valid] = DEFAULT_STRESS_FACTOR,
-    // The size of vmspace. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
+    /// The plan to use. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
+    init_only plan:                  PlanSelector         [always_valid] = PlanSelector::NoGC,
+    /// Number of GC threads.
+    init_only threads:               usize                [|v: &usize| *v > 0]    = num_cpus::get(),
+    /// Enable an optimization that only scans the part of the stack that has changed since the last GC (not supported)
+    init_only use_short_stack_scans: bool                 [always_valid] = false,
+    /// Enable a return barrier (not supported)
+    init_only use_return_barrier:    bool                 [always_valid] = false,
+    /// Sweep lazily after mutators resume, rather than in the Release stage of a GC. Chunks are swept on demand
+    /// by mutators when they allocate. This is supported by MarkSweep and Immix.
+    init_only lazy_sweep:            bool                 [always_valid] = false,
+    /// With lazy_sweep, should GC workers eagerly finish sweeping in the background after mutators resume?
+    /// Otherwise the chunks that the mutators do not sweep are swept at the start of the next collection.
+    init_only eager_complete_sweep:  bool                 [always_valid] = false,
+    /// Should we ignore GCs requested by the user (e.g. java.lang.System.gc)?
+    dynamic   ignore_system_g_c:     bool                 [always_valid] = false,
+    /// The upper bound of nursery size.
+    dynamic   max_nursery:           usize                [|v: &usize| *v > 0 ] = DEFAULT_MAX_NURSERY,
+    /// The lower bound of nusery size.
+    dynamic   min_nursery:           usize                [|v: &usize| *v > 0 ] = DEFAULT_MIN_NURSERY,
+    /// The policy that decides the heap size at which a GC is triggered. FixedHeapSize uses the heap size given in gc_init().
+    /// PauseTime and Throughput adjust the heap size (up to the size given in gc_init()) after each GC to meet their goals.
+    init_only gc_trigger:            GCTriggerSelector    [always_valid] = GCTriggerSelector::FixedHeapSize,
+    /// The pause time goal in milliseconds for the PauseTime GC trigger.
+    init_only gc_pause_time_goal:    usize                [|v: &usize| *v > 0] = 10,
+    /// The GC time ratio for the Throughput GC trigger. MMTk aims to spend no more than 1/(1+gc_time_ratio) of the total time in GC.
+    init_only gc_time_ratio:         usize                [|v: &usize| *v > 0] = 19,
+    /// Should a major GC be performed when a system GC is required?
+    dynamic   full_heap_system_gc:   bool                 [always_valid] = false,
+    /// Should we shrink/grow the heap to adjust to application working set? (not supported)
+    init_only variable_size_heap:    bool                 [always_valid] = true,
+    /// Should finalization be disabled?
+    init_only no_finalizer:          bool                 [always_valid] = false,
+    /// Should reference type processing be disabled?
+    init_only no_reference_types:    bool                 [always_valid] = false,
+    /// The zeroing approach to use for new object allocations. Affects each plan differently. (not supported)
+    init_only nursery_zeroing:       NurseryZeroingOptions[always_valid] = NurseryZeroingOptions::Temporal,
+    /// How frequent (every X bytes) should we do a stress GC?
+    dynamic   stress_factor:         usize                [always_valid] = DEFAULT_STRESS_FACTOR,
+    /// How frequent (every X bytes) should we run analysis (a STW event that collects data)
+    dynamic   analysis_factor:       usize                [always_valid] = DEFAULT_STRESS_FACTOR,
+    /// Force a GC at every Kth allocation by mutators (counted over all mutators). The bump pointer and Immix allocators then
+    /// hide their limit so that every allocation takes the slow path, including those that the binding inlines. 0 disables this.
+    init_only stress_gc_interval:    usize                [always_valid] = 0,
+    /// If not 0, the number of allocations between two stress GCs is random between 1 and 2K-1 (K is `stress_gc_interval`),
+    /// drawn from a generator with this seed. The same seed gives the same GC points if the mutators allocate in the same order.
+    init_only stress_gc_seed:        usize                [always_valid] = 0,
+    /// A file to write the allocation number of each stress GC to, one per line.
+    init_only stress_gc_log:         String               [always_valid] = String::new(),
+    /// A file written with `stress_gc_log`. If set, stress GCs happen exactly at the allocations in the file, so a failing run
+    /// can be replayed. This overrides `stress_gc_interval` and `stress_gc_seed`.
+    init_only stress_gc_replay:      String               [always_valid] = String::new(),
+    /// The size of vmspace. This needs to be initialized before creating an MMTk instance (currently by setting env vars)
     // FIXME: This value is set for JikesRVM. We need a proper way to set options.
     //   We need to set these values programmatically in VM specific code.
-    vm_space_size:         usize                [|v: &usize| *v > 0]    = 0x7cc_cccc,
+    init_only vm_space_size:         usize                [|v: &usize| *v > 0]    = 0x7cc_cccc,
+    /// Names of extra immortal spaces, separated by commas (at most 4). The binding allocates into them with
+    /// AllocationSemantics::Extra0 to Extra3, e.g. to keep interned strings, class metadata and JIT stubs apart.
+    init_only extra_spaces:          ExtraSpacesOptions   [ExtraSpacesOptions::validate] = ExtraSpacesOptions {names: vec![]},
+    /// Should we advise the OS to back the heap and side metadata with transparent huge pages (madvise(MADV_HUGEPAGE), Linux only)?
+    /// This applies to memory mapped by any MMTk instance once an instance with this option is initialized.
+    init_only transparent_hugepages: bool                 [always_valid] = false,
+    /// Should we map the heap and side metadata, and touch every page of them in gc_init()? This moves the cost of
+    /// page faults to start-up. The heap size given in gc_init() is split between the contiguous spaces in chunks, and the
+    /// pages are touched with `threads` threads.
+    init_only pretouch_heap:         bool                 [always_valid] = false,
+    /// Should we place memory and GC workers based on NUMA nodes (Linux only)? Pages acquired by a thread are preferably
+    /// placed on the thread's node, GC workers are bound to nodes in a round-robin fashion, and they prefer work from their own node.
+    init_only numa:                  bool                 [always_valid] = false,
+    /// Should we relocate the heap if something else in the process (e.g. ASan) has mapped memory in MMTk's default heap range
+    /// (64-bit only)? The heap is moved to a free range with a contiguous layout, and the side metadata is moved to a free range
+    /// if its default range is occupied too. Bindings that compute side metadata addresses from the constants must then add
+    /// `side_metadata_relocation()`. This only applies to the first MMTk instance, and only if the binding has not called
+    /// `memory_manager::set_vm_layout()`.
+    init_only relocate_heap_on_conflict: bool             [always_valid] = false,
+    /// The maximum number of pages of freed large object cells (up to 1MB each) that we keep for reuse until the next GC,
+    /// instead of releasing their pages. Large objects are rounded up to a size class if this is not 0. 0 disables the cache.
+    init_only los_cache_pages:       usize                [always_valid] = 0,
+    /// Verify the heap in every GC, after the transitive closure and before the spaces are released. Every edge of every
+    /// reachable object is checked against the policy and the metadata. At the end of the GC, the path from a root to each bad edge is
+    /// printed, and the process aborts.
+    init_only verify_heap:           bool                 [always_valid] = false,
+    /// For generational plans, check before every nursery GC that each mature object that refers to a nursery object was logged
+    /// by the write barrier, and report the slot and the object if not. This traces the whole heap, and aborts if the check fails.
+    init_only verify_remset:         bool                 [always_valid] = false,
+    /// Fill memory with a recognizable pattern (0xdeadbeef) when it is released: the from-space of copying spaces, the free lines
+    /// and blocks of Immix, the cells freed by MallocSpace and the pages of the large object space. Spaces that promise zeroed memory
+    /// then zero the memory when it is acquired again.
+    init_only poison_on_release:     bool                 [always_valid] = false,
+    /// Protect released pages with mprotect so any access crashes, and unprotect them when they are allocated again. This applies
+    /// to contiguous copying spaces and the large object space (which then does not cache freed cells). The large object space
+    /// unprotects its free chunks when it returns them to the global pool, as another space may take them.
+    init_only protect_on_release:    bool                 [always_valid] = false,
+    /// For the PageProtect plan, copy every reachable object to fresh pages in every GC, so the pages of the old copies are
+    /// released and protected. Any stale reference to an object that the binding failed to update then crashes on access.
+    init_only page_protect_moving:   bool                 [always_valid] = false,
     // Perf events to measure
     // Semicolons are used to separate events
     // Each event is in the format of event_name,pid,cpu (see man perf_event_open for what pid and cpu mean)
     //
-    // Measuring perf events for work packets
-    work_perf_events:       PerfEventOptions     [always_valid] = PerfEventOptions {events: vec![]},
-    // Measuring perf events for GC and mutators
-    phase_perf_events:      PerfEventOptions     [always_valid] = PerfEventOptions {events: vec![]}
+    /// Perf events to measure for work packets. Events are separated by semicolons, and each event is event_name,pid,cpu.
+    init_only work_perf_events:       PerfEventOptions     [always_valid] = PerfEventOptions {events: vec![]},
+    /// Perf events to measure for GC and mutators. Events are separated by semicolons, and each event is event_name,pid,cpu.
+    init_only phase_perf_events:      PerfEventOptions     [always_valid] = PerfEventOptions {events: vec![]}
 }
 
 impl Options {
     fn set_from_camelcase_str(&mut self, s: &str, val: &str) -> bool {
         trace!("Trying to process option pair: ({}, {})", s, val);
 
-        let mut sr = String::with_capacity(s.len());
-        for c in s.chars() {
-            if c.is_uppercase() {
-                sr.push('_');
-                for c in c.to_lowercase() {
-                    sr.push(c);
-                }
-            } else {
-                sr.push(c)
-            }
-        }
+        let sr = camelcase_to_snakecase(s);
 
         let result = self.set_from_str(sr.as_str(), val);
 
@@ -237,6 +743,43 @@ impl Options {
         }
         result
     }
+
+    /// Set options from a comma separated list of `name=value` pairs, such as
+    /// `"plan=Immix,threads=8,stress_factor=4096"`. Names can be in snake case or camel case. A value that contains
+    /// commas needs to be in double quotes, e.g. `work_perf_events="PERF_COUNT_HW_CPU_CYCLES,0,-1"`.
+    /// The plan is only used if these options create an MMTk instance (see `MMTK::with_options()`).
+    /// The pairs are processed in order, and this stops at the first error. The pairs before the error are kept.
+    pub fn set_bulk_from_str(&mut self, options: &str) -> Result<(), OptionError> {
+        for (name, value) in split_bulk(options)? {
+            trace!("Trying to process option pair: ({}, {})", name, value);
+            self.try_set_from_str(&camelcase_to_snakecase(name), value)?;
+        }
+        Ok(())
+    }
+
+    /// Set options from a config file. Each line of the file is a `name = value` pair, and the value
+    /// may be quoted. Empty lines and lines starting with `#` are ignored. A line with an error is reported
+    /// with its line number and ignored.
+    pub fn set_from_config_file(&mut self, path: &str) {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Warn: unable to read config file {}: {}", path, e);
+                return;
+            }
+        };
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(e) = split_pair(line).and_then(|(name, value)| {
+                self.try_set_from_str(&camelcase_to_snakecase(name), value)
+            }) {
+                eprintln!("Warn: {}:{}: {}. The line is ignored.", path, i + 1, e);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -250,7 +793,7 @@ mod tests {
     fn no_env_var() {
         serial_test(|| {
             let options = Options::default();
-            assert_eq!(options.stress_factor, DEFAULT_STRESS_FACTOR);
+            assert_eq!(options.stress_factor.get(), DEFAULT_STRESS_FACTOR);
         })
     }
 
@@ -262,7 +805,7 @@ mod tests {
                     std::env::set_var("MMTK_STRESS_FACTOR", "4096");
 
                     let options = Options::default();
-                    assert_eq!(options.stress_factor, 4096);
+                    assert_eq!(options.stress_factor.get(), 4096);
                 },
                 || {
                     std::env::remove_var("MMTK_STRESS_FACTOR");
@@ -280,7 +823,7 @@ mod tests {
                     std::env::set_var("MMTK_NO_FINALIZER", "true");
 
                     let options = Options::default();
-                    assert_eq!(options.stress_factor, 4096);
+                    assert_eq!(options.stress_factor.get(), 4096);
                     assert!(options.no_finalizer);
                 },
                 || {
@@ -300,7 +843,7 @@ mod tests {
                     std::env::set_var("MMTK_STRESS_FACTOR", "abc");
 
                     let options = Options::default();
-                    assert_eq!(options.stress_factor, DEFAULT_STRESS_FACTOR);
+                    assert_eq!(options.stress_factor.get(), DEFAULT_STRESS_FACTOR);
                 },
                 || {
                     std::env::remove_var("MMTK_STRESS_FACTOR");
@@ -318,7 +861,7 @@ mod tests {
                     std::env::set_var("MMTK_ABC", "42");
 
                     let options = Options::default();
-                    assert_eq!(options.stress_factor, DEFAULT_STRESS_FACTOR);
+                    assert_eq!(options.stress_factor.get(), DEFAULT_STRESS_FACTOR);
                 },
                 || {
                     std::env::remove_var("MMTK_ABC");
@@ -381,4 +924,238 @@ mod tests {
             )
         })
     }
+
+    #[test]
+    fn test_bulk_options() {
+        serial_test(|| {
+            let mut options = Options::default();
+            assert_eq!(
+                options.set_bulk_from_str("threads=8, stress_factor = 4096,noFinalizer=true"),
+                Ok(())
+            );
+            assert_eq!(options.threads, 8);
+            assert_eq!(options.stress_factor.get(), 4096);
+            assert!(options.no_finalizer);
+        })
+    }
+
+    #[test]
+    fn test_bulk_options_with_comma_in_value() {
+        serial_test(|| {
+            let mut options = Options::default();
+            assert_eq!(
+                options.set_bulk_from_str(
+                    "work_perf_events=\"PERF_COUNT_HW_CPU_CYCLES,0,-1\",threads=2"
+                ),
+                Ok(())
+            );
+            assert_eq!(
+                &options.work_perf_events,
+                &PerfEventOptions {
+                    events: vec![("PERF_COUNT_HW_CPU_CYCLES".into(), 0, -1)]
+                }
+            );
+            assert_eq!(options.threads, 2);
+        })
+    }
+
+    #[test]
+    fn test_bulk_options_errors() {
+        serial_test(|| {
+            let mut options = Options::default();
+            assert_eq!(
+                options.set_bulk_from_str("threads=4,abc=1"),
+                Err(OptionError::UnknownKey("abc".to_string()))
+            );
+            // Options before the error are set.
+            assert_eq!(options.threads, 4);
+            assert_eq!(
+                options.set_bulk_from_str("stress_factor=abc"),
+                Err(OptionError::ParseError(
+                    "stress_factor".to_string(),
+                    "abc".to_string()
+                ))
+            );
+            assert_eq!(
+                options.set_bulk_from_str("threads=0"),
+                Err(OptionError::InvalidValue(
+                    "threads".to_string(),
+                    "0".to_string()
+                ))
+            );
+            assert_eq!(
+                options.set_bulk_from_str("threads"),
+                Err(OptionError::MalformedPair("threads".to_string()))
+            );
+            // A comma in a value needs quotes.
+            assert_eq!(
+                options.set_bulk_from_str("work_perf_events=PERF_COUNT_HW_CPU_CYCLES,0,-1"),
+                Err(OptionError::MalformedPair("0".to_string()))
+            );
+            assert_eq!(options.threads, 4);
+        })
+    }
+
+    #[test]
+    fn test_config_file() {
+        serial_test(|| {
+            let path = std::env::temp_dir().join("mmtk_test_config_file");
+            with_cleanup(
+                || {
+                    std::fs::write(
+                        &path,
+                        "# MMTk options\n\nstress_factor = 4096\nno_finalizer = \"true\"\nabc = 1\nthreads = 0\n",
+                    )
+                    .unwrap();
+                    std::env::set_var("MMTK_CONFIG_FILE", &path);
+                    // Env vars override the config file
+                    std::env::set_var("MMTK_STRESS_FACTOR", "1024");
+
+                    let options = Options::default();
+                    assert_eq!(options.stress_factor.get(), 1024);
+                    assert!(options.no_finalizer);
+                    // Invalid lines are ignored
+                    assert_eq!(options.threads, num_cpus::get());
+                },
+                || {
+                    std::env::remove_var("MMTK_CONFIG_FILE");
+                    std::env::remove_var("MMTK_STRESS_FACTOR");
+                    let _ = std::fs::remove_file(&path);
+                },
+            )
+        })
+    }
+
+    #[test]
+    fn test_describe() {
+        serial_test(|| {
+            let mut options = Options::default();
+            options
+                .set_bulk_from_str(
+                    "stress_factor=4096,work_perf_events=\"PERF_COUNT_HW_CPU_CYCLES,0,-1\"",
+                )
+                .unwrap();
+            let described = options.describe();
+            let stress_factor = described
+                .iter()
+                .find(|o| o.name == "stress_factor")
+                .unwrap();
+            assert_eq!(stress_factor.value, "4096");
+            assert_eq!(stress_factor.default, DEFAULT_STRESS_FACTOR.to_string());
+            assert!(!stress_factor.description.is_empty());
+
+            // The values can be parsed back.
+            let mut parsed = Options::default();
+            for o in described.iter() {
+                assert_eq!(parsed.try_set_from_str(o.name, &o.value), Ok(()));
+            }
+            assert_eq!(parsed.stress_factor.get(), 4096);
+            assert_eq!(parsed.work_perf_events, options.work_perf_events);
+            assert_eq!(parsed.plan as usize, options.plan as usize);
+        })
+    }
+
+    #[test]
+    fn test_init_only_options() {
+        serial_test(|| {
+            let options = UnsafeOptionsWrapper::new(Options::default());
+            unsafe {
+                // Before initialization, all the options except the plan can be set.
+                assert!(options.process("threads", "2"));
+                assert!(options.process("stressFactor", "4096"));
+                assert!(!options.process("plan", "SemiSpace"));
+
+                options.mark_initialized();
+                assert_eq!(Options::mode_of("threads"), Some(OptionMode::InitOnly));
+                assert!(!options.process("threads", "4"));
+                assert_eq!(options.threads, 2);
+                assert_eq!(Options::mode_of("stress_factor"), Some(OptionMode::Dynamic));
+                assert!(options.process("stressFactor", "8192"));
+                assert_eq!(options.stress_factor.get(), 8192);
+
+                // Nothing is changed if any option in the bulk is init-only.
+                assert_eq!(
+                    options.process_bulk("stress_factor=1024,threads=4"),
+                    Err(OptionError::NotAllowed(
+                        "threads".to_string(),
+                        "The option is init-only, and MMTk has been initialized"
+                    ))
+                );
+                assert_eq!(options.stress_factor.get(), 8192);
+                assert_eq!(
+                    options.process_bulk("stress_factor=1024,min_nursery=4096"),
+                    Ok(())
+                );
+                assert_eq!(options.stress_factor.get(), 1024);
+                assert_eq!(options.min_nursery.get(), 4096);
+            }
+        })
+    }
+
+    #[test]
+    fn test_dynamic_options_from_threads() {
+        serial_test(|| {
+            use std::sync::Arc;
+            let options = Arc::new(UnsafeOptionsWrapper::new(Options::default()));
+            options.mark_initialized();
+            let values = [1024usize, 2048, 4096, 8192];
+            let writers: Vec<_> = values
+                .iter()
+                .map(|&v| {
+                    let options = options.clone();
+                    std::thread::spawn(move || {
+                        for _ in 0..1000 {
+                            assert!(unsafe { options.process("stress_factor", &v.to_string()) });
+                            assert!(unsafe {
+                                options.process_bulk(&format!("full_heap_system_gc={}", v > 2048))
+                            }
+                            .is_ok());
+                        }
+                    })
+                })
+                .collect();
+            let readers: Vec<_> = (0..4)
+                .map(|_| {
+                    let options = options.clone();
+                    std::thread::spawn(move || {
+                        for _ in 0..1000 {
+                            let stress_factor = options.stress_factor.get();
+                            assert!(
+                                stress_factor == DEFAULT_STRESS_FACTOR
+                                    || values.contains(&stress_factor)
+                            );
+                            let _ = options.full_heap_system_gc.get();
+                        }
+                    })
+                })
+                .collect();
+            for t in writers.into_iter().chain(readers) {
+                t.join().unwrap();
+            }
+            assert!(values.contains(&options.stress_factor.get()));
+        })
+    }
+
+    #[test]
+    fn test_extra_spaces() {
+        serial_test(|| {
+            let mut options = Options::default();
+            assert!(options.extra_spaces.names.is_empty());
+            assert_eq!(
+                options.set_bulk_from_str("extra_spaces=\"strings, metadata,stubs\",threads=2"),
+                Ok(())
+            );
+            assert_eq!(
+                options.extra_spaces.names,
+                vec!["strings", "metadata", "stubs"]
+            );
+            assert_eq!(options.extra_spaces.to_string(), "strings,metadata,stubs");
+            assert_eq!(options.threads, 2);
+
+            // Too many spaces, or duplicate names
+            assert!(!options.set_from_str("extra_spaces", "a,b,c,d,e"));
+            assert!(!options.set_from_str("extra_spaces", "a,b,a"));
+            assert_eq!(options.extra_spaces.names.len(), 3);
+        })
+    }
 }
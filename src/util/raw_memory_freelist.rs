@@ -194,8 +194,12 @@ impl RawMemoryFreeList {
     }
 
     fn mmap(&self, start: Address, bytes: usize) {
-        if super::memory::dzmmap_noreplace(start, bytes).is_err() {
-            panic!("Can't get more space with mmap()");
+        if let Err(e) = super::memory::dzmmap_noreplace(start, bytes) {
+            panic!(
+                "Can't get more space with mmap(): {}. Conflicting mappings:\n{}",
+                e,
+                super::memory::get_overlapping_mappings(&[(start, bytes)]).join("\n")
+            );
         }
     }
     pub fn get_limit(&self) -> Address {
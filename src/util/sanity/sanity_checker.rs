@@ -58,6 +58,11 @@ impl<P: Plan, W: CopyContext + GCWorkerLocal> GCWork<P::VM> for ScheduleSanityGC
         }
         scheduler.work_buckets[WorkBucketStage::Prepare]
             .add(ScanVMSpecificRoots::<SanityGCProcessEdges<P::VM>>::new());
+        schedule_vm_region_roots::<SanityGCProcessEdges<P::VM>>(
+            mmtk,
+            plan.base().vm_regions.dirty_objects(),
+            WorkBucketStage::Prepare,
+        );
         // Prepare global/collectors/mutators
         worker.scheduler().work_buckets[WorkBucketStage::Prepare].add(SanityPrepare::<P, W>::new(
             plan.downcast_ref::<P>().unwrap(),
@@ -1,4 +1,6 @@
 use crate::mmtk::MMTK;
+use crate::util::heap::gc_trigger::GCTiming;
+use crate::util::metadata::side_metadata;
 use crate::util::options::Options;
 use crate::util::statistics::counter::*;
 use crate::util::statistics::Timer;
@@ -10,6 +12,7 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Instant;
 
 pub const MAX_PHASES: usize = 1 << 12;
 pub const MAX_COUNTERS: usize = 100;
@@ -49,6 +52,10 @@ pub struct Stats {
     pub shared: Arc<SharedStats>,
     counters: Mutex<Vec<Arc<Mutex<dyn Counter + Send>>>>,
     exceeded_phase_limit: AtomicBool,
+    // Wall-clock timing for GC triggers. Unlike the counters, this is recorded for every GC.
+    gc_start_time: Mutex<Instant>,
+    gc_end_time: Mutex<Instant>,
+    last_gc_timing: Mutex<GCTiming>,
 }
 
 impl Stats {
@@ -92,6 +99,9 @@ impl Stats {
             shared,
             counters: Mutex::new(counters),
             exceeded_phase_limit: AtomicBool::new(false),
+            gc_start_time: Mutex::new(Instant::now()),
+            gc_end_time: Mutex::new(Instant::now()),
+            last_gc_timing: Mutex::new(GCTiming::default()),
         }
     }
 
@@ -143,6 +153,12 @@ impl Stats {
 
     pub fn start_gc(&self) {
         self.gc_count.fetch_add(1, Ordering::SeqCst);
+        {
+            let now = Instant::now();
+            *self.gc_start_time.lock().unwrap() = now;
+            self.last_gc_timing.lock().unwrap().mutator =
+                now.duration_since(*self.gc_end_time.lock().unwrap());
+        }
         if !self.get_gathering_stats() {
             return;
         }
@@ -159,6 +175,12 @@ impl Stats {
     }
 
     pub fn end_gc(&self) {
+        {
+            let now = Instant::now();
+            *self.gc_end_time.lock().unwrap() = now;
+            self.last_gc_timing.lock().unwrap().pause =
+                now.duration_since(*self.gc_start_time.lock().unwrap());
+        }
         if !self.get_gathering_stats() {
             return;
         }
@@ -174,11 +196,22 @@ impl Stats {
         }
     }
 
+    /// The timing of the last finished GC.
+    pub fn last_gc_timing(&self) -> GCTiming {
+        *self.last_gc_timing.lock().unwrap()
+    }
+
     pub fn print_stats<VM: VMBinding>(&self, mmtk: &'static MMTK<VM>) {
         println!(
             "============================ MMTk Statistics Totals ============================"
         );
-        let scheduler_stat = mmtk.scheduler.statistics();
+        let mut scheduler_stat = mmtk.scheduler.statistics();
+        for stats in side_metadata::side_metadata_stats() {
+            scheduler_stat.insert(
+                format!("side_metadata.{}", stats.name),
+                stats.committed_bytes.to_string(),
+            );
+        }
         self.print_column_names(&scheduler_stat);
         print!("{}\t", self.get_phase() / 2);
         let counter = self.counters.lock().unwrap();
@@ -0,0 +1,193 @@
+use crate::util::options::Options;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// A schedule of stress GCs at allocation points (see the `stress_gc_*` options). Allocations by mutators
+/// are counted, and a GC is forced at the allocations picked by the schedule: every `stress_gc_interval`
+/// allocations, at random intervals from a seed, or at the allocations read from a replay file.
+/// The GC points are deterministic as long as the mutators allocate in the same order.
+pub struct StressGCSchedule {
+    enabled: bool,
+    /// The number of allocations by mutators so far.
+    allocations: AtomicUsize,
+    /// The allocation at which the next GC is forced. `usize::MAX` if there is none.
+    next_gc: AtomicUsize,
+    state: Mutex<ScheduleState>,
+}
+
+struct ScheduleState {
+    interval: usize,
+    /// The state of the random generator. 0 if the interval is fixed.
+    rng: u64,
+    /// The GC points that are left in the replay file.
+    replay: Option<VecDeque<usize>>,
+    log: Option<File>,
+}
+
+impl StressGCSchedule {
+    pub fn new(options: &Options) -> Self {
+        let replay = if options.stress_gc_replay.is_empty() {
+            None
+        } else {
+            Some(read_gc_points(&options.stress_gc_replay))
+        };
+        let log = if options.stress_gc_log.is_empty() {
+            None
+        } else {
+            Some(File::create(&options.stress_gc_log).unwrap_or_else(|e| {
+                panic!(
+                    "Failed to create the stress GC log {}: {}",
+                    options.stress_gc_log, e
+                )
+            }))
+        };
+        let mut state = ScheduleState {
+            interval: options.stress_gc_interval,
+            rng: options.stress_gc_seed as u64,
+            replay,
+            log,
+        };
+        let enabled = state.replay.is_some() || state.interval != 0;
+        let next_gc = state.next_gc_after(0);
+        StressGCSchedule {
+            enabled,
+            allocations: AtomicUsize::new(0),
+            next_gc: AtomicUsize::new(next_gc),
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Are stress GCs scheduled?
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Count an allocation by a mutator. Return true if a GC should be forced before the allocation.
+    pub fn on_allocation(&self) -> bool {
+        let allocation = self.allocations.fetch_add(1, Ordering::SeqCst) + 1;
+        if allocation < self.next_gc.load(Ordering::SeqCst) {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        // Another mutator may have taken this GC point while we were waiting for the lock.
+        let gc = self.next_gc.load(Ordering::SeqCst);
+        if allocation < gc {
+            return false;
+        }
+        info!("Stress GC at allocation {}", gc);
+        if let Some(log) = state.log.as_mut() {
+            // Write each point as it happens, so the log is complete if the program crashes in the GC.
+            if let Err(e) = writeln!(log, "{}", gc) {
+                warn!("Failed to write the stress GC log: {}", e);
+            }
+        }
+        let next = state.next_gc_after(gc);
+        self.next_gc.store(next, Ordering::SeqCst);
+        true
+    }
+}
+
+impl ScheduleState {
+    /// Get the allocation of the GC after the one at `gc`.
+    fn next_gc_after(&mut self, gc: usize) -> usize {
+        if let Some(replay) = self.replay.as_mut() {
+            return replay.pop_front().unwrap_or(usize::MAX);
+        }
+        if self.interval == 0 {
+            usize::MAX
+        } else if self.rng == 0 {
+            gc.saturating_add(self.interval)
+        } else {
+            let range = (self.interval.saturating_mul(2) - 1) as u64;
+            gc.saturating_add(1 + (next_random(&mut self.rng) % range) as usize)
+        }
+    }
+}
+
+/// Read the GC points from a stress GC log.
+fn read_gc_points(path: &str) -> VecDeque<usize> {
+    let file = File::open(path)
+        .unwrap_or_else(|e| panic!("Failed to open the stress GC replay file {}: {}", path, e));
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+            line.trim().parse::<usize>().unwrap_or_else(|_| {
+                panic!("{}:{}: not an allocation number: {}", path, i + 1, line)
+            })
+        })
+        .collect()
+}
+
+/// The xorshift64* generator. The state must not be 0.
+fn next_random(state: &mut u64) -> u64 {
+    let mut x = *state;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    *state = x;
+    x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gc_points(schedule: &StressGCSchedule, allocations: usize) -> Vec<usize> {
+        (1..=allocations)
+            .filter(|_| schedule.on_allocation())
+            .collect()
+    }
+
+    #[test]
+    fn fixed_interval() {
+        let mut options = Options::default();
+        options.stress_gc_interval = 10;
+        let schedule = StressGCSchedule::new(&options);
+        assert!(schedule.is_enabled());
+        assert_eq!(gc_points(&schedule, 35), vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn disabled() {
+        let schedule = StressGCSchedule::new(&Options::default());
+        assert!(!schedule.is_enabled());
+        assert!(gc_points(&schedule, 100).is_empty());
+    }
+
+    #[test]
+    fn random_interval_is_deterministic() {
+        let mut options = Options::default();
+        options.stress_gc_interval = 8;
+        options.stress_gc_seed = 42;
+        let points = gc_points(&StressGCSchedule::new(&options), 1000);
+        assert!(!points.is_empty());
+        assert!(points.windows(2).all(|w| w[1] > w[0] && w[1] - w[0] < 16));
+        assert_eq!(points, gc_points(&StressGCSchedule::new(&options), 1000));
+    }
+
+    #[test]
+    fn replay_log() {
+        let dir = std::env::temp_dir();
+        let log = dir.join(format!("mmtk-stress-gc-{}.log", std::process::id()));
+        let log = log.to_str().unwrap().to_string();
+
+        let mut options = Options::default();
+        options.stress_gc_interval = 5;
+        options.stress_gc_seed = 7;
+        options.stress_gc_log = log.clone();
+        let points = gc_points(&StressGCSchedule::new(&options), 200);
+
+        let mut options = Options::default();
+        options.stress_gc_replay = log.clone();
+        let schedule = StressGCSchedule::new(&options);
+        assert!(schedule.is_enabled());
+        assert_eq!(gc_points(&schedule, 200), points);
+        std::fs::remove_file(log).unwrap();
+    }
+}
@@ -0,0 +1,507 @@
+//! Heap verification. With the `verify_heap` option, every GC traces the heap once more after the transitive closure
+//! (in the `Verify` stage, before any space is released), and checks every edge of every reachable object against the
+//! policy of the target object and its metadata. Unlike the sanity GC, the verifier does not keep objects alive or
+//! update any edge, so it can run in any GC without changing its outcome. With the `verify_remset` option, the same
+//! trace checks the remembered set of a generational plan before every nursery GC (in the `Prepare` stage, before
+//! the closure changes anything).
+
+use crate::mmtk::SFT_MAP;
+use crate::plan::{Mutator, TransitiveClosure};
+use crate::policy::space::{Space, EMPTY_SFT_NAME, SFT};
+use crate::scheduler::gc_work::*;
+use crate::scheduler::*;
+use crate::util::metadata::load_metadata;
+use crate::util::object_forwarding;
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use crate::MMTK;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// We report at most this many failures for a GC. The rest are only counted.
+const MAX_REPORTED_FAILURES: usize = 16;
+/// A path from a root is cut after this many objects.
+const MAX_PATH_LENGTH: usize = 32;
+
+/// How the verifier first reached an object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Referrer {
+    /// From a root slot.
+    Root(Address),
+    /// From a slot of another object.
+    Object(ObjectReference, Address),
+}
+
+/// The state of the heap verification for the current GC.
+#[derive(Default)]
+pub struct HeapVerifier {
+    /// The referrer of each object that has been reached, so we can tell the path from a root to a bad edge.
+    parents: Mutex<HashMap<ObjectReference, Referrer>>,
+    /// The descriptions of the first failures.
+    failures: Mutex<Vec<String>>,
+    /// The number of failures, including the ones that are not reported.
+    num_failures: AtomicUsize,
+    /// Is the current trace checking the remembered set before a nursery GC, rather than verifying the heap?
+    checking_remset: AtomicBool,
+}
+
+impl HeapVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule the verification for the current GC. This should be called after the plan schedules its collection.
+    /// The failures are reported by `report()` at the end of the GC.
+    pub fn schedule<VM: VMBinding>(scheduler: &GCWorkScheduler<VM>) {
+        scheduler.work_buckets[WorkBucketStage::Verify].add(ScheduleVerification {
+            checking_remset: false,
+        });
+    }
+
+    /// Schedule the check of the remembered set for the current nursery GC. This should be called after the plan
+    /// schedules its collection. The trace runs in the `Prepare` stage, after the mutators are stopped and before
+    /// the closure, so the heap is as the mutators left it.
+    pub fn schedule_remset_check<VM: VMBinding>(scheduler: &GCWorkScheduler<VM>) {
+        scheduler.work_buckets[WorkBucketStage::Prepare].add(ScheduleVerification {
+            checking_remset: true,
+        });
+    }
+
+    /// Start a new trace. The failures are kept, as a GC may check the remembered set and then verify the heap,
+    /// and they are reported together.
+    fn reset(&self, checking_remset: bool) {
+        self.parents.lock().unwrap().clear();
+        self.checking_remset
+            .store(checking_remset, Ordering::SeqCst);
+    }
+
+    /// The stage that the packets of the current trace go to.
+    fn stage(&self) -> WorkBucketStage {
+        if self.checking_remset.load(Ordering::Relaxed) {
+            WorkBucketStage::Prepare
+        } else {
+            WorkBucketStage::Verify
+        }
+    }
+
+    /// Record the referrer of an object. Return true if this is the first time the object is reached.
+    fn visit(&self, object: ObjectReference, referrer: Referrer) -> bool {
+        match self.parents.lock().unwrap().entry(object) {
+            Entry::Vacant(e) => {
+                e.insert(referrer);
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    /// Verify an edge to the given object. Return true if the object is reached for the first time, and should be scanned.
+    fn verify_edge<VM: VMBinding>(
+        &self,
+        mmtk: &'static MMTK<VM>,
+        object: ObjectReference,
+        referrer: Referrer,
+    ) -> bool {
+        let result = if self.checking_remset.load(Ordering::Relaxed) {
+            Self::verify_remset(mmtk, object, referrer)
+        } else {
+            Self::verify_object(mmtk, object)
+        };
+        if let Err(problem) = result {
+            self.record_failure(object, referrer, problem);
+            // Do not scan the object. Its fields may not be valid.
+            return false;
+        }
+        // Like the GC, we do not trace through the objects in VM regions. The objects in the regions that have
+        // references into the heap are roots.
+        self.visit(object, referrer) && !mmtk.plan.base().vm_regions.contains(object.to_address())
+    }
+
+    /// Check a reachable object against its policy and its metadata.
+    fn verify_object<VM: VMBinding>(
+        mmtk: &'static MMTK<VM>,
+        object: ObjectReference,
+    ) -> Result<(), String> {
+        let sft = sft_of(mmtk, object)?;
+        let plan = &mmtk.plan;
+        if plan.constraints().moves_objects
+            && sft.is_movable()
+            && object_forwarding::is_forwarded::<VM>(object)
+        {
+            return Err(format!(
+                "the object in {} has been forwarded to {}, but the edge was not updated",
+                sft.name(),
+                object_forwarding::read_forwarding_pointer::<VM>(object)
+            ));
+        }
+        // A nursery GC does not trace the mature spaces, so only the objects in the nursery tell their liveness.
+        let check_policy =
+            !plan.is_current_gc_nursery() || plan.generational().nursery.in_space(object);
+        if check_policy && !sft.is_live(object) {
+            return Err(format!("the object is not live in {}", sft.name()));
+        }
+        #[cfg(feature = "global_alloc_bit")]
+        if !mmtk.plan.base().vm_regions.contains(object.to_address())
+            && !crate::util::alloc_bit::is_alloced(object)
+        {
+            return Err(format!(
+                "the alloc bit of the object in {} is not set",
+                sft.name()
+            ));
+        }
+        // The barrier logs an object at its first write after a GC, and the GC unlogs the objects in the mod buffer.
+        // So every object that survives a GC must be unlogged.
+        if is_logged(mmtk, object) == Some(true) {
+            return Err(format!(
+                "the object in {} is still logged after the GC",
+                sft.name()
+            ));
+        }
+        if check_policy {
+            sft.verify_object(object)
+                .map_err(|problem| format!("{} in {}", problem, sft.name()))?;
+        }
+        Ok(())
+    }
+
+    /// Check the remembered set of a generational plan before a nursery GC. The nursery GC only scans the roots and
+    /// the objects that the write barrier logged (and put in the mod buffers), so a mature object that refers to a
+    /// nursery object needs to be logged. Otherwise the write barrier was missed, and the nursery object may be
+    /// reclaimed while the mature object refers to it.
+    fn verify_remset<VM: VMBinding>(
+        mmtk: &'static MMTK<VM>,
+        object: ObjectReference,
+        referrer: Referrer,
+    ) -> Result<(), String> {
+        sft_of(mmtk, object)?;
+        if let Referrer::Object(source, slot) = referrer {
+            if is_young(mmtk, object)
+                && !is_young(mmtk, source)
+                && is_logged(mmtk, source) == Some(false)
+            {
+                return Err(format!(
+                    "slot {} of the mature object {} points to a nursery object, but {} is not logged. The write barrier was missed",
+                    slot, source, source
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn record_failure(&self, object: ObjectReference, referrer: Referrer, problem: String) {
+        if self.num_failures.fetch_add(1, Ordering::SeqCst) >= MAX_REPORTED_FAILURES {
+            return;
+        }
+        let failure = format!(
+            "{}: {}\n    path: {}",
+            object,
+            problem,
+            self.path(object, referrer)
+        );
+        self.failures.lock().unwrap().push(failure);
+    }
+
+    /// Describe the path from a root to the given object, which is reached through `referrer`.
+    fn path(&self, object: ObjectReference, referrer: Referrer) -> String {
+        let parents = self.parents.lock().unwrap();
+        let mut steps = vec![];
+        let mut current = object;
+        let mut referrer = Some(referrer);
+        loop {
+            match referrer {
+                Some(Referrer::Root(slot)) => {
+                    steps.push(format!("{} (root slot {})", current, slot));
+                    break;
+                }
+                Some(Referrer::Object(parent, slot)) if steps.len() < MAX_PATH_LENGTH => {
+                    steps.push(format!("{} (slot {})", current, slot));
+                    current = parent;
+                    referrer = parents.get(&parent).copied();
+                }
+                _ => {
+                    steps.push(format!("{} ...", current));
+                    break;
+                }
+            }
+        }
+        steps.reverse();
+        steps.join(" -> ")
+    }
+
+    /// Print all the failures found in this GC, if any, and abort. This is called by the GC controller after all
+    /// the work buckets are drained. A panic in a GC thread would leave the mutators waiting for the GC forever.
+    pub(crate) fn report(&self) {
+        let num_failures = self.num_failures.load(Ordering::SeqCst);
+        if num_failures == 0 {
+            return;
+        }
+        let failures = self.failures.lock().unwrap();
+        eprintln!(
+            "Heap verification found {} bad edge(s) ({} reported):\n{}",
+            num_failures,
+            failures.len(),
+            failures.join("\n")
+        );
+        std::process::abort();
+    }
+}
+
+/// Find the space of an object with the SFT. The malloc space has no SFT entries, so we look for it in the spaces
+/// of the plan.
+fn sft_of<VM: VMBinding>(
+    mmtk: &'static MMTK<VM>,
+    object: ObjectReference,
+) -> Result<&'static dyn SFT, String> {
+    if !object.is_mapped() {
+        return Err(
+            "the object is not in any space. It may be in a region that has been released"
+                .to_string(),
+        );
+    }
+    let sft = SFT_MAP.get(object.to_address());
+    if sft.name() != EMPTY_SFT_NAME {
+        return Ok(sft);
+    }
+    let mut found: Option<*const dyn SFT> = None;
+    mmtk.plan.for_each_space(&mut |space| {
+        if found.is_none() && space.in_space(object) {
+            found = Some(space.as_sft() as *const (dyn SFT + Sync) as *const dyn SFT);
+        }
+    });
+    // The spaces are owned by the plan, which lives as long as the MMTk instance.
+    found
+        .map(|sft| unsafe { &*sft })
+        .ok_or_else(|| "the object is not in any space of the plan".to_string())
+}
+
+/// Is the object in the nursery of a generational plan? The young objects in the large object space are in place,
+/// and only have their nursery bit set.
+fn is_young<VM: VMBinding>(mmtk: &'static MMTK<VM>, object: ObjectReference) -> bool {
+    let los = mmtk.plan.common().get_los();
+    mmtk.plan.generational().nursery.in_space(object)
+        || (los.in_space(object) && los.is_in_nursery(object))
+}
+
+/// Is the object logged? Return `None` if the plan does not use the log bit, or if the object is in a VM region or
+/// the VM space. Those objects are not allocated by MMTk, so their log bits are never set up.
+fn is_logged<VM: VMBinding>(mmtk: &'static MMTK<VM>, object: ObjectReference) -> Option<bool> {
+    let plan = &mmtk.plan;
+    if !plan.constraints().needs_log_bit || plan.base().vm_regions.contains(object.to_address()) {
+        return None;
+    }
+    #[cfg(feature = "vm_space")]
+    if plan.base().vm_space.in_space(object) {
+        return None;
+    }
+    Some(
+        load_metadata::<VM>(
+            &VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
+            object,
+            None,
+            Some(Ordering::SeqCst),
+        ) == 0,
+    )
+}
+
+/// Start the verification: scan the roots again with `VerifyRootEdges`.
+struct ScheduleVerification {
+    /// Check the remembered set before a nursery GC (see `HeapVerifier::schedule_remset_check()`).
+    checking_remset: bool,
+}
+
+impl<VM: VMBinding> GCWork<VM> for ScheduleVerification {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        mmtk.verifier.reset(self.checking_remset);
+        let stage = mmtk.verifier.stage();
+        let scheduler = worker.scheduler();
+        for mutator in VM::VMActivePlan::mutators() {
+            scheduler.work_buckets[stage].add(VerifyStackRoot(mutator));
+        }
+        scheduler.work_buckets[stage].add(ScanVMSpecificRoots::<VerifyRootEdges<VM>>::new());
+        // Before the GC, the objects in VM regions have been taken by `StopMutators`, and they are scanned and
+        // recorded again during the GC. So the remembered set is not checked for the objects that are only reachable
+        // through VM regions. The writes to the objects in VM regions are not remembered by the write barrier anyway.
+        if !self.checking_remset {
+            schedule_vm_region_roots::<VerifyRootEdges<VM>>(
+                mmtk,
+                mmtk.plan.base().vm_regions.dirty_objects(),
+                stage,
+            );
+        }
+    }
+}
+
+/// Scan the stack of a mutator with `VerifyRootEdges`. Unlike `ScanStackRoot`, this only scans the stack. The
+/// mutator has been flushed, and the GC has been told that the stacks are scanned.
+struct VerifyStackRoot<VM: VMBinding>(&'static mut Mutator<VM>);
+
+impl<VM: VMBinding> GCWork<VM> for VerifyStackRoot<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        VM::VMScanning::scan_thread_root::<VerifyRootEdges<VM>>(
+            unsafe { &mut *(self.0 as *mut _) },
+            worker.tls,
+        );
+    }
+}
+
+/// Verify root edges. The edges are not updated.
+pub struct VerifyRootEdges<VM: VMBinding> {
+    base: ProcessEdgesBase<VerifyRootEdges<VM>>,
+}
+
+impl<VM: VMBinding> Deref for VerifyRootEdges<VM> {
+    type Target = ProcessEdgesBase<Self>;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for VerifyRootEdges<VM> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for VerifyRootEdges<VM> {
+    type VM = VM;
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<Address>, _roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        Self {
+            base: ProcessEdgesBase::new(edges, mmtk),
+        }
+    }
+
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        object
+    }
+
+    fn process_edge(&mut self, slot: Address) {
+        let object = unsafe { slot.load::<ObjectReference>() };
+        if !object.is_null()
+            && self
+                .mmtk()
+                .verifier
+                .verify_edge(self.mmtk(), object, Referrer::Root(slot))
+        {
+            self.process_node(object);
+        }
+    }
+
+    fn flush(&mut self) {
+        let nodes = mem::take(&mut self.nodes);
+        let stage = self.mmtk().verifier.stage();
+        self.mmtk().scheduler.work_buckets[stage].add(VerifyObjects::new(nodes));
+    }
+}
+
+/// Collect the edges of an object.
+struct EdgeCollector(Vec<Address>);
+
+impl TransitiveClosure for EdgeCollector {
+    fn process_edge(&mut self, slot: Address) {
+        self.0.push(slot);
+    }
+    fn process_node(&mut self, _object: ObjectReference) {
+        unreachable!();
+    }
+}
+
+/// Verify the edges of the given objects, and verify the newly reached objects in new packets.
+pub struct VerifyObjects<VM: VMBinding> {
+    objects: Vec<ObjectReference>,
+    phantom: PhantomData<VM>,
+}
+
+impl<VM: VMBinding> VerifyObjects<VM> {
+    fn new(objects: Vec<ObjectReference>) -> Self {
+        Self {
+            objects,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for VerifyObjects<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let capacity = <VerifyRootEdges<VM> as ProcessEdgesWork>::CAPACITY;
+        let stage = mmtk.verifier.stage();
+        let mut next = vec![];
+        for &object in &self.objects {
+            // Scan each object on its own, so we know the source object of each edge.
+            let mut edges = EdgeCollector(vec![]);
+            VM::VMScanning::scan_object(&mut edges, object, worker.tls);
+            for slot in edges.0 {
+                let target = unsafe { slot.load::<ObjectReference>() };
+                if !target.is_null()
+                    && mmtk
+                        .verifier
+                        .verify_edge(mmtk, target, Referrer::Object(object, slot))
+                {
+                    next.push(target);
+                    if next.len() >= capacity {
+                        worker.scheduler().work_buckets[stage]
+                            .add(VerifyObjects::<VM>::new(mem::take(&mut next)));
+                    }
+                }
+            }
+        }
+        if !next.is_empty() {
+            worker.scheduler().work_buckets[stage].add(VerifyObjects::<VM>::new(next));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(i: usize) -> ObjectReference {
+        unsafe { Address::from_usize(i << 4).to_object_reference() }
+    }
+
+    fn slot(i: usize) -> Address {
+        unsafe { Address::from_usize((i << 4) + 8) }
+    }
+
+    #[test]
+    fn path_from_root() {
+        let verifier = HeapVerifier::new();
+        assert!(verifier.visit(object(1), Referrer::Root(slot(100))));
+        assert!(verifier.visit(object(2), Referrer::Object(object(1), slot(1))));
+        // The first referrer is kept.
+        assert!(!verifier.visit(object(2), Referrer::Root(slot(101))));
+
+        let path = verifier.path(object(3), Referrer::Object(object(2), slot(2)));
+        assert_eq!(
+            path,
+            format!(
+                "{} (root slot {}) -> {} (slot {}) -> {} (slot {})",
+                object(1),
+                slot(100),
+                object(2),
+                slot(1),
+                object(3),
+                slot(2)
+            )
+        );
+    }
+
+    #[test]
+    fn long_path_is_cut() {
+        let verifier = HeapVerifier::new();
+        verifier.visit(object(1), Referrer::Root(slot(100)));
+        for i in 2..=(MAX_PATH_LENGTH * 2) {
+            verifier.visit(object(i), Referrer::Object(object(i - 1), slot(i - 1)));
+        }
+        let last = MAX_PATH_LENGTH * 2;
+        let path = verifier.path(object(last + 1), Referrer::Object(object(last), slot(last)));
+        assert!(path.starts_with(&format!("{} ...", object(last + 1 - MAX_PATH_LENGTH))));
+        assert_eq!(path.matches(" -> ").count(), MAX_PATH_LENGTH);
+    }
+}
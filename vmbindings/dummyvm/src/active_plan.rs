@@ -2,34 +2,33 @@ use mmtk::Plan;
 use mmtk::vm::ActivePlan;
 use mmtk::util::opaque_pointer::*;
 use mmtk::Mutator;
+use instance::Instance;
 use DummyVM;
-use SINGLETON;
 
 pub struct VMActivePlan<> {}
 
 impl ActivePlan<DummyVM> for VMActivePlan {
     fn global() -> &'static dyn Plan<VM=DummyVM> {
-        SINGLETON.get_plan()
+        Instance::current().mmtk().get_plan()
     }
 
     fn number_of_mutators() -> usize {
-        unimplemented!()
+        Instance::current().number_of_mutators()
     }
 
     fn is_mutator(_tls: VMThread) -> bool {
-        // FIXME
-        true
+        !Instance::is_gc_thread()
     }
 
-    fn mutator(_tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
-        unimplemented!()
+    fn mutator(tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
+        Instance::current().mutator(tls)
     }
 
     fn reset_mutator_iterator() {
-        unimplemented!()
+        Instance::current().reset_mutator_iterator()
     }
 
     fn get_next_mutator() -> Option<&'static mut Mutator<DummyVM>> {
-        unimplemented!()
+        Instance::current().get_next_mutator()
     }
 }
\ No newline at end of file
@@ -142,6 +142,18 @@ pub extern "C" fn process(name: *const c_char, value: *const c_char) -> bool {
     memory_manager::process(&SINGLETON, name_str.to_str().unwrap(), value_str.to_str().unwrap())
 }
 
+#[no_mangle]
+pub extern "C" fn process_bulk(options: *const c_char) -> bool {
+    let options_str: &CStr = unsafe { CStr::from_ptr(options) };
+    match memory_manager::process_bulk(&SINGLETON, options_str.to_str().unwrap()) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Failed to process options: {}", e);
+            false
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn starting_heap_address() -> Address {
     memory_manager::starting_heap_address()
@@ -2,28 +2,29 @@ use mmtk::vm::Collection;
 use mmtk::MutatorContext;
 use mmtk::util::opaque_pointer::*;
 use mmtk::scheduler::*;
+use instance::Instance;
 use DummyVM;
 
 pub struct VMCollection {}
 
 impl Collection<DummyVM> for VMCollection {
     fn stop_all_mutators<E: ProcessEdgesWork<VM=DummyVM>>(_tls: VMWorkerThread) {
-        unimplemented!()
+        Instance::current().stop_all_mutators()
     }
 
     fn resume_mutators(_tls: VMWorkerThread) {
-        unimplemented!()
+        Instance::current().resume_mutators()
     }
 
     fn block_for_gc(_tls: VMMutatorThread) {
-        unimplemented!();
+        Instance::current().block_for_gc()
     }
 
-    fn spawn_worker_thread(_tls: VMThread, _ctx: Option<&GCWorker<DummyVM>>) {
-        unimplemented!();
+    fn spawn_worker_thread(_tls: VMThread, ctx: Option<&GCWorker<DummyVM>>) {
+        Instance::current().spawn_gc_thread(ctx)
     }
 
     fn prepare_mutator<T: MutatorContext<DummyVM>>(_tls_w: VMWorkerThread, _tls_m: VMMutatorThread, _mutator: &T) {
-        unimplemented!()
+        // DummyVM has nothing to prepare for a mutator.
     }
 }
\ No newline at end of file
@@ -0,0 +1,276 @@
+//! The runtime state of an MMTk instance in DummyVM: its mutators, its roots, and the synchronization
+//! between the mutators and the GC. DummyVM does not have mutator threads of its own. Mutators are bound
+//! by the threads that use them, and they only yield to the GC when they block for a GC in an allocation
+//! or a collection request. So all the mutators of an instance should be used by one thread.
+//!
+//! The VM traits do not know which MMTk instance they are called for, so each thread has a current instance.
+//! The GC threads of an instance use it as their current instance, and other threads use `SINGLETON` unless
+//! they enter another instance.
+
+use mmtk::memory_manager;
+use mmtk::scheduler::GCWorker;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+use mmtk::MutatorContext;
+use mmtk::MMTK;
+use object_model;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+use DummyVM;
+use SINGLETON;
+
+lazy_static! {
+    static ref SINGLETON_INSTANCE: Instance = Instance::new(&*SINGLETON);
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<&'static Instance>> = Cell::new(None);
+    static IS_GC_THREAD: Cell<bool> = Cell::new(false);
+}
+
+struct GCState {
+    /// The number of GCs that have finished.
+    epoch: usize,
+    /// The number of threads that are blocked for a GC.
+    blocked: usize,
+}
+
+pub struct Instance {
+    mmtk: &'static MMTK<DummyVM>,
+    mutators: Mutex<Vec<(ThreadId, *mut Mutator<DummyVM>)>>,
+    /// The index of the next mutator returned by `get_next_mutator()`.
+    next_mutator: AtomicUsize,
+    /// The number of mutators that have been bound, used to give each mutator a distinct `tls`.
+    bound_mutators: AtomicUsize,
+    roots: Mutex<Vec<Box<ObjectReference>>>,
+    gc_state: Mutex<GCState>,
+    gc_cond: Condvar,
+}
+
+// The mutators are only accessed by their thread, or by the GC when the mutators are stopped.
+unsafe impl Send for Instance {}
+unsafe impl Sync for Instance {}
+
+impl Instance {
+    fn new(mmtk: &'static MMTK<DummyVM>) -> Self {
+        Instance {
+            mmtk,
+            mutators: Mutex::new(vec![]),
+            next_mutator: AtomicUsize::new(0),
+            bound_mutators: AtomicUsize::new(0),
+            roots: Mutex::new(vec![]),
+            gc_state: Mutex::new(GCState { epoch: 0, blocked: 0 }),
+            gc_cond: Condvar::new(),
+        }
+    }
+
+    /// Create an MMTk instance with the given options (in the format of `MMTK::with_options()`), initialize
+    /// it with the heap size, and enable collection. The instance becomes the current instance of the calling thread.
+    pub fn create(options: &str, heap_size: usize) -> &'static Instance {
+        let mmtk: &'static mut MMTK<DummyVM> =
+            Box::leak(Box::new(MMTK::with_options(options).unwrap()));
+        let mmtk_ptr = mmtk as *mut MMTK<DummyVM>;
+        let instance: &'static Instance = Box::leak(Box::new(Instance::new(unsafe { &*mmtk_ptr })));
+        instance.enter();
+        memory_manager::gc_init(unsafe { &mut *mmtk_ptr }, heap_size);
+        memory_manager::enable_collection(instance.mmtk, VMThread::UNINITIALIZED);
+        instance
+    }
+
+    /// The current instance of the calling thread.
+    pub fn current() -> &'static Instance {
+        CURRENT.with(|current| current.get()).unwrap_or(&SINGLETON_INSTANCE)
+    }
+
+    /// Make this instance the current instance of the calling thread.
+    pub fn enter(&'static self) {
+        CURRENT.with(|current| current.set(Some(self)));
+    }
+
+    /// Is the calling thread a GC thread?
+    pub fn is_gc_thread() -> bool {
+        IS_GC_THREAD.with(|gc| gc.get())
+    }
+
+    pub fn mmtk(&self) -> &'static MMTK<DummyVM> {
+        self.mmtk
+    }
+
+    /// Bind a mutator for the calling thread.
+    pub fn bind_mutator(&self) -> &'static mut Mutator<DummyVM> {
+        let id = self.bound_mutators.fetch_add(1, Ordering::SeqCst) + 1;
+        let tls = VMMutatorThread(VMThread(OpaquePointer::from_address(unsafe {
+            Address::from_usize(id)
+        })));
+        let mutator = Box::leak(memory_manager::bind_mutator(self.mmtk, tls));
+        self.mutators
+            .lock()
+            .unwrap()
+            .push((thread::current().id(), mutator as *mut _));
+        mutator
+    }
+
+    /// Destroy a mutator bound by `bind_mutator()`.
+    pub fn destroy_mutator(&self, mutator: &'static mut Mutator<DummyVM>) {
+        let ptr = mutator as *mut Mutator<DummyVM>;
+        self.mutators.lock().unwrap().retain(|(_, m)| *m != ptr);
+        memory_manager::destroy_mutator(unsafe { Box::from_raw(ptr) });
+    }
+
+    pub fn number_of_mutators(&self) -> usize {
+        self.mutators.lock().unwrap().len()
+    }
+
+    pub fn mutator(&self, tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
+        let mutators = self.mutators.lock().unwrap();
+        let (_, mutator) = mutators
+            .iter()
+            .find(|(_, m)| unsafe { (**m).get_tls() } == tls)
+            .unwrap();
+        unsafe { &mut **mutator }
+    }
+
+    pub fn reset_mutator_iterator(&self) {
+        self.next_mutator.store(0, Ordering::SeqCst);
+    }
+
+    pub fn get_next_mutator(&self) -> Option<&'static mut Mutator<DummyVM>> {
+        let index = self.next_mutator.fetch_add(1, Ordering::SeqCst);
+        self.mutators
+            .lock()
+            .unwrap()
+            .get(index)
+            .map(|(_, m)| unsafe { &mut **m })
+    }
+
+    /// Allocate an object with `num_refs` null reference fields and `payload` bytes of payload. Objects that
+    /// are too large for the default allocator are allocated in the large object space.
+    pub fn alloc(
+        &self,
+        mutator: &mut Mutator<DummyVM>,
+        num_refs: usize,
+        payload: usize,
+        semantics: AllocationSemantics,
+    ) -> ObjectReference {
+        let size = object_model::object_size(num_refs, payload);
+        let semantics = if semantics == AllocationSemantics::Default
+            && size >= self.mmtk.get_plan().constraints().max_non_los_default_alloc_bytes
+        {
+            AllocationSemantics::Los
+        } else {
+            semantics
+        };
+        let addr = memory_manager::alloc(mutator, size, 8, 0, semantics);
+        assert!(!addr.is_zero());
+        let object = object_model::init_object(addr, size, num_refs);
+        memory_manager::post_alloc(mutator, object, size, semantics);
+        object
+    }
+
+    /// Store `target` in the `i`th reference field of `object`, and run the write barrier.
+    pub fn write_ref(
+        &self,
+        mutator: &mut Mutator<DummyVM>,
+        object: ObjectReference,
+        i: usize,
+        target: ObjectReference,
+    ) {
+        unsafe { object_model::ref_slot(object, i).store(target) };
+        mutator.record_modified_node(object);
+    }
+
+    /// Add a root that holds `object`, and return the index of the root.
+    pub fn add_root(&self, object: ObjectReference) -> usize {
+        let mut roots = self.roots.lock().unwrap();
+        roots.push(Box::new(object));
+        roots.len() - 1
+    }
+
+    /// The object that a root holds. The GC updates the root if the object is moved.
+    pub fn root(&self, index: usize) -> ObjectReference {
+        *self.roots.lock().unwrap()[index]
+    }
+
+    pub fn set_root(&self, index: usize, object: ObjectReference) {
+        *self.roots.lock().unwrap()[index] = object;
+    }
+
+    /// The slots of the non-null roots.
+    pub fn root_slots(&self) -> Vec<Address> {
+        self.roots
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|root| !root.is_null())
+            .map(|root| Address::from_ref(&**root))
+            .collect()
+    }
+
+    /// Trigger a GC from a mutator of the calling thread, and wait for it to finish.
+    pub fn gc(&self, mutator: &Mutator<DummyVM>) {
+        memory_manager::handle_user_collection_request(self.mmtk, mutator.get_tls());
+    }
+
+    /// The number of GCs that have finished.
+    pub fn gc_count(&self) -> usize {
+        self.gc_state.lock().unwrap().epoch
+    }
+
+    pub fn block_for_gc(&self) {
+        let mut state = self.gc_state.lock().unwrap();
+        let epoch = state.epoch;
+        state.blocked += 1;
+        self.gc_cond.notify_all();
+        while state.epoch == epoch {
+            state = self.gc_cond.wait(state).unwrap();
+        }
+        state.blocked -= 1;
+    }
+
+    /// Wait until every thread with a mutator is blocked for the GC.
+    pub fn stop_all_mutators(&self) {
+        let mut state = self.gc_state.lock().unwrap();
+        while state.blocked < self.mutator_threads() {
+            state = self.gc_cond.wait(state).unwrap();
+        }
+    }
+
+    pub fn resume_mutators(&self) {
+        let mut state = self.gc_state.lock().unwrap();
+        state.epoch += 1;
+        self.gc_cond.notify_all();
+    }
+
+    fn mutator_threads(&self) -> usize {
+        let mut threads: Vec<ThreadId> = vec![];
+        for (thread, _) in self.mutators.lock().unwrap().iter() {
+            if !threads.contains(thread) {
+                threads.push(*thread);
+            }
+        }
+        threads.len()
+    }
+
+    /// Spawn the GC controller thread (if `worker` is `None`) or a GC worker thread for this instance.
+    pub fn spawn_gc_thread(&'static self, worker: Option<&GCWorker<DummyVM>>) {
+        // The worker lives as long as the scheduler of the instance.
+        let worker = worker.map(|w| w as *const GCWorker<DummyVM> as usize);
+        thread::spawn(move || {
+            self.enter();
+            IS_GC_THREAD.with(|gc| gc.set(true));
+            let tls = VMWorkerThread(VMThread::UNINITIALIZED);
+            match worker {
+                None => memory_manager::start_control_collector(self.mmtk, tls),
+                Some(worker) => memory_manager::start_worker(
+                    tls,
+                    unsafe { &mut *(worker as *mut GCWorker<DummyVM>) },
+                    self.mmtk,
+                ),
+            }
+        });
+    }
+}
@@ -12,6 +12,7 @@ pub mod object_model;
 pub mod active_plan;
 pub mod reference_glue;
 pub mod api;
+pub mod instance;
 
 #[cfg(test)]
 mod tests;
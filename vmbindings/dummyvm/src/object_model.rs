@@ -1,4 +1,5 @@
-use mmtk::util::metadata::header_metadata::HeaderMetadataSpec;
+use mmtk::util::constants::BYTES_IN_WORD;
+use mmtk::util::metadata::header_metadata::{self, HeaderMetadataSpec};
 use mmtk::util::{Address, ObjectReference};
 use mmtk::vm::*;
 use mmtk::AllocationSemantics;
@@ -6,97 +7,177 @@ use mmtk::CopyContext;
 use std::sync::atomic::Ordering;
 use DummyVM;
 
+/// DummyVM objects start with a header of four words:
+/// * the forwarding word, which holds the forwarding pointer and the forwarding bits (the lowest two bits),
+/// * the GC word, which holds the mark and nursery bits for large objects,
+/// * the size of the object in bytes,
+/// * the number of reference fields.
+///
+/// The reference fields follow the header, and the rest of the object is payload.
+/// An object reference points to the start of the object.
+pub const HEADER_BYTES: usize = 4 * BYTES_IN_WORD;
+const GC_WORD_OFFSET: usize = BYTES_IN_WORD;
+const SIZE_OFFSET: usize = 2 * BYTES_IN_WORD;
+const NUM_REFS_OFFSET: usize = 3 * BYTES_IN_WORD;
+
+/// The size of an object with `num_refs` reference fields and `payload` bytes of payload.
+pub fn object_size(num_refs: usize, payload: usize) -> usize {
+    HEADER_BYTES + num_refs * BYTES_IN_WORD + mmtk::util::conversions::raw_align_up(payload, BYTES_IN_WORD)
+}
+
+/// Write the header of a new object of `size` bytes with `num_refs` null reference fields at `addr`.
+pub fn init_object(addr: Address, size: usize, num_refs: usize) -> ObjectReference {
+    debug_assert!(size >= object_size(num_refs, 0));
+    unsafe {
+        addr.store::<usize>(0);
+        (addr + GC_WORD_OFFSET).store::<usize>(0);
+        (addr + SIZE_OFFSET).store::<usize>(size);
+        (addr + NUM_REFS_OFFSET).store::<usize>(num_refs);
+        for i in 0..num_refs {
+            (addr + HEADER_BYTES + i * BYTES_IN_WORD).store::<usize>(0);
+        }
+        addr.to_object_reference()
+    }
+}
+
+/// The number of reference fields of an object.
+pub fn num_refs(object: ObjectReference) -> usize {
+    unsafe { (object.to_address() + NUM_REFS_OFFSET).load::<usize>() }
+}
+
+/// The address of the `i`th reference field of an object.
+pub fn ref_slot(object: ObjectReference, i: usize) -> Address {
+    debug_assert!(i < num_refs(object));
+    object.to_address() + HEADER_BYTES + i * BYTES_IN_WORD
+}
+
+/// Read the `i`th reference field of an object.
+pub fn get_ref(object: ObjectReference, i: usize) -> ObjectReference {
+    unsafe { ref_slot(object, i).load::<ObjectReference>() }
+}
+
+/// The address of the payload of an object.
+pub fn payload(object: ObjectReference) -> Address {
+    object.to_address() + HEADER_BYTES + num_refs(object) * BYTES_IN_WORD
+}
+
 pub struct VMObjectModel {}
 
 impl ObjectModel<DummyVM> for VMObjectModel {
-    const GLOBAL_LOG_BIT_SPEC: VMGlobalLogBitSpec = VMGlobalLogBitSpec::in_header(0);
+    const GLOBAL_LOG_BIT_SPEC: VMGlobalLogBitSpec = VMGlobalLogBitSpec::side_first();
     const LOCAL_FORWARDING_POINTER_SPEC: VMLocalForwardingPointerSpec = VMLocalForwardingPointerSpec::in_header(0);
     const LOCAL_FORWARDING_BITS_SPEC: VMLocalForwardingBitsSpec = VMLocalForwardingBitsSpec::in_header(0);
-    const LOCAL_MARK_BIT_SPEC: VMLocalMarkBitSpec = VMLocalMarkBitSpec::in_header(0);
-    const LOCAL_LOS_MARK_NURSERY_SPEC: VMLocalLOSMarkNurserySpec = VMLocalLOSMarkNurserySpec::in_header(0);
+    // Immix only supports a mark bit on the side.
+    const LOCAL_MARK_BIT_SPEC: VMLocalMarkBitSpec = VMLocalMarkBitSpec::side_first();
+    const LOCAL_LOS_MARK_NURSERY_SPEC: VMLocalLOSMarkNurserySpec =
+        VMLocalLOSMarkNurserySpec::in_header((GC_WORD_OFFSET * 8) as isize);
 
     fn load_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _mask: Option<usize>,
-        _atomic_ordering: Option<Ordering>,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        mask: Option<usize>,
+        atomic_ordering: Option<Ordering>,
     ) -> usize {
-        unimplemented!()
+        header_metadata::load_metadata(metadata_spec, object, mask, atomic_ordering)
     }
 
     fn store_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _val: usize,
-        _mask: Option<usize>,
-        _atomic_ordering: Option<Ordering>,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        val: usize,
+        mask: Option<usize>,
+        atomic_ordering: Option<Ordering>,
     ) {
-        unimplemented!()
+        header_metadata::store_metadata(metadata_spec, object, val, mask, atomic_ordering)
     }
 
     fn compare_exchange_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _old_val: usize,
-        _new_val: usize,
-        _mask: Option<usize>,
-        _success_order: Ordering,
-        _failure_order: Ordering,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        old_val: usize,
+        new_val: usize,
+        mask: Option<usize>,
+        success_order: Ordering,
+        failure_order: Ordering,
     ) -> bool {
-        unimplemented!()
+        header_metadata::compare_exchange_metadata(
+            metadata_spec,
+            object,
+            old_val,
+            new_val,
+            mask,
+            success_order,
+            failure_order,
+        )
     }
 
     fn fetch_add_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _val: usize,
-        _order: Ordering,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        val: usize,
+        order: Ordering,
     ) -> usize {
-        unimplemented!()
+        header_metadata::fetch_add_metadata(metadata_spec, object, val, order)
     }
 
     fn fetch_sub_metadata(
-        _metadata_spec: &HeaderMetadataSpec,
-        _object: ObjectReference,
-        _val: usize,
-        _order: Ordering,
+        metadata_spec: &HeaderMetadataSpec,
+        object: ObjectReference,
+        val: usize,
+        order: Ordering,
     ) -> usize {
-        unimplemented!()
+        header_metadata::fetch_sub_metadata(metadata_spec, object, val, order)
     }
 
     fn copy(
-        _from: ObjectReference,
-        _semantics: AllocationSemantics,
-        _copy_context: &mut impl CopyContext,
+        from: ObjectReference,
+        semantics: AllocationSemantics,
+        copy_context: &mut impl CopyContext,
     ) -> ObjectReference {
-        unimplemented!()
+        let bytes = Self::get_current_size(from);
+        let dst = copy_context.alloc_copy(from, bytes, BYTES_IN_WORD, 0, semantics);
+        let to = unsafe { dst.to_object_reference() };
+        Self::copy_to(from, to, dst);
+        copy_context.post_copy(to, Address::ZERO, bytes, semantics);
+        to
     }
 
-    fn copy_to(_from: ObjectReference, _to: ObjectReference, _region: Address) -> Address {
-        unimplemented!()
+    fn copy_to(from: ObjectReference, to: ObjectReference, _region: Address) -> Address {
+        let bytes = Self::get_current_size(from);
+        unsafe {
+            std::ptr::copy_nonoverlapping::<u8>(from.to_address().to_ptr(), to.to_address().to_mut_ptr(), bytes);
+            // The copy is not forwarded, even if the forwarding bits of the original object say it is being forwarded.
+            to.to_address().store::<usize>(0);
+        }
+        to.to_address() + bytes
     }
 
-    fn get_current_size(_object: ObjectReference) -> usize {
-        unimplemented!()
+    fn get_current_size(object: ObjectReference) -> usize {
+        unsafe { (object.to_address() + SIZE_OFFSET).load::<usize>() }
     }
 
-    fn get_reference_when_copied_to(_from: ObjectReference, _to: Address) -> ObjectReference {
-        unimplemented!()
+    fn get_reference_when_copied_to(_from: ObjectReference, to: Address) -> ObjectReference {
+        unsafe { to.to_object_reference() }
     }
 
     fn get_type_descriptor(_reference: ObjectReference) -> &'static [i8] {
         unimplemented!()
     }
 
-    fn object_start_ref(_object: ObjectReference) -> Address {
-        unimplemented!()
+    fn object_start_ref(object: ObjectReference) -> Address {
+        object.to_address()
     }
 
-    fn ref_to_address(_object: ObjectReference) -> Address {
-        unimplemented!()
+    fn ref_to_address(object: ObjectReference) -> Address {
+        object.to_address()
     }
 
-    fn dump_object(_object: ObjectReference) {
-        unimplemented!()
+    fn dump_object(object: ObjectReference) {
+        eprintln!(
+            "{}: size = {}, refs = {:?}",
+            object,
+            Self::get_current_size(object),
+            (0..num_refs(object)).map(|i| get_ref(object, i)).collect::<Vec<_>>()
+        );
     }
 }
@@ -1,32 +1,49 @@
 use mmtk::vm::Scanning;
 use mmtk::{TransitiveClosure, Mutator};
+use mmtk::memory_manager;
+use mmtk::plan::ObjectsClosure;
 use mmtk::util::ObjectReference;
 use mmtk::util::opaque_pointer::*;
 use mmtk::scheduler::*;
 use crate::DummyVM;
+use crate::instance::Instance;
+use crate::object_model;
 
 pub struct VMScanning {}
 
 impl Scanning<DummyVM> for VMScanning {
-    fn scan_objects<W: ProcessEdgesWork<VM=DummyVM>>(_objects: &[ObjectReference], _worker: &mut GCWorker<DummyVM>) {
-        unimplemented!()
+    fn scan_objects<W: ProcessEdgesWork<VM=DummyVM>>(objects: &[ObjectReference], worker: &mut GCWorker<DummyVM>) {
+        let mut closure = ObjectsClosure::<W>::new(Instance::current().mmtk(), vec![], worker);
+        for object in objects {
+            Self::scan_object(&mut closure, *object, VMWorkerThread(VMThread::UNINITIALIZED));
+        }
     }
     fn scan_thread_roots<W: ProcessEdgesWork<VM=DummyVM>>() {
-        unimplemented!()
+        // DummyVM mutators have no stacks. All the roots are VM specific roots.
     }
     fn scan_thread_root<W: ProcessEdgesWork<VM=DummyVM>>(_mutator: &'static mut Mutator<DummyVM>, _tls: VMWorkerThread) {
-        unimplemented!()
+        // DummyVM mutators have no stacks. All the roots are VM specific roots.
     }
     fn scan_vm_specific_roots<W: ProcessEdgesWork<VM=DummyVM>>() {
-        unimplemented!()
+        let instance = Instance::current();
+        for slots in instance.root_slots().chunks(W::CAPACITY) {
+            memory_manager::add_work_packet(
+                instance.mmtk(),
+                WorkBucketStage::Closure,
+                W::new(slots.to_vec(), true, instance.mmtk()),
+            );
+        }
     }
-    fn scan_object<T: TransitiveClosure>(_trace: &mut T, _object: ObjectReference, _tls: VMWorkerThread) {
-        unimplemented!()
+    fn scan_object<T: TransitiveClosure>(trace: &mut T, object: ObjectReference, _tls: VMWorkerThread) {
+        for i in 0..object_model::num_refs(object) {
+            if !object_model::get_ref(object, i).is_null() {
+                trace.process_edge(object_model::ref_slot(object, i));
+            }
+        }
     }
     fn notify_initial_thread_scan_complete(_partial_scan: bool, _tls: VMWorkerThread) {
-        unimplemented!()
     }
     fn supports_return_barrier() -> bool {
-        unimplemented!()
+        false
     }
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::util::{Address, VMLayout};
+use mmtk::AllocationSemantics;
+use std::process::Command;
+
+/// Set in the child process that uses the contiguous layout.
+const CONTIGUOUS_LAYOUT_ENV: &str = "MMTK_DUMMYVM_CONTIGUOUS_LAYOUT";
+
+// A heap below 32 GB, which a VM could refer to with compressed pointers.
+const HEAP_START: usize = 0x1_0000_0000;
+const HEAP_END: usize = 0x8_0000_0000;
+
+fn in_heap(addr: Address) -> bool {
+    addr.as_usize() >= HEAP_START && addr.as_usize() < HEAP_END
+}
+
+/// All the spaces of a plan are placed in the heap range of a contiguous layout, and the plan works there.
+fn run_in_contiguous_layout() {
+    let (start, end) = unsafe { (Address::from_usize(HEAP_START), Address::from_usize(HEAP_END)) };
+    memory_manager::set_vm_layout(VMLayout::contiguous(start, end));
+    assert_eq!(memory_manager::starting_heap_address(), start);
+    assert_eq!(memory_manager::last_heap_address(), end);
+
+    for plan in &["SemiSpace", "GenCopy"] {
+        let instance = Instance::create(&format!("plan={}", plan), 20 * 1024 * 1024);
+        let mutator = instance.bind_mutator();
+        let head = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+        let root = instance.add_root(head);
+        let mut tail = head;
+        for i in 0..1000usize {
+            let next = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+            unsafe { object_model::payload(next).store(i) };
+            instance.write_ref(mutator, tail, 0, next);
+            tail = next;
+        }
+        let large = instance.alloc(mutator, 0, 64 * 1024, AllocationSemantics::Los);
+        assert!(in_heap(large.to_address()));
+        instance.gc(mutator);
+        instance.gc(mutator);
+
+        let mut object = object_model::get_ref(instance.root(root), 0);
+        for i in 0..1000usize {
+            assert!(in_heap(object.to_address()), "{}: {}", plan, object);
+            assert_eq!(unsafe { object_model::payload(object).load::<usize>() }, i);
+            object = object_model::get_ref(object, 0);
+        }
+        for stats in memory_manager::space_stats(instance.mmtk()) {
+            for (region_start, region_end) in stats.regions {
+                assert!(
+                    region_start >= start && region_end <= end,
+                    "{}: {} [{}, {})",
+                    plan,
+                    stats.name,
+                    region_start,
+                    region_end
+                );
+            }
+        }
+    }
+}
+
+#[test]
+pub fn contiguous_layout() {
+    if std::env::var(CONTIGUOUS_LAYOUT_ENV).is_ok() {
+        run_in_contiguous_layout();
+        return;
+    }
+
+    // The layout can only be set once in a process, before any other test uses it, so we set it in a child
+    // process that only runs this test.
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(&["--exact", "tests::contiguous_layout::contiguous_layout", "--nocapture", "--test-threads=1"])
+        .env(CONTIGUOUS_LAYOUT_ENV, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::AllocationSemantics;
+
+const HEAP_SIZE: usize = 20 * 1024 * 1024;
+const OBJECT_BYTES: usize = 64;
+
+/// Keep a list of objects alive, and fill half of the heap with garbage before a GC. After the GC, allocate
+/// as much again. This only works without another GC if the garbage is swept and reused.
+fn allocate_after_lazy_sweep(options: &str) {
+    let instance = Instance::create(options, HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+    let payload = OBJECT_BYTES - object_model::object_size(1, 0);
+
+    let head = instance.alloc(mutator, 1, payload, AllocationSemantics::Default);
+    let root = instance.add_root(head);
+    let mut tail = head;
+    for i in 0..(HEAP_SIZE / 2 / OBJECT_BYTES) {
+        if i % 16 == 0 {
+            let next = instance.alloc(mutator, 1, payload, AllocationSemantics::Default);
+            unsafe { object_model::payload(next).store(i) };
+            instance.write_ref(mutator, tail, 0, next);
+            tail = next;
+        } else {
+            instance.alloc(mutator, 1, payload, AllocationSemantics::Default);
+        }
+    }
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 1);
+
+    // The garbage is swept as we allocate, so we do not need another GC.
+    for _ in 0..(HEAP_SIZE / 2 / OBJECT_BYTES) {
+        instance.alloc(mutator, 1, payload, AllocationSemantics::Default);
+    }
+    assert_eq!(instance.gc_count(), 1);
+
+    // The chunks that are not swept yet are swept before the next GC marks.
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 2);
+    let mut object = object_model::get_ref(instance.root(root), 0);
+    for i in (0..(HEAP_SIZE / 2 / OBJECT_BYTES)).step_by(16) {
+        assert_eq!(unsafe { object_model::payload(object).load::<usize>() }, i);
+        object = object_model::get_ref(object, 0);
+    }
+    assert!(object.is_null());
+}
+
+#[test]
+pub fn lazy_sweep_basic() {
+    allocate_after_lazy_sweep("plan=MarkSweep,lazy_sweep=true");
+    allocate_after_lazy_sweep("plan=Immix,lazy_sweep=true,eager_complete_sweep=true");
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::AllocationSemantics;
+use mmtk::MMTK;
+
+#[test]
+pub fn lazy_sweep_options() {
+    let mmtk = Box::into_raw(Box::new(
+        MMTK::<DummyVM>::with_options("plan=MarkSweep,lazy_sweep=true,eager_complete_sweep=true")
+            .unwrap(),
+    ));
+    memory_manager::gc_init(unsafe { &mut *mmtk }, 20 * 1024 * 1024);
+    let mmtk = unsafe { &*mmtk };
+
+    // Nothing is queued for sweeping before the first GC, so allocation works as usual.
+    let mut mutator = memory_manager::bind_mutator(mmtk, VMMutatorThread(VMThread::UNINITIALIZED));
+    for _ in 0..100 {
+        let addr = memory_manager::alloc(&mut mutator, 64, 8, 0, AllocationSemantics::Default);
+        assert!(!addr.is_zero());
+    }
+}
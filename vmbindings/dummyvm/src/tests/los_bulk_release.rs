@@ -0,0 +1,55 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::util::constants::BYTES_IN_PAGE;
+use mmtk::AllocationSemantics;
+
+// Large enough for each object to get a cell of its own.
+const PAYLOAD_BYTES: usize = 300 * 1024;
+const OBJECTS: usize = 6;
+
+fn los_committed_pages(instance: &Instance) -> usize {
+    memory_manager::space_stats(instance.mmtk())
+        .into_iter()
+        .find(|stats| stats.name == "los")
+        .unwrap()
+        .committed_pages
+}
+
+/// A GC releases the cells of the dead large objects with `FreeListPageResource::release_pages_bulk()`. The
+/// adjacent dead cells are released as one run, so a larger object fits in them.
+#[test]
+pub fn los_bulk_release() {
+    let instance = Instance::create("plan=MarkSweep", 64 * 1024 * 1024);
+    let mutator = instance.bind_mutator();
+
+    let objects: Vec<_> = (0..OBJECTS)
+        .map(|i| {
+            let object = instance.alloc(mutator, 0, PAYLOAD_BYTES, AllocationSemantics::Los);
+            unsafe { object_model::payload(object).store(i) };
+            object
+        })
+        .collect();
+    let cell_bytes = objects[1].to_address() - objects[0].to_address();
+    for pair in objects.windows(2) {
+        assert_eq!(pair[1].to_address() - pair[0].to_address(), cell_bytes);
+    }
+    // The first and the last objects survive. The objects in between are released as one run.
+    let first = instance.add_root(objects[0]);
+    let last = instance.add_root(objects[OBJECTS - 1]);
+    let committed = los_committed_pages(&instance);
+
+    instance.gc(mutator);
+    let dead = OBJECTS - 2;
+    assert_eq!(los_committed_pages(&instance), committed - dead * cell_bytes / BYTES_IN_PAGE);
+    assert_eq!(instance.root(first), objects[0]);
+    assert_eq!(instance.root(last), objects[OBJECTS - 1]);
+    assert_eq!(unsafe { object_model::payload(objects[0]).load::<usize>() }, 0);
+    assert_eq!(unsafe { object_model::payload(objects[OBJECTS - 1]).load::<usize>() }, OBJECTS - 1);
+
+    // The most recently freed block is found first, so an object as large as the whole run is allocated in it.
+    let size = dead * cell_bytes - 8;
+    let object = instance.alloc(mutator, 0, size - object_model::HEADER_BYTES, AllocationSemantics::Los);
+    assert_eq!(object.to_address(), objects[1].to_address());
+    assert_eq!(los_committed_pages(&instance), committed);
+}
\ No newline at end of file
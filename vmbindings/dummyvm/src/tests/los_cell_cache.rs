@@ -0,0 +1,53 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::util::constants::BYTES_IN_PAGE;
+use mmtk::AllocationSemantics;
+
+// The cells of these objects are rounded up to a cached size class of 80 pages.
+const PAYLOAD_BYTES: usize = 300 * 1024;
+const CELL_PAGES: usize = 80;
+
+fn los_pages(instance: &Instance) -> (usize, usize) {
+    let stats = memory_manager::space_stats(instance.mmtk())
+        .into_iter()
+        .find(|stats| stats.name == "los")
+        .unwrap();
+    (stats.reserved_pages, stats.committed_pages)
+}
+
+/// The cell of a dead large object is cached by a GC, and reused by the next object of the same size class.
+/// Cached cells are not counted as used, and a cell that is not reused is released by the next GC.
+#[test]
+pub fn los_cell_cache() {
+    let instance = Instance::create("plan=MarkSweep,los_cache_pages=1024", 64 * 1024 * 1024);
+    let mutator = instance.bind_mutator();
+
+    let survivor = instance.alloc(mutator, 0, PAYLOAD_BYTES, AllocationSemantics::Los);
+    let root = instance.add_root(survivor);
+    let dead = instance.alloc(mutator, 0, PAYLOAD_BYTES, AllocationSemantics::Los);
+    let last_word = object_model::payload(dead) + PAYLOAD_BYTES - 8;
+    unsafe { last_word.store(0xdead_usize) };
+    let (reserved, committed) = los_pages(&instance);
+
+    // The cell of the dead object is cached: it is still committed, but not used.
+    instance.gc(mutator);
+    assert_eq!(instance.root(root), survivor);
+    assert_eq!(los_pages(&instance), (reserved - CELL_PAGES, committed));
+
+    // The cached cell is reused, and zeroed.
+    let reused = instance.alloc(mutator, 0, PAYLOAD_BYTES - 1024, AllocationSemantics::Los);
+    assert_eq!(reused.to_address(), dead.to_address());
+    assert_eq!(unsafe { last_word.load::<usize>() }, 0);
+    assert_eq!(los_pages(&instance), (reserved, committed));
+
+    // The reused cell is freed and cached again, and released by the next GC as it is not reused.
+    instance.gc(mutator);
+    assert_eq!(los_pages(&instance), (reserved - CELL_PAGES, committed));
+    instance.gc(mutator);
+    assert_eq!(
+        los_pages(&instance),
+        (reserved - CELL_PAGES, committed - CELL_PAGES)
+    );
+    assert_eq!(instance.root(root), survivor);
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::AllocationSemantics;
+
+const HEAP_SIZE: usize = 64 * 1024 * 1024;
+// Larger than the largest object in the nursery, so the objects are allocated in the large object space.
+const PAYLOAD_BYTES: usize = 80 * 1024;
+const OBJECTS: usize = 30;
+
+fn los_reserved_pages(instance: &Instance) -> usize {
+    memory_manager::space_stats(instance.mmtk())
+        .into_iter()
+        .find(|stats| stats.name == "los")
+        .unwrap()
+        .reserved_pages
+}
+
+/// Young large objects are allocated in blocks that are released in bulk. A nursery GC releases the blocks
+/// of the dead young objects, and keeps the block of a survivor. A full-heap GC releases that block when
+/// the survivor dies.
+#[test]
+pub fn los_nursery() {
+    let instance = Instance::create("plan=GenCopy", HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+
+    let mut survivor = None;
+    for i in 0..OBJECTS {
+        let object = instance.alloc(mutator, 0, PAYLOAD_BYTES, AllocationSemantics::Default);
+        if i == OBJECTS / 3 {
+            unsafe { object_model::payload(object).store(42usize) };
+            survivor = Some(object);
+        }
+    }
+    let survivor = survivor.unwrap();
+    let root = instance.add_root(survivor);
+    let all_young = los_reserved_pages(instance);
+
+    // A nursery GC
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 1);
+    let promoted = los_reserved_pages(instance);
+    assert!(promoted > 0);
+    assert!(promoted < all_young / 4, "{} of {} pages are kept", promoted, all_young);
+    // The survivor is promoted in place.
+    assert_eq!(instance.root(root), survivor);
+    assert_eq!(unsafe { object_model::payload(survivor).load::<usize>() }, 42);
+
+    // A full-heap GC keeps the promoted object, and releases the new young objects.
+    assert!(memory_manager::process(instance.mmtk(), "full_heap_system_gc", "true"));
+    for _ in 0..OBJECTS {
+        instance.alloc(mutator, 0, PAYLOAD_BYTES, AllocationSemantics::Default);
+    }
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 2);
+    assert_eq!(los_reserved_pages(instance), promoted);
+    assert_eq!(unsafe { object_model::payload(survivor).load::<usize>() }, 42);
+
+    // When the promoted object dies, its block is released.
+    let small = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    instance.set_root(root, small);
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 3);
+    assert!(los_reserved_pages(instance) < promoted);
+}
\ No newline at end of file
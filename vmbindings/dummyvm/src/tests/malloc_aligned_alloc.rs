@@ -0,0 +1,32 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::AllocationSemantics;
+use mmtk::MMTK;
+
+#[test]
+pub fn malloc_alloc_honours_alignment_and_offset() {
+    let mmtk = Box::into_raw(Box::new(
+        MMTK::<DummyVM>::with_options("plan=MarkSweep").unwrap(),
+    ));
+    memory_manager::gc_init(unsafe { &mut *mmtk }, 20 * 1024 * 1024);
+    let mmtk = unsafe { &*mmtk };
+
+    let mut mutator = memory_manager::bind_mutator(mmtk, VMMutatorThread(VMThread::UNINITIALIZED));
+    for align in [8usize, 16, 32, 64, 128].iter() {
+        for offset in [0isize, 8, 24].iter() {
+            let addr =
+                memory_manager::alloc(&mut mutator, 40, *align, *offset, AllocationSemantics::Default);
+            assert!(!addr.is_zero());
+            assert!(
+                (addr + *offset as usize).is_aligned_to(*align),
+                "{} + {} is not aligned to {}",
+                addr,
+                offset,
+                align
+            );
+            let object = unsafe { addr.to_object_reference() };
+            memory_manager::post_alloc(&mut mutator, object, 40, AllocationSemantics::Default);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::instance::Instance;
+use crate::object_model;
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::ObjectReference;
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+
+const OBJECTS: usize = 600;
+// Alignments and offsets that need an over-sized malloc region, and one that does not.
+const ALIGNMENTS: [(usize, isize); 4] = [(32, 0), (64, 8), (128, 24), (8, 0)];
+
+/// Allocate an object with one reference field, whose start plus `offset` is aligned to `align`.
+fn alloc_aligned(mutator: &mut Mutator<DummyVM>, payload: usize, align: usize, offset: isize) -> ObjectReference {
+    let size = object_model::object_size(1, payload);
+    let addr = memory_manager::alloc(mutator, size, align, offset, AllocationSemantics::Default);
+    assert!((addr + offset as usize).is_aligned_to(align));
+    let object = object_model::init_object(addr, size, 1);
+    memory_manager::post_alloc(mutator, object, size, AllocationSemantics::Default);
+    object
+}
+
+/// Allocate objects with various alignments and offsets, and keep every third object alive in a list.
+fn alloc_objects(instance: &Instance, mutator: &mut Mutator<DummyVM>, root: usize) {
+    let mut tail = instance.root(root);
+    while !object_model::get_ref(tail, 0).is_null() {
+        tail = object_model::get_ref(tail, 0);
+    }
+    for i in 0..OBJECTS {
+        let (align, offset) = ALIGNMENTS[i % ALIGNMENTS.len()];
+        let object = alloc_aligned(mutator, 8 + (i % 7) * 40, align, offset);
+        unsafe { object_model::payload(object).store(i) };
+        if i % 3 == 0 {
+            instance.write_ref(mutator, tail, 0, object);
+            tail = object;
+        }
+    }
+}
+
+/// Check the objects in the list.
+fn check_objects(instance: &Instance, root: usize, batches: usize) {
+    let mut object = object_model::get_ref(instance.root(root), 0);
+    for _ in 0..batches {
+        for i in (0..OBJECTS).step_by(3) {
+            let (align, offset) = ALIGNMENTS[i % ALIGNMENTS.len()];
+            assert!((object.to_address() + offset as usize).is_aligned_to(align));
+            assert_eq!(unsafe { object_model::payload(object).load::<usize>() }, i);
+            object = object_model::get_ref(object, 0);
+        }
+    }
+    assert!(object.is_null());
+}
+
+/// A GC frees the dead objects that were allocated from over-sized malloc regions with the pointer that malloc
+/// returned, and skips the whole region of each live object when it sweeps a chunk.
+#[test]
+pub fn malloc_offset_sweep() {
+    let instance = Instance::create("plan=MarkSweep", 20 * 1024 * 1024);
+    let mutator = instance.bind_mutator();
+    let head = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+    let root = instance.add_root(head);
+
+    alloc_objects(instance, mutator, root);
+    let used = memory_manager::used_bytes(instance.mmtk());
+    instance.gc(mutator);
+    assert!(memory_manager::used_bytes(instance.mmtk()) < used);
+    check_objects(instance, root, 1);
+
+    // The live objects are swept again, with the new objects around them.
+    alloc_objects(instance, mutator, root);
+    instance.gc(mutator);
+    check_objects(instance, root, 2);
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+use mmtk::util::Address;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::memory;
+use crate::DummyVM;
+
+#[test]
+pub fn test_mmap_conflict_diagnostics() {
+    let start = unsafe { Address::from_usize(0x100_0000 )};
+    let one_megabyte = 1000000;
+    let mmap1_res = memory::dzmmap_noreplace(start, one_megabyte);
+    assert!(mmap1_res.is_ok());
+
+    let panic_res = std::panic::catch_unwind(|| {
+        let mmap2_res = memory::dzmmap_noreplace(start, one_megabyte);
+        assert!(mmap2_res.is_err());
+        memory::handle_mmap_error_in_ranges::<DummyVM>(mmap2_res.err().unwrap(), VMThread::UNINITIALIZED, &[(start, one_megabyte)]);
+    });
+
+    // The error should match the error message in memory::handle_mmap_error_in_ranges()
+    assert!(panic_res.is_err());
+    let err = panic_res.err().unwrap();
+    assert!(err.is::<String>());
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(msg.starts_with("Failed to mmap, the address is already mapped. Should MMTk quanrantine the address range first?"));
+    // The message lists the conflicting mapping from /proc/self/maps, which is our first mmap.
+    if cfg!(target_os = "linux") {
+        let mapping = format!("\n{:08x}-", start.as_usize());
+        assert!(msg.contains(&mapping), "{} is not in the conflicting mappings:\n{}", mapping, msg);
+    }
+}
\ No newline at end of file
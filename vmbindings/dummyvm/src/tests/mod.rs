@@ -3,4 +3,28 @@
 // setup/teardown procedure for MMTk instances.
 mod issue139;
 mod handle_mmap_oom;
-mod handle_mmap_conflict;
\ No newline at end of file
+mod handle_mmap_conflict;
+mod multiple_instances;
+mod vm_region_load;
+mod side_metadata_stats;
+mod native_mark_sweep_alloc;
+mod malloc_aligned_alloc;
+mod lazy_sweep_options;
+mod verify_heap;
+mod pretouch_heap;
+mod vm_region_roots;
+mod lazy_sweep_basic;
+mod native_mark_sweep_gc;
+mod los_nursery;
+mod page_protect_moving;
+mod verify_remset;
+mod stress_gc;
+mod contiguous_layout;
+mod los_bulk_release;
+mod los_cell_cache;
+mod poison_on_release;
+mod protect_on_release;
+mod malloc_offset_sweep;
+mod total_bytes_before_init;
+mod mmap_conflict_diagnostics;
+mod space_stats;
\ No newline at end of file
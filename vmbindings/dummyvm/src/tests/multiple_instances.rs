@@ -0,0 +1,100 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::util::Address;
+use mmtk::AllocationSemantics;
+use std::sync::{Arc, Barrier};
+
+const PLANS: [&str; 2] = ["plan=SemiSpace", "plan=Immix"];
+const OBJECTS: usize = 1000;
+
+/// Check the list of objects from a root, and return the addresses of the objects.
+fn check_list(instance: &Instance, root: usize, tag: usize) -> Vec<Address> {
+    let mut addrs = vec![];
+    let mut object = instance.root(root);
+    for i in 0..OBJECTS {
+        assert_eq!(unsafe { object_model::payload(object).load::<usize>() }, tag + i);
+        addrs.push(object.to_address());
+        object = object_model::get_ref(object, 0);
+    }
+    assert!(object.is_null());
+    addrs
+}
+
+#[test]
+pub fn multiple_instances_with_different_plans() {
+    // Instances need to be created and initialized one at a time.
+    let instances: Vec<&'static Instance> = PLANS
+        .iter()
+        .map(|options| Instance::create(options, 20 * 1024 * 1024))
+        .collect();
+
+    // Both heaps allocate at the same time. Then each heap runs a GC in turn, while the other heap checks its objects.
+    let barrier = Arc::new(Barrier::new(instances.len()));
+    let threads: Vec<_> = instances
+        .iter()
+        .enumerate()
+        .map(|(index, &instance)| {
+            let barrier = barrier.clone();
+            std::thread::spawn(move || {
+                instance.enter();
+                let mutator = instance.bind_mutator();
+                // Each instance stores different values in its objects.
+                let tag = index * OBJECTS;
+                let head = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+                unsafe { object_model::payload(head).store(tag) };
+                let root = instance.add_root(head);
+                let mut tail = head;
+                for i in 1..OBJECTS {
+                    let object = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+                    unsafe { object_model::payload(object).store(tag + i) };
+                    instance.write_ref(mutator, tail, 0, object);
+                    tail = object;
+                }
+                let mut addrs = check_list(instance, root, tag);
+
+                for gc_index in 0..PLANS.len() {
+                    barrier.wait();
+                    if gc_index == index {
+                        instance.gc(mutator);
+                    }
+                    barrier.wait();
+                    // A GC in the other heap does not touch the objects of this heap.
+                    let after = check_list(instance, root, tag);
+                    if gc_index != index {
+                        assert_eq!(after, addrs);
+                    }
+                    addrs = after;
+                }
+                assert_eq!(instance.gc_count(), 1);
+                addrs
+            })
+        })
+        .collect();
+    let results: Vec<Vec<Address>> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+
+    // The spaces of the two heaps do not overlap.
+    let ranges: Vec<Vec<(Address, Address)>> = instances
+        .iter()
+        .map(|instance| {
+            memory_manager::space_stats(instance.mmtk())
+                .into_iter()
+                .flat_map(|stats| stats.regions)
+                .collect()
+        })
+        .collect();
+    for (a, b) in ranges[0]
+        .iter()
+        .flat_map(|a| ranges[1].iter().map(move |b| (a, b)))
+    {
+        assert!(a.1 <= b.0 || b.1 <= a.0);
+    }
+    // The objects that survived the GCs are in the spaces of their heap.
+    for (i, addrs) in results.iter().enumerate() {
+        for addr in addrs {
+            assert!(ranges[i]
+                .iter()
+                .any(|&(start, end)| start <= *addr && *addr < end));
+        }
+    }
+}
\ No newline at end of file
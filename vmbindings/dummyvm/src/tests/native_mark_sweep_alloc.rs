@@ -0,0 +1,36 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::AllocationSemantics;
+use mmtk::MMTK;
+
+#[test]
+pub fn native_mark_sweep_allocates_disjoint_cells() {
+    let mmtk = Box::into_raw(Box::new(
+        MMTK::<DummyVM>::with_options("plan=NativeMarkSweep").unwrap(),
+    ));
+    memory_manager::gc_init(unsafe { &mut *mmtk }, 20 * 1024 * 1024);
+    let mmtk = unsafe { &*mmtk };
+
+    let mut mutator = memory_manager::bind_mutator(mmtk, VMMutatorThread(VMThread::UNINITIALIZED));
+    let mut allocated = vec![];
+    for size in [16usize, 24, 40, 100, 1000, 4000].iter().cycle().take(600) {
+        let addr = memory_manager::alloc(&mut mutator, *size, 8, 0, AllocationSemantics::Default);
+        assert!(!addr.is_zero());
+        assert!(addr.is_aligned_to(8));
+        // A new cell is zeroed, including the word used to link free cells.
+        assert_eq!(unsafe { addr.load::<usize>() }, 0);
+        allocated.push((addr, *size));
+    }
+
+    // No two allocations overlap.
+    allocated.sort();
+    for pair in allocated.windows(2) {
+        assert!(pair[0].0 + pair[0].1 <= pair[1].0);
+    }
+
+    // Objects are allocated in the mark-sweep space.
+    let stats = memory_manager::side_metadata_stats(mmtk);
+    let spec = stats.iter().find(|s| s.name == "MSBlockFreeList").unwrap();
+    assert!(spec.committed_bytes > 0);
+}
@@ -0,0 +1,73 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::util::ObjectReference;
+use mmtk::AllocationSemantics;
+use std::collections::HashSet;
+
+const LIST_LENGTH: usize = 2000;
+
+/// Check that the list from `head` holds `0..LIST_LENGTH` in order.
+fn check_list(head: ObjectReference) {
+    let mut object = head;
+    for i in 0..LIST_LENGTH {
+        assert_eq!(unsafe { object_model::payload(object).load::<usize>() }, i);
+        object = object_model::get_ref(object, 0);
+    }
+    assert!(object.is_null());
+}
+
+#[test]
+pub fn native_mark_sweep_gc() {
+    let instance = Instance::create("plan=NativeMarkSweep", 20 * 1024 * 1024);
+    let mmtk = instance.mmtk();
+    let mutator = instance.bind_mutator();
+
+    // A list of live objects with dead objects of the same size class in between. The blocks of this
+    // size class are swept, and the dead cells are put back to the free lists.
+    let head = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+    let root = instance.add_root(head);
+    let mut live = HashSet::new();
+    let mut dead = HashSet::new();
+    let mut tail = head;
+    for i in 0..LIST_LENGTH {
+        if i > 0 {
+            let next = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+            instance.write_ref(mutator, tail, 0, next);
+            tail = next;
+        }
+        unsafe { object_model::payload(tail).store(i) };
+        live.insert(tail);
+        for _ in 0..3 {
+            dead.insert(instance.alloc(mutator, 1, 8, AllocationSemantics::Default));
+        }
+    }
+    // Dead objects of another size class only. Their blocks are released.
+    for _ in 0..2000 {
+        instance.alloc(mutator, 0, 1000, AllocationSemantics::Default);
+    }
+
+    let used_before = memory_manager::used_bytes(mmtk);
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 1);
+    assert!(memory_manager::used_bytes(mmtk) < used_before);
+    assert_eq!(instance.root(root), head);
+    check_list(head);
+
+    // New objects take the cells of dead objects, and never the cells of live objects.
+    let mut reused = 0;
+    for _ in 0..LIST_LENGTH {
+        let object = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+        assert!(!live.contains(&object));
+        if dead.contains(&object) {
+            reused += 1;
+        }
+    }
+    assert!(reused > 0);
+    check_list(head);
+
+    // The mark bits are cleared by the sweep, so the list survives another GC.
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 2);
+    check_list(head);
+}
\ No newline at end of file
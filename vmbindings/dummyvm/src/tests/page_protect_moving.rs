@@ -0,0 +1,61 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::AllocationSemantics;
+
+const HEAP_SIZE: usize = 20 * 1024 * 1024;
+const TAG: usize = 0b10;
+
+fn tagged(object: ObjectReference) -> ObjectReference {
+    unsafe { (object.to_address() + TAG).to_object_reference() }
+}
+
+/// Find the permissions of the mapping that contains an address in /proc/self/maps.
+fn permissions(addr: Address) -> String {
+    let maps = std::fs::read_to_string("/proc/self/maps").unwrap();
+    for line in maps.lines() {
+        let mut fields = line.split_whitespace();
+        let mut range = fields.next().unwrap().split('-');
+        let start = usize::from_str_radix(range.next().unwrap(), 16).unwrap();
+        let end = usize::from_str_radix(range.next().unwrap(), 16).unwrap();
+        if (start..end).contains(&addr.as_usize()) {
+            return fields.next().unwrap().to_string();
+        }
+    }
+    panic!("{} is not mapped", addr)
+}
+
+/// A moving PageProtect GC copies every reachable object, updates all the references to it (keeping the tag bits
+/// of tagged references), and protects the pages of the old objects.
+#[test]
+pub fn page_protect_moving() {
+    let instance = Instance::create("plan=PageProtect,page_protect_moving=true", HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+
+    let parent = instance.alloc(mutator, 2, 8, AllocationSemantics::Default);
+    let child = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    unsafe { object_model::payload(child).store(42usize) };
+    instance.write_ref(mutator, parent, 0, child);
+    instance.write_ref(mutator, parent, 1, tagged(child));
+    let root = instance.add_root(parent);
+    let tagged_root = instance.add_root(tagged(parent));
+
+    for _ in 0..3 {
+        let old_parent = instance.root(root);
+        let old_child = object_model::get_ref(old_parent, 0);
+        instance.gc(mutator);
+
+        let parent = instance.root(root);
+        let child = object_model::get_ref(parent, 0);
+        assert_ne!(parent, old_parent);
+        assert_ne!(child, old_child);
+        assert_eq!(instance.root(tagged_root), tagged(parent));
+        assert_eq!(object_model::get_ref(parent, 1), tagged(child));
+        assert_eq!(unsafe { object_model::payload(child).load::<usize>() }, 42);
+
+        // The pages of the old objects are protected, and the copies are accessible.
+        assert!(permissions(old_parent.to_address()).starts_with("---"));
+        assert!(permissions(old_child.to_address()).starts_with("---"));
+        assert!(permissions(parent.to_address()).starts_with("rw"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::util::memory::POISON_PATTERN;
+use mmtk::util::Address;
+use mmtk::AllocationSemantics;
+
+const HEAP_SIZE: usize = 64 * 1024 * 1024;
+
+fn is_poisoned(addr: Address) -> bool {
+    unsafe { addr.load::<u32>() == POISON_PATTERN }
+}
+
+/// Allocate a survivor and a dead object of `payload` bytes, run a GC, and check that the word at `offset` in the
+/// payload of the dead object is poisoned while the survivor is intact.
+fn check_dead_object_poisoned(options: &str, payload: usize, semantics: AllocationSemantics, offset: usize) {
+    let instance = Instance::create(&format!("{},poison_on_release=true", options), HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+
+    let survivor = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    unsafe { object_model::payload(survivor).store(42usize) };
+    let root = instance.add_root(survivor);
+    let dead = instance.alloc(mutator, 0, payload, semantics);
+    let word = object_model::payload(dead) + offset;
+    unsafe { word.store(1usize) };
+    assert!(!is_poisoned(word));
+
+    instance.gc(mutator);
+    assert_eq!(unsafe { object_model::payload(instance.root(root)).load::<usize>() }, 42);
+    assert!(is_poisoned(word), "{}: {} is not poisoned", options, word);
+}
+
+/// Each policy poisons the memory of dead objects when it releases it.
+#[test]
+pub fn poison_on_release() {
+    // The from-space of a copying space (MonotonePageResource).
+    check_dead_object_poisoned("plan=SemiSpace", 64, AllocationSemantics::Default, 32);
+    // The free lines of an Immix block that is kept for its live lines (or the whole block if it is released).
+    check_dead_object_poisoned("plan=Immix", 2048, AllocationSemantics::Default, 1024);
+    // Large object cells that are released to the FreeListPageResource, or cached for reuse.
+    check_dead_object_poisoned("plan=MarkSweep", 300 * 1024, AllocationSemantics::Los, 1024);
+    check_dead_object_poisoned("plan=MarkSweep,los_cache_pages=1024", 300 * 1024, AllocationSemantics::Los, 1024);
+    // The cells freed by MallocSpace. We read past the start of the cell, which the allocator may reuse for its free lists.
+    check_dead_object_poisoned("plan=MarkSweep", 1024, AllocationSemantics::Default, 512);
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::MMTK;
+
+#[cfg(target_os = "linux")]
+fn resident_bytes() -> usize {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    let line = status.lines().find(|line| line.starts_with("VmRSS:")).unwrap();
+    line.split_whitespace().nth(1).unwrap().parse::<usize>().unwrap() * 1024
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn pretouch_heap_touches_heap_size_in_total() {
+    const HEAP_SIZE: usize = 64 * 1024 * 1024;
+    let mmtk = Box::into_raw(Box::new(
+        MMTK::<DummyVM>::with_options("plan=SemiSpace,pretouch_heap=true").unwrap(),
+    ));
+    let before = resident_bytes();
+    memory_manager::gc_init(unsafe { &mut *mmtk }, HEAP_SIZE);
+    let touched = resident_bytes() - before;
+
+    // SemiSpace has four contiguous spaces (the two copy spaces, the immortal space and the large object space).
+    // They share the heap size, plus the side metadata for the touched pages.
+    assert!(touched >= HEAP_SIZE * 3 / 4, "touched {} bytes", touched);
+    assert!(touched < HEAP_SIZE * 3 / 2, "touched {} bytes", touched);
+}
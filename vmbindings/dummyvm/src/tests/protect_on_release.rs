@@ -0,0 +1,76 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::util::Address;
+use mmtk::AllocationSemantics;
+
+const HEAP_SIZE: usize = 64 * 1024 * 1024;
+// Large enough for the large object space.
+const LARGE_PAYLOAD_BYTES: usize = 300 * 1024;
+
+/// Find the permissions of the mapping that contains an address in /proc/self/maps.
+fn permissions(addr: Address) -> String {
+    let maps = std::fs::read_to_string("/proc/self/maps").unwrap();
+    for line in maps.lines() {
+        let mut fields = line.split_whitespace();
+        let mut range = fields.next().unwrap().split('-');
+        let start = usize::from_str_radix(range.next().unwrap(), 16).unwrap();
+        let end = usize::from_str_radix(range.next().unwrap(), 16).unwrap();
+        if (start..end).contains(&addr.as_usize()) {
+            return fields.next().unwrap().to_string();
+        }
+    }
+    panic!("{} is not mapped", addr)
+}
+
+fn is_protected(addr: Address) -> bool {
+    permissions(addr).starts_with("---")
+}
+
+/// The nursery of a generational plan is a contiguous copying space. Its pages are protected by a nursery GC, and
+/// unprotected when they are allocated again.
+fn check_nursery() {
+    let instance = Instance::create("plan=GenCopy,protect_on_release=true", HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+
+    let dead = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    let survivor = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    unsafe { object_model::payload(survivor).store(42usize) };
+    let root = instance.add_root(survivor);
+    instance.gc(mutator);
+    assert!(is_protected(dead.to_address()));
+    assert!(!is_protected(instance.root(root).to_address()));
+    assert_eq!(unsafe { object_model::payload(instance.root(root)).load::<usize>() }, 42);
+
+    // The allocator starts from the start of the nursery again.
+    let object = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    assert_eq!(object, dead);
+    assert!(!is_protected(object.to_address()));
+}
+
+/// The cells of dead large objects are protected, and unprotected when their chunk is returned to the global pool,
+/// as another space may take the chunk.
+fn check_large_object_space() {
+    let instance = Instance::create("plan=SemiSpace,protect_on_release=true", HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+
+    let survivor = instance.alloc(mutator, 0, LARGE_PAYLOAD_BYTES, AllocationSemantics::Los);
+    let root = instance.add_root(survivor);
+    let dead = instance.alloc(mutator, 0, LARGE_PAYLOAD_BYTES, AllocationSemantics::Los);
+    instance.gc(mutator);
+    assert!(is_protected(dead.to_address()));
+    assert!(!is_protected(survivor.to_address()));
+    assert_eq!(instance.root(root), survivor);
+
+    // The chunk is free when the survivor dies.
+    instance.set_root(root, unsafe { Address::ZERO.to_object_reference() });
+    instance.gc(mutator);
+    assert!(!is_protected(dead.to_address()));
+    assert!(!is_protected(survivor.to_address()));
+}
+
+/// Released pages are protected, so an access through a dangling reference crashes.
+#[test]
+pub fn protect_on_release() {
+    check_nursery();
+    check_large_object_space();
+}
\ No newline at end of file
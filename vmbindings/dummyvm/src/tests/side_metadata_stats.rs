@@ -0,0 +1,28 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::AllocationSemantics;
+use mmtk::MMTK;
+
+#[test]
+pub fn side_metadata_is_accounted() {
+    let mmtk = Box::into_raw(Box::new(
+        MMTK::<DummyVM>::with_options("plan=Immix").unwrap(),
+    ));
+    memory_manager::gc_init(unsafe { &mut *mmtk }, 20 * 1024 * 1024);
+    let mmtk = unsafe { &*mmtk };
+
+    let mut mutator = memory_manager::bind_mutator(mmtk, VMMutatorThread(VMThread::UNINITIALIZED));
+    for _ in 0..1000 {
+        let addr = memory_manager::alloc(&mut mutator, 16, 8, 0, AllocationSemantics::Default);
+        assert!(!addr.is_zero());
+    }
+
+    // Allocating into Immix blocks maps the Immix line and block tables.
+    let stats = memory_manager::side_metadata_stats(mmtk);
+    for name in &["ImmixLineMark", "ImmixBlockMark"] {
+        let spec = stats.iter().find(|s| s.name == *name).unwrap();
+        assert!(!spec.is_global);
+        assert!(spec.committed_bytes > 0);
+    }
+}
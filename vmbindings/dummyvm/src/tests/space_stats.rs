@@ -0,0 +1,57 @@
+use crate::instance::Instance;
+use mmtk::memory_manager;
+use mmtk::util::Address;
+use mmtk::AllocationSemantics;
+use mmtk::SpaceStats;
+
+const SMALL_OBJECTS: usize = 1024;
+const SMALL_PAYLOAD_BYTES: usize = 1024;
+const LARGE_PAYLOAD_BYTES: usize = 1024 * 1024;
+// Each group of objects takes at least 1MB, i.e. 256 pages.
+const MIN_PAGES: usize = 256;
+
+fn stats_of(instance: &Instance, name: &str) -> SpaceStats {
+    memory_manager::space_stats(instance.mmtk())
+        .into_iter()
+        .find(|stats| stats.name == name)
+        .unwrap()
+}
+
+fn contains(stats: &SpaceStats, addr: Address) -> bool {
+    stats.regions.iter().any(|&(start, end)| start <= addr && addr < end)
+}
+
+/// The stats of the spaces we allocate into grow by the pages of the objects, and their regions include the objects.
+#[test]
+pub fn space_stats_after_allocation() {
+    let instance = Instance::create("plan=SemiSpace", 64 * 1024 * 1024);
+    let mutator = instance.bind_mutator();
+    let copyspace_before = stats_of(instance, "copyspace0");
+    let los_before = stats_of(instance, "los");
+
+    let small: Vec<_> = (0..SMALL_OBJECTS)
+        .map(|_| instance.alloc(mutator, 0, SMALL_PAYLOAD_BYTES, AllocationSemantics::Default))
+        .collect();
+    let large = instance.alloc(mutator, 0, LARGE_PAYLOAD_BYTES, AllocationSemantics::Los);
+
+    // The objects are allocated in the to-space, which is copyspace0 before the first GC.
+    let copyspace = stats_of(instance, "copyspace0");
+    assert!(small.iter().all(|object| contains(&copyspace, object.to_address())));
+    assert!(copyspace.reserved_pages >= copyspace_before.reserved_pages + MIN_PAGES);
+    assert!(copyspace.committed_pages >= copyspace_before.committed_pages + MIN_PAGES);
+    let copyspace1 = stats_of(instance, "copyspace1");
+    assert!(!small.iter().any(|object| contains(&copyspace1, object.to_address())));
+
+    let los = stats_of(instance, "los");
+    assert!(contains(&los, large.to_address()));
+    assert!(los.reserved_pages >= los_before.reserved_pages + MIN_PAGES);
+    assert!(los.committed_pages >= los_before.committed_pages + MIN_PAGES);
+
+    // A contiguous space has one region for its whole range. A discontiguous space (on 32 bits) has the chunks it owns.
+    for stats in memory_manager::space_stats(instance.mmtk()) {
+        if stats.contiguous {
+            assert_eq!(stats.regions.len(), 1, "{}", stats.name);
+        }
+        assert!(stats.regions.iter().all(|&(start, end)| start < end), "{}", stats.name);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use crate::instance::Instance;
+use mmtk::AllocationSemantics;
+
+const HEAP_SIZE: usize = 20 * 1024 * 1024;
+const INTERVAL: usize = 100;
+const ALLOCATIONS: usize = 1000;
+
+/// Allocate small objects, and return the allocations at which a GC happened (counted from 1).
+fn gc_points(options: &str) -> Vec<usize> {
+    let instance = Instance::create(options, HEAP_SIZE);
+    let mutator = instance.bind_mutator();
+    let mut points = vec![];
+    for i in 1..=ALLOCATIONS {
+        let gc_count = instance.gc_count();
+        // These allocations fit in the current block or free list, so they would not leave the fast path without
+        // the stress test.
+        instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+        if instance.gc_count() != gc_count {
+            assert_eq!(instance.gc_count(), gc_count + 1);
+            points.push(i);
+        }
+    }
+    points
+}
+
+#[test]
+pub fn stress_gc() {
+    // The bump pointer, Immix and free-list allocators
+    let every_interval: Vec<usize> = (1..=ALLOCATIONS / INTERVAL).map(|i| i * INTERVAL).collect();
+    for plan in &["SemiSpace", "Immix", "NativeMarkSweep"] {
+        let points = gc_points(&format!("plan={},stress_gc_interval={}", plan, INTERVAL));
+        assert_eq!(points, every_interval, "{}", plan);
+    }
+
+    // A log of random GC points is replayed.
+    let log = std::env::temp_dir().join(format!("mmtk-dummyvm-stress-gc-{}.log", std::process::id()));
+    let log = log.to_str().unwrap();
+    let points = gc_points(&format!(
+        "plan=Immix,stress_gc_interval={},stress_gc_seed=42,stress_gc_log={}",
+        INTERVAL, log
+    ));
+    assert!(!points.is_empty());
+    assert_ne!(points, every_interval);
+    assert_eq!(gc_points(&format!("plan=Immix,stress_gc_replay={}", log)), points);
+    std::fs::remove_file(log).unwrap();
+}
\ No newline at end of file
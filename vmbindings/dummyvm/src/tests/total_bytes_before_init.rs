@@ -0,0 +1,22 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::MMTK;
+
+#[test]
+pub fn total_bytes_before_init() {
+    const HEAP_SIZE: usize = 64 * 1024 * 1024;
+    let mmtk = Box::into_raw(Box::new(
+        MMTK::<DummyVM>::with_options("plan=SemiSpace,gc_trigger=PauseTime").unwrap(),
+    ));
+
+    // There is no GC trigger before gc_init(), so there is no heap yet.
+    let mmtk_ref = unsafe { &*mmtk };
+    assert_eq!(memory_manager::total_bytes(mmtk_ref), 0);
+    assert_eq!(memory_manager::free_bytes(mmtk_ref), 0);
+    assert_eq!(memory_manager::used_bytes(mmtk_ref), 0);
+
+    memory_manager::gc_init(unsafe { &mut *mmtk }, HEAP_SIZE);
+    let mmtk_ref = unsafe { &*mmtk };
+    assert_eq!(memory_manager::total_bytes(mmtk_ref), HEAP_SIZE);
+    assert!(memory_manager::free_bytes(mmtk_ref) <= HEAP_SIZE);
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::AllocationSemantics;
+
+#[test]
+pub fn verify_heap_in_gc() {
+    // The objects of MarkSweep are allocated by malloc, and have no SFT entries.
+    let instance = Instance::create("plan=MarkSweep,verify_heap=true", 20 * 1024 * 1024);
+    let mutator = instance.bind_mutator();
+
+    // A list of objects from a root, with some garbage in between.
+    let head = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+    let root = instance.add_root(head);
+    let mut tail = head;
+    for i in 0..1000usize {
+        let next = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+        unsafe { object_model::payload(next).store(i) };
+        instance.write_ref(mutator, tail, 0, next);
+        tail = next;
+        instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+    }
+    let large = instance.alloc(mutator, 1, 64 * 1024, AllocationSemantics::Los);
+    instance.write_ref(mutator, large, 0, head);
+    instance.add_root(large);
+
+    // The verifier aborts the process at the end of a GC if it finds a bad edge.
+    instance.gc(mutator);
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 2);
+
+    let mut object = object_model::get_ref(instance.root(root), 0);
+    for i in 0..1000usize {
+        assert_eq!(unsafe { object_model::payload(object).load::<usize>() }, i);
+        object = object_model::get_ref(object, 0);
+    }
+    assert!(object.is_null());
+}
@@ -0,0 +1,53 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::AllocationSemantics;
+use std::os::unix::process::ExitStatusExt;
+use std::process::Command;
+
+/// Set in the child process that misses the write barrier.
+const MISS_BARRIER_ENV: &str = "MMTK_DUMMYVM_MISS_BARRIER";
+
+/// Make a mature object refer to a nursery object, and run a nursery GC.
+fn write_to_mature_object(miss_barrier: bool) {
+    let instance = Instance::create("plan=GenCopy,verify_remset=true", 20 * 1024 * 1024);
+    let mutator = instance.bind_mutator();
+
+    let parent = instance.alloc(mutator, 1, 8, AllocationSemantics::Default);
+    let root = instance.add_root(parent);
+    // The parent is promoted.
+    instance.gc(mutator);
+    let parent = instance.root(root);
+
+    let child = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    unsafe { object_model::payload(child).store(42usize) };
+    if miss_barrier {
+        unsafe { object_model::ref_slot(parent, 0).store(child) };
+    } else {
+        instance.write_ref(mutator, parent, 0, child);
+    }
+    // The verifier aborts the process if the barrier was missed.
+    instance.gc(mutator);
+    assert_eq!(instance.gc_count(), 2);
+    let child = object_model::get_ref(instance.root(root), 0);
+    assert_eq!(unsafe { object_model::payload(child).load::<usize>() }, 42);
+}
+
+#[test]
+pub fn verify_remset() {
+    if std::env::var(MISS_BARRIER_ENV).is_ok() {
+        write_to_mature_object(true);
+        return;
+    }
+
+    // The verifier aborts the process, so we miss the barrier in a child process that only runs this test.
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(&["--exact", "tests::verify_remset::verify_remset", "--nocapture", "--test-threads=1"])
+        .env(MISS_BARRIER_ENV, "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.signal(), Some(libc::SIGABRT), "{}", stderr);
+    assert!(stderr.contains("The write barrier was missed"), "{}", stderr);
+
+    write_to_mature_object(false);
+}
\ No newline at end of file
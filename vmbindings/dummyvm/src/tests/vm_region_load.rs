@@ -0,0 +1,58 @@
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::Address;
+use mmtk::VMRegionError;
+use mmtk::MMTK;
+use std::io::Write;
+
+// The size of a chunk, which VM regions are aligned to.
+const CHUNK_BYTES: usize = 4 << 20;
+
+#[test]
+pub fn load_vm_region_from_file() {
+    let mmtk = Box::into_raw(Box::new(MMTK::<DummyVM>::new()));
+    memory_manager::gc_init(unsafe { &mut *mmtk }, 20 * 1024 * 1024);
+    let mmtk = unsafe { &*mmtk };
+
+    // A fake boot image
+    let path = std::env::temp_dir().join(format!("mmtk_vm_region_{}", std::process::id()));
+    let image: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+    std::fs::File::create(&path)
+        .unwrap()
+        .write_all(&image)
+        .unwrap();
+
+    // Below the heap range
+    let start = unsafe { Address::from_usize(0x100_0000_0000) };
+    let size = memory_manager::load_vm_region(mmtk, "boot", path.to_str().unwrap(), start).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(size, CHUNK_BYTES);
+    assert!(memory_manager::is_in_vm_region(mmtk, start));
+    assert!(memory_manager::is_in_vm_region(mmtk, start + size - 1));
+    assert!(!memory_manager::is_in_vm_region(mmtk, start + size));
+    for (i, byte) in image.iter().enumerate() {
+        assert_eq!(unsafe { (start + i).load::<u8>() }, *byte);
+    }
+    // The mapping is private, and writable.
+    unsafe { start.store::<u8>(42) };
+    memory_manager::record_vm_region_write(mmtk, unsafe { start.to_object_reference() });
+
+    // Invalid regions
+    assert!(matches!(
+        memory_manager::register_vm_region(mmtk, "unaligned", start + size + 8usize, CHUNK_BYTES),
+        Err(VMRegionError::Unaligned(..))
+    ));
+    assert!(matches!(
+        memory_manager::register_vm_region(mmtk, "overlap", start, CHUNK_BYTES),
+        Err(VMRegionError::Overlap(..))
+    ));
+    assert!(matches!(
+        memory_manager::register_vm_region(
+            mmtk,
+            "heap",
+            memory_manager::starting_heap_address(),
+            CHUNK_BYTES
+        ),
+        Err(VMRegionError::InvalidRange(..))
+    ));
+}
@@ -0,0 +1,39 @@
+use crate::instance::Instance;
+use crate::object_model;
+use mmtk::memory_manager;
+use mmtk::util::memory;
+use mmtk::util::Address;
+use mmtk::AllocationSemantics;
+
+// The size of a chunk, which VM regions are aligned to.
+const CHUNK_BYTES: usize = 4 << 20;
+
+#[test]
+pub fn vm_region_objects_are_roots() {
+    let instance = Instance::create("plan=SemiSpace", 20 * 1024 * 1024);
+    let mmtk = instance.mmtk();
+    let mutator = instance.bind_mutator();
+
+    // A VM region below the heap range, with one object in it.
+    let start = unsafe { Address::from_usize(0x110_0000_0000) };
+    memory::dzmmap_noreplace(start, CHUNK_BYTES).unwrap();
+    memory_manager::register_vm_region(mmtk, "roots", start, CHUNK_BYTES).unwrap();
+    let holder = object_model::init_object(start, object_model::object_size(1, 0), 1);
+
+    let target = instance.alloc(mutator, 0, 8, AllocationSemantics::Default);
+    unsafe { object_model::payload(target).store::<usize>(42) };
+    unsafe { object_model::ref_slot(holder, 0).store(target) };
+    memory_manager::record_vm_region_write(mmtk, holder);
+
+    // The object is scanned in every GC while it refers to the heap, even though it is only recorded once.
+    // SemiSpace moves the target in each GC, so the slot must be updated each time.
+    let mut last = target;
+    for gc in 1..=3 {
+        instance.gc(mutator);
+        assert_eq!(instance.gc_count(), gc);
+        let target = object_model::get_ref(holder, 0);
+        assert_ne!(target, last);
+        assert_eq!(unsafe { object_model::payload(target).load::<usize>() }, 42);
+        last = target;
+    }
+}
\ No newline at end of file